@@ -39,6 +39,7 @@ void main() async {
   // Rustブリッジの初期化（エラーハンドリング付き）
   try {
     await RustLib.init();
+    AppLogger.attachRustLogs();
     AppLogger.info('Rust初期化成功', tag: 'INIT');
   } catch (e, stackTrace) {
     AppLogger.error('Rust初期化エラー', error: e, stackTrace: stackTrace, tag: 'INIT');
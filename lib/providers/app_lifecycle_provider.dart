@@ -42,6 +42,7 @@ class AppLifecycleNotifier extends StateNotifier<AppLifecycleState> with Widgets
 
     // フォアグラウンドに復帰した場合
     if (lifecycleState == AppLifecycleState.resumed) {
+      _ref.read(nostrServiceProvider).setAppForeground(true);
       _onAppResumed();
     } else if (lifecycleState == AppLifecycleState.paused) {
       _onAppPaused();
@@ -101,7 +102,8 @@ class AppLifecycleNotifier extends StateNotifier<AppLifecycleState> with Widgets
   /// アプリがバックグラウンドに移行した時の処理
   void _onAppPaused() {
     AppLogger.debug('📱 App paused');
-    // 必要に応じてクリーンアップ処理を追加
+    // バックグラウンド中はRust側のリレー再接続を止める
+    _ref.read(nostrServiceProvider).setAppForeground(false);
   }
 
   /// 公開鍵を復元する（Amberモード対応）
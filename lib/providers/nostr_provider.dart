@@ -5,6 +5,7 @@ import '../services/logger_service.dart';
 import 'package:path_provider/path_provider.dart';
 import '../services/logger_service.dart';
 import '../bridge_generated.dart/api.dart' as rust_api;
+import '../bridge_generated.dart/reconnect.dart' as rust_reconnect;
 import '../services/logger_service.dart';
 import '../models/todo.dart';
 import '../services/logger_service.dart';
@@ -521,6 +522,14 @@ class NostrService {
     }
   }
 
+  /// アプリの表示状態をRust側の再接続監視に通知
+  /// バックグラウンド中は再接続を止め、フォアグラウンド復帰時に切断中のリレーだけ再接続する
+  void setAppForeground(bool foreground) {
+    rust_api.setAppLifecycle(
+      state: foreground ? rust_reconnect.AppLifecycle.foreground : rust_reconnect.AppLifecycle.background,
+    );
+  }
+
   // ========================================
   // マイグレーション関連API
   // ========================================
@@ -1,5 +1,9 @@
+import 'dart:async';
+
 import 'package:talker_flutter/talker_flutter.dart';
 import 'package:flutter/foundation.dart';
+import '../bridge_generated.dart/api.dart' as rust_api;
+import '../bridge_generated.dart/logging.dart' as rust_log;
 
 /// グローバルTalkerインスタンス
 /// デバッグモード時のみログを有効化
@@ -95,5 +99,29 @@ class AppLogger {
       );
     }
   }
-}
 
+  static StreamSubscription<rust_log.LogRecord>? _rustLogSubscription;
+
+  /// Rust側のログをTalkerに転送
+  /// メッセージはRust側でマスク済み。RustLib.init()の後に呼ぶこと
+  static void attachRustLogs() {
+    if (!kDebugMode) return;
+
+    rust_api.setLogLevel(level: rust_log.LogLevel.debug);
+    _rustLogSubscription?.cancel();
+    _rustLogSubscription = rust_api.createLogStream().listen((record) {
+      final message = '[RUST:${record.target}] ${record.message}';
+      switch (record.level) {
+        case rust_log.LogLevel.error:
+          talker.error(message);
+        case rust_log.LogLevel.warn:
+          talker.warning(message);
+        case rust_log.LogLevel.info:
+          talker.info(message);
+        case rust_log.LogLevel.debug:
+        case rust_log.LogLevel.trace:
+          talker.debug(message);
+      }
+    });
+  }
+}
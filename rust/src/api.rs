@@ -4,6 +4,13 @@ use nostr_sdk::nips::nip44; // NIP-44暗号化を明示的にインポート
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
+use crate::error::MeisoError;
+use crate::logging::{log_debug, log_error, log_info, log_trace, log_warn, note_id};
+use crate::proxy::{ProxyProbeResult, ProxyRouting, ProxySettings, RelayRoute};
+use crate::reconnect::{ReconnectPolicy, ReconnectSupervisor};
+use crate::relay_status::{RelayErrorLog, RelayStatusReport};
+use crate::secret::SecretString;
+use crate::session::SessionLocked;
 use crate::{NOSTR_CLIENTS, DEFAULT_CLIENT_ID};
 
 /// クライアントモード
@@ -15,6 +22,78 @@ pub enum ClientMode {
     Amber { public_key_hex: String },
 }
 
+/// クライアントのタイムアウト・接続動作の設定
+///
+/// 初期化時に省略した場合は`default_client_config`の値（従来の固定値）が使われる
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClientConfig {
+    /// 初期化時にリレー接続を待つ時間（秒）
+    pub connect_timeout_secs: u64,
+    /// 初期化時に接続完了を待つか（falseなら接続はバックグラウンドで続け、すぐに戻る）
+    pub wait_for_connection: bool,
+    /// 再接続を待つ時間（秒）
+    pub reconnect_timeout_secs: u64,
+    /// 切断されたリレーを再接続するまでの最初の待ち時間（ミリ秒、失敗するたびに倍になる）
+    pub reconnect_base_delay_ms: u64,
+    /// 再接続の待ち時間の上限（ミリ秒）
+    pub reconnect_max_delay_ms: u64,
+    /// イベント取得のタイムアウト（秒）
+    pub fetch_timeout_secs: u64,
+    /// イベント送信のタイムアウト（秒）
+    pub send_timeout_secs: u64,
+    /// 取得に失敗したときの再試行回数
+    pub fetch_retries: u32,
+    /// 送信に失敗（全リレー失敗・タイムアウト）したときの再試行回数
+    pub send_retries: u32,
+    /// 再試行までの待ち時間（ミリ秒）
+    pub retry_delay_ms: u64,
+    /// プロキシ指定時にどのリレーをプロキシ経由にするか（`.onion`は常にプロキシ経由）
+    pub proxy_routing: ProxyRouting,
+    /// 厳格モード（Tor有効時のフェイルクローズ）
+    ///
+    /// プロキシが必須になり、すべてのリレーをプロキシ経由にする。
+    /// 接続・再接続の前にSOCKS5の応答を確認し、応答がなければ`ProxyUnavailable`を返してリレーには接続しない
+    pub strict_proxy: bool,
+}
+
+impl ClientConfig {
+    /// モードとプロキシの有無に応じたデフォルト値
+    /// Tor経由は接続に時間がかかるので長めに待つ
+    pub fn defaults_for(amber: bool, with_proxy: bool) -> Self {
+        let connect_timeout_secs = match (amber, with_proxy) {
+            (false, false) => 5,
+            (false, true) => 15,
+            (true, false) => 10,
+            (true, true) => 20,
+        };
+        Self {
+            connect_timeout_secs,
+            wait_for_connection: true,
+            reconnect_timeout_secs: 10,
+            reconnect_base_delay_ms: 1000,
+            reconnect_max_delay_ms: 60_000,
+            fetch_timeout_secs: 10,
+            send_timeout_secs: 10,
+            fetch_retries: 0,
+            send_retries: 0,
+            retry_delay_ms: 1000,
+            proxy_routing: ProxyRouting::All,
+            strict_proxy: false,
+        }
+    }
+
+    fn retry_delay(&self) -> Duration {
+        Duration::from_millis(self.retry_delay_ms)
+    }
+
+    fn reconnect_policy(&self) -> ReconnectPolicy {
+        ReconnectPolicy {
+            base_delay: Duration::from_millis(self.reconnect_base_delay_ms),
+            max_delay: Duration::from_millis(self.reconnect_max_delay_ms.max(self.reconnect_base_delay_ms)),
+        }
+    }
+}
+
 /// イベント送信結果
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EventSendResult {
@@ -158,75 +237,87 @@ pub struct MeisoNostrClient {
     pub(crate) client: Client,
     /// クライアントモード
     pub(crate) mode: ClientMode,
+    /// 公開鍵（セッションのロック中も参照できるように秘密鍵とは別に保持）
+    pub(crate) public_key: PublicKey,
+    /// ロック解除時に秘密鍵を読み直す鍵ストア（秘密鍵を直接渡して初期化した場合はNone）
+    pub(crate) key_source: Option<String>,
+    /// タイムアウト・再試行の設定
+    pub(crate) config: ClientConfig,
+    /// このクライアントのSOCKS5プロキシ（リレーごとに経路を決める）
+    pub(crate) proxy: Option<ProxySettings>,
+    /// リレーごとの最後のエラー
+    pub(crate) relay_errors: RelayErrorLog,
+    /// 切断されたリレーの再接続を監視するタスク
+    pub(crate) supervisor: ReconnectSupervisor,
+}
+
+impl std::fmt::Debug for MeisoNostrClient {
+    /// 秘密鍵はログに出さない
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MeisoNostrClient")
+            .field("keys", &self.keys.as_ref().map(|_| "[REDACTED]"))
+            .field("mode", &self.mode)
+            .field("key_source", &self.key_source)
+            .field("config", &self.config)
+            .field("proxy", &self.proxy)
+            .finish_non_exhaustive()
+    }
 }
 
 impl MeisoNostrClient {
     /// 新しいクライアントを作成（秘密鍵から）
-    pub async fn new(secret_key_hex: &str, relays: Vec<String>) -> Result<Self> {
-        Self::new_with_proxy(secret_key_hex, relays, None).await
+    pub async fn new(secret_key: &SecretString, relays: Vec<String>) -> Result<Self> {
+        Self::new_with_proxy(secret_key, relays, None, ClientConfig::defaults_for(false, false)).await
     }
 
     /// 新しいクライアントを作成（秘密鍵 + プロキシオプション）
     pub async fn new_with_proxy(
-        secret_key_hex: &str, 
+        secret_key: &SecretString, 
         relays: Vec<String>,
         proxy_url: Option<String>,
+        config: ClientConfig,
     ) -> Result<Self> {
-        println!("Parsing secret key (format: {})", 
-            if secret_key_hex.starts_with("nsec") { "nsec" } else { "hex" });
+        let format = if secret_key.expose_secret().starts_with("nsec") { "nsec" } else { "hex" };
+        log_debug!("Parsing secret key (format: {})", format);
         
-        let keys = Keys::parse(secret_key_hex)
-            .map_err(|e| anyhow::anyhow!("秘密鍵のパースに失敗 ({}): {}. フォーマットを確認してください (hex or nsec1...)", 
-                if secret_key_hex.starts_with("nsec") { "nsec形式" } else { "hex形式" }, e))?;
+        let keys = Keys::parse(secret_key.expose_secret())
+            .map_err(|e| MeisoError::invalid_key(format!("{} ({} format, expected hex or nsec1...)", e, format)))?;
 
-        // プロキシ設定（環境変数経由）
-        if let Some(ref proxy) = proxy_url {
-            println!("🔐 Tor/Proxy経由で接続します: {}", proxy);
-            
-            // SOCKS5プロキシを環境変数に設定
-            // nostr-sdkは内部でこれらの環境変数を使用する可能性がある
-            std::env::set_var("all_proxy", proxy);
-            std::env::set_var("ALL_PROXY", proxy);
-            std::env::set_var("socks_proxy", proxy);
-            std::env::set_var("SOCKS_PROXY", proxy);
-            
-            println!("✅ プロキシ環境変数を設定: {}", proxy);
-        }
+        // プロキシ設定（このクライアントのリレー接続だけに適用）
+        let proxy = Self::parse_proxy(proxy_url.as_deref(), &config).await?;
 
         let client = Client::new(keys.clone());
 
         // リレー追加
+        let relay_errors = RelayErrorLog::default();
         for relay_url in &relays {
-            println!("Adding relay: {}", relay_url);
-            match client.add_relay(relay_url).await {
-                Ok(_) => println!("✅ Relay added: {}", relay_url),
+            log_debug!("Adding relay: {}", relay_url);
+            match Self::add_routed_relay(&client, proxy.as_ref(), relay_url).await {
+                Ok(_) => log_debug!("✅ Relay added: {}", relay_url),
                 Err(e) => {
-                    eprintln!("⚠️ Failed to add relay {}: {}", relay_url, e);
+                    log_warn!("⚠️ Failed to add relay {}: {}", relay_url, e);
+                    relay_errors.record(relay_url, &e);
                     // リレー追加失敗は続行（他のリレーで接続を試みる）
                 }
             }
         }
 
         // リレーに接続（タイムアウト付きで待機）
-        let timeout_sec = if proxy_url.is_some() { 15 } else { 5 }; // Tor経由は時間がかかる
-        println!("Connecting to relays{}...", 
+        log_info!("Connecting to relays{}...", 
             if proxy_url.is_some() { " (via proxy)" } else { "" });
-        
-        match tokio::time::timeout(
-            std::time::Duration::from_secs(timeout_sec), 
-            client.connect()
-        ).await {
-            Ok(_) => println!("✅ Connected to relays"),
-            Err(_) => {
-                eprintln!("⚠️ Relay connection timeout ({}s) - continuing offline mode", timeout_sec);
-                // タイムアウトしても続行（オフライン対応）
-            }
-        }
+        Self::connect_relays(&client, &config).await;
+        let supervisor = ReconnectSupervisor::start(client.clone(), relay_errors.clone(), config.reconnect_policy());
 
         Ok(Self { 
+            public_key: keys.public_key(),
             keys: Some(keys), 
             client,
             mode: ClientMode::SecretKey,
+            key_source: None,
+            config,
+            proxy,
+            relay_errors,
+            supervisor,
         })
     }
     
@@ -235,25 +326,17 @@ impl MeisoNostrClient {
         public_key_hex: String,
         relays: Vec<String>,
         proxy_url: Option<String>,
+        config: ClientConfig,
     ) -> Result<Self> {
-        println!("🟡 Creating Amber mode client (no secret key)");
+        log_info!("🟡 Creating Amber mode client (no secret key)");
         
-        // プロキシ設定（環境変数経由）
-        if let Some(ref proxy) = proxy_url {
-            println!("🔐 Tor/Proxy経由で接続します (Amber mode): {}", proxy);
-            
-            std::env::set_var("all_proxy", proxy);
-            std::env::set_var("ALL_PROXY", proxy);
-            std::env::set_var("socks_proxy", proxy);
-            std::env::set_var("SOCKS_PROXY", proxy);
-            
-            println!("✅ プロキシ環境変数を設定 (Amber mode): {}", proxy);
-        }
+        // プロキシ設定（このクライアントのリレー接続だけに適用）
+        let proxy = Self::parse_proxy(proxy_url.as_deref(), &config).await?;
         
         // Amberモードでは秘密鍵なしでクライアントを作成
         // nostr-sdk 0.30以降はPublicKeyだけでClientを作成可能
-        let _public_key = PublicKey::from_hex(&public_key_hex)
-            .context("Failed to parse public key")?;
+        let public_key = PublicKey::from_hex(&public_key_hex)
+            .map_err(MeisoError::invalid_key)?;
         
         // Keysをpublic keyだけから作成する方法がないため、
         // ダミーの秘密鍵を生成するが、使わないことを明示
@@ -261,47 +344,106 @@ impl MeisoNostrClient {
         let client = Client::new(dummy_keys);
         
         // リレー追加
+        let relay_errors = RelayErrorLog::default();
         for relay_url in &relays {
-            println!("Adding relay: {}", relay_url);
-            match client.add_relay(relay_url).await {
-                Ok(_) => println!("✅ Relay added: {}", relay_url),
+            log_debug!("Adding relay: {}", relay_url);
+            match Self::add_routed_relay(&client, proxy.as_ref(), relay_url).await {
+                Ok(_) => log_debug!("✅ Relay added: {}", relay_url),
                 Err(e) => {
-                    eprintln!("⚠️ Failed to add relay {}: {}", relay_url, e);
+                    log_warn!("⚠️ Failed to add relay {}: {}", relay_url, e);
+                    relay_errors.record(relay_url, &e);
                 }
             }
         }
         
         // リレーに接続（タイムアウト付き）
-        let timeout_sec = if proxy_url.is_some() { 20 } else { 10 };
-        println!("🔌 Connecting to relays (Amber mode){}...",
+        log_info!("🔌 Connecting to relays (Amber mode){}...",
             if proxy_url.is_some() { " (via proxy)" } else { "" });
-        
-        match tokio::time::timeout(
-            std::time::Duration::from_secs(timeout_sec), 
-            client.connect()
-        ).await {
-            Ok(_) => println!("✅ Connected to relays (Amber mode)"),
-            Err(_) => {
-                eprintln!("⚠️ Relay connection timeout ({}s) in Amber mode - continuing anyway", timeout_sec);
-            }
-        }
+        Self::connect_relays(&client, &config).await;
+        let supervisor = ReconnectSupervisor::start(client.clone(), relay_errors.clone(), config.reconnect_policy());
         
         Ok(Self {
             keys: None, // Amberモードでは秘密鍵なし
             client,
             mode: ClientMode::Amber { public_key_hex },
+            public_key,
+            key_source: None,
+            config,
+            proxy,
+            relay_errors,
+            supervisor,
         })
     }
 
+    /// プロキシURLを解析（Noneならプロキシなし）
+    /// 厳格モードではプロキシが応答することまで確認する
+    async fn parse_proxy(proxy_url: Option<&str>, config: &ClientConfig) -> Result<Option<ProxySettings>> {
+        let Some(proxy_url) = proxy_url else {
+            if config.strict_proxy {
+                return Err(MeisoError::invalid_argument("strict proxy mode requires a proxy URL").into());
+            }
+            return Ok(None);
+        };
+        let proxy = ProxySettings::new(proxy_url, config.proxy_routing).await?.strict(config.strict_proxy);
+        log_debug!("🔐 Tor/Proxy経由で接続します: {} ({:?}, strict={})", proxy.addr, proxy.routing, proxy.strict);
+
+        if let Err(e) = proxy
+            .ensure_available(Duration::from_secs(config.connect_timeout_secs))
+            .await
+        {
+            log_error!("❌ Proxy unavailable - not connecting to any relay: {}", e);
+            return Err(e);
+        }
+        Ok(Some(proxy))
+    }
+
+    /// 経路設定に従ってリレーを追加
+    /// `.onion`のリレーはプロキシがなければ追加しない（直接接続するとアドレスがDNSに漏れる）
+    ///
+    /// 再接続は`ReconnectSupervisor`が行うので、nostr-sdkの自動再接続は無効にする
+    async fn add_routed_relay(client: &Client, proxy: Option<&ProxySettings>, relay_url: &str) -> Result<bool> {
+        let opts = match crate::proxy::route_for(proxy, relay_url) {
+            RelayRoute::Direct => RelayOptions::new(),
+            RelayRoute::Proxy(addr) => RelayOptions::new().connection_mode(ConnectionMode::Proxy(addr)),
+            RelayRoute::Blocked => {
+                return Err(MeisoError::invalid_argument(format!(
+                    "{} is an onion relay but no proxy is configured",
+                    relay_url
+                ))
+                .into());
+            }
+        };
+        Ok(client.pool().add_relay(relay_url, opts.reconnect(false)).await?)
+    }
+
+    /// リレーに接続
+    /// タイムアウトしても続行する（オフライン対応）。待たない設定なら接続をバックグラウンドで開始してすぐ戻る
+    async fn connect_relays(client: &Client, config: &ClientConfig) {
+        if !config.wait_for_connection {
+            let client = client.clone();
+            tokio::spawn(async move { client.connect().await });
+            log_info!("🔌 Connecting to relays in the background");
+            return;
+        }
+
+        let timeout = Duration::from_secs(config.connect_timeout_secs);
+        match tokio::time::timeout(timeout, client.connect()).await {
+            Ok(_) => log_info!("✅ Connected to relays"),
+            Err(_) => {
+                log_warn!("⚠️ Relay connection timeout ({}s) - continuing offline mode", config.connect_timeout_secs);
+            }
+        }
+    }
+
+    /// タイムアウト・再試行の設定
+    pub fn config(&self) -> &ClientConfig {
+        &self.config
+    }
+
     /// 公開鍵を取得（hex形式）
     pub fn public_key_hex(&self) -> String {
         match &self.mode {
-            ClientMode::SecretKey => {
-                self.keys.as_ref()
-                    .expect("SecretKey mode must have keys")
-                    .public_key()
-                    .to_hex()
-            }
+            ClientMode::SecretKey => self.public_key.to_hex(),
             ClientMode::Amber { public_key_hex } => public_key_hex.clone(),
         }
     }
@@ -310,10 +452,7 @@ impl MeisoNostrClient {
     pub fn public_key_npub(&self) -> String {
         match &self.mode {
             ClientMode::SecretKey => {
-                let pubkey = self.keys.as_ref()
-                    .expect("SecretKey mode must have keys")
-                    .public_key();
-                pubkey.to_bech32().unwrap_or_else(|_| pubkey.to_hex())
+                self.public_key.to_bech32().unwrap_or_else(|_| self.public_key.to_hex())
             }
             ClientMode::Amber { public_key_hex } => {
                 // hex → npub変換
@@ -335,17 +474,82 @@ impl MeisoNostrClient {
         self.keys.is_some()
     }
 
+    /// セッションがロックされているか（秘密鍵モードで秘密鍵を破棄済み）
+    pub fn is_locked(&self) -> bool {
+        matches!(self.mode, ClientMode::SecretKey) && self.keys.is_none()
+    }
+
+    /// 署名・暗号化に使う秘密鍵を取得
+    /// ロック中は`SessionLocked`エラーを返す
+    pub(crate) fn signing_keys(&self) -> Result<&Keys> {
+        match (&self.keys, &self.mode) {
+            (Some(keys), _) => Ok(keys),
+            (None, ClientMode::SecretKey) => Err(SessionLocked.into()),
+            (None, ClientMode::Amber { .. }) => {
+                Err(MeisoError::unsupported_in_amber_mode("signing with a secret key").into())
+            }
+        }
+    }
+
+    /// 秘密鍵をメモリから破棄（リレー接続は維持するので読み取りは継続できる）
+    pub(crate) async fn lock(&mut self) {
+        if self.keys.take().is_some() {
+            self.client.unset_signer().await;
+        }
+    }
+
+    /// クライアントを停止（Subscriptionを止めてリレーから切断し、秘密鍵を破棄）
+    ///
+    /// `Client`のクローンは内部状態を共有しているので、処理中の他のハンドルも切断される
+    pub(crate) async fn shutdown(&mut self) {
+        self.supervisor.stop();
+        self.client.unsubscribe_all().await;
+        if let Err(e) = self.client.disconnect().await {
+            log_warn!("⚠️ Failed to disconnect relays: {}", e);
+        }
+        self.lock().await;
+    }
+
+    /// 鍵ストアから読み直した秘密鍵を戻す
+    pub(crate) async fn restore_keys(&mut self, keys: Keys) -> Result<()> {
+        if keys.public_key() != self.public_key {
+            return Err(crate::key_store::PublicKeyMismatch {
+                stored: keys.public_key().to_hex(),
+                expected: self.public_key.to_hex(),
+            }
+            .into());
+        }
+        self.client.set_signer(keys.clone()).await;
+        self.keys = Some(keys);
+        Ok(())
+    }
+
     /// イベントをリレーに送信（改善されたエラーハンドリング）
     async fn send_event_with_result(&self, event: Event) -> Result<EventSendResult> {
         let event_id = event.id.to_hex();
+        let timeout = Duration::from_secs(self.config.send_timeout_secs);
+
+        let mut attempt = 0;
+        let result = loop {
+            let result = tokio::time::timeout(timeout, self.client.send_event(event.clone())).await;
+            if matches!(result, Ok(Ok(_))) || attempt >= self.config.send_retries {
+                break result;
+            }
+            attempt += 1;
+            log_warn!("🔁 Retrying event send ({}/{})", attempt, self.config.send_retries);
+            tokio::time::sleep(self.config.retry_delay()).await;
+        };
         
-        match tokio::time::timeout(Duration::from_secs(10), self.client.send_event(event)).await {
+        match result {
             Ok(Ok(send_output)) => {
                 // 成功: nostr-sdkのSendEventOutputから情報を取得
                 let successful = send_output.success.len();
                 let failed = send_output.failed.len();
+                for (relay_url, error) in &send_output.failed {
+                    self.relay_errors.record(relay_url.as_str(), error.as_deref().unwrap_or("unknown error"));
+                }
                 
-                println!("✅ Event sent: {} successful, {} failed", successful, failed);
+                log_info!("✅ Event sent: {} successful, {} failed", successful, failed);
                 
                 Ok(EventSendResult {
                     event_id,
@@ -362,7 +566,7 @@ impl MeisoNostrClient {
             }
             Ok(Err(e)) => {
                 // 送信エラー（全リレー失敗）
-                eprintln!("❌ Failed to send event: {}", e);
+                log_error!("❌ Failed to send event: {}", e);
                 Ok(EventSendResult {
                     event_id,
                     success: false,
@@ -374,14 +578,14 @@ impl MeisoNostrClient {
             }
             Err(_) => {
                 // タイムアウト
-                eprintln!("⏱️ Event send timeout (10s)");
+                log_warn!("⏱️ Event send timeout ({}s)", self.config.send_timeout_secs);
                 Ok(EventSendResult {
                     event_id,
                     success: false,
                     successful_relays: 0,
                     failed_relays: 0,
                     timed_out: true,
-                    error_message: Some("Timeout after 10 seconds".to_string()),
+                    error_message: Some(format!("Timeout after {} seconds", self.config.send_timeout_secs)),
                 })
             }
         }
@@ -392,20 +596,18 @@ impl MeisoNostrClient {
     pub async fn create_todo_list(&self, todos: Vec<TodoData>) -> Result<EventSendResult> {
         // Amberモードでは暗号化/署名ができないのでエラー
         if let ClientMode::Amber { .. } = self.mode {
-            return Err(anyhow::anyhow!(
-                "Cannot create TODO list in Amber mode. Use create_unsigned_encrypted_todo_list_event + Amber signing instead."
-            ));
+            // create_unsigned_encrypted_todo_list_event + Amber署名を使う
+            return Err(MeisoError::unsupported_in_amber_mode("create_todo_list").into());
         }
         
-        let keys = self.keys.as_ref()
-            .context("Secret key required for TODO list creation")?;
+        let keys = self.signing_keys()?;
         
         // Todoをリストごとにグループ化
         let grouped_todos = self.group_todos_by_list(&todos);
         
-        println!("📦 Grouped todos into {} lists", grouped_todos.len());
+        log_debug!("📦 Grouped todos into {} lists", grouped_todos.len());
         for (list_id, list_todos) in &grouped_todos {
-            println!("  - List '{}': {} todos", list_id, list_todos.len());
+            log_debug!("  - List '{}': {} todos", list_id, list_todos.len());
         }
         
         let mut last_result: Option<EventSendResult> = None;
@@ -452,7 +654,7 @@ impl MeisoNostrClient {
                 .sign(keys)
                 .await?;
 
-            println!("📤 Sending TODO list event (d='{}', {} todos)", d_tag_value, list_todos.len());
+            log_info!("📤 Sending TODO list event (d='{}', {} todos)", d_tag_value, list_todos.len());
             
             // リレーに送信
             let result = self.send_event_with_result(event).await?;
@@ -482,13 +684,11 @@ impl MeisoNostrClient {
     /// すべてのリスト（デフォルト + カスタムリスト）から取得
     pub async fn sync_todo_list(&self) -> Result<Vec<TodoData>> {
         if let ClientMode::Amber { .. } = self.mode {
-            return Err(anyhow::anyhow!(
-                "Cannot sync TODO list in Amber mode. Use fetch_encrypted_todo_list_for_pubkey + Amber decryption instead."
-            ));
+            // fetch_encrypted_todo_list_for_pubkey + Amber復号を使う
+            return Err(MeisoError::unsupported_in_amber_mode("sync_todo_list").into());
         }
         
-        let keys = self.keys.as_ref()
-            .context("Secret key required for syncing")?;
+        let keys = self.signing_keys()?;
         
         // すべてのリスト（meiso-todos および meiso-list-*）を取得
         let filter = Filter::new()
@@ -496,19 +696,18 @@ impl MeisoNostrClient {
             .author(keys.public_key());
 
         let events = self
-            .client
-            .fetch_events(vec![filter], Some(Duration::from_secs(10)))
+            .fetch_events(filter)
             .await?;
 
         // EventsをVec<Event>に変換
         let events_vec: Vec<_> = events.into_iter().collect();
 
         if events_vec.is_empty() {
-            println!("⚠️ No TODO lists found");
+            log_info!("⚠️ No TODO lists found");
             return Ok(Vec::new());
         }
 
-        println!("📥 Found {} TODO list events", events_vec.len());
+        log_info!("📥 Found {} TODO list events", events_vec.len());
         
         // 同じd tagを持つイベントが複数ある場合、最新のもの（created_atが最大）のみを保持
         use std::collections::HashMap;
@@ -521,8 +720,8 @@ impl MeisoNostrClient {
                 .and_then(|tag| tag.content())
                 .map(|s| s.to_string());
             
-            println!("🔍 Found event: d_tag={:?}, event_id={}, created_at={}", 
-                d_tag, event.id.to_hex(), event.created_at.as_u64());
+            log_debug!("🔍 Found event: d_tag={:?}, event_id={}, created_at={}", 
+                d_tag, note_id(&event.id), event.created_at.as_u64());
             
             // meiso-todos または meiso-list-* のみを処理（meiso-settings等は除外）
             if let Some(ref d_value) = d_tag {
@@ -530,34 +729,34 @@ impl MeisoNostrClient {
                     // 既存のイベントと比較して、新しい方を保持
                     if let Some(existing_event) = latest_events.get(d_value) {
                         if event.created_at > existing_event.created_at {
-                            println!("🔄 Replacing older event for d='{}' (old: {}, new: {})", 
+                            log_debug!("🔄 Replacing older event for d='{}' (old: {}, new: {})", 
                                 d_value, existing_event.created_at.as_u64(), event.created_at.as_u64());
                             latest_events.insert(d_value.clone(), event);
                         } else {
-                            println!("⏭️  Skipping older event for d='{}' (keeping: {})", 
+                            log_debug!("⏭️  Skipping older event for d='{}' (keeping: {})", 
                                 d_value, existing_event.created_at.as_u64());
                         }
                     } else {
-                        println!("✅ Adding TODO list event: d='{}', event_id={}, created_at={}", 
-                            d_value, event.id.to_hex(), event.created_at.as_u64());
+                        log_debug!("✅ Adding TODO list event: d='{}', event_id={}, created_at={}", 
+                            d_value, note_id(&event.id), event.created_at.as_u64());
                         latest_events.insert(d_value.clone(), event);
                     }
                 } else {
-                    println!("⏭️  Skipping event with d='{}' (not a TODO list)", d_value);
+                    log_debug!("⏭️  Skipping event with d='{}' (not a TODO list)", d_value);
                 }
             } else {
-                println!("⏭️  Skipping event with no d tag");
+                log_debug!("⏭️  Skipping event with no d tag");
             }
         }
         
-        println!("📋 After deduplication: {} unique TODO lists", latest_events.len());
+        log_debug!("📋 After deduplication: {} unique TODO lists", latest_events.len());
         
         let mut all_todos = Vec::new();
         
         // 各リストイベントを復号化してTodoを取得
         for (d_tag, event) in latest_events {
-            println!("✅ Processing TODO list event: d='{}', event_id={}, created_at={}", 
-                d_tag, event.id.to_hex(), event.created_at.as_u64());
+            log_debug!("✅ Processing TODO list event: d='{}', event_id={}, created_at={}", 
+                d_tag, note_id(&event.id), event.created_at.as_u64());
 
             // NIP-44で復号化
             match nip44::decrypt(
@@ -568,23 +767,23 @@ impl MeisoNostrClient {
                 Ok(decrypted) => {
                     match serde_json::from_str::<Vec<TodoData>>(&decrypted) {
                         Ok(todos) => {
-                            println!("✅ Decrypted {} todos from list {:?}", todos.len(), d_tag);
+                            log_debug!("✅ Decrypted {} todos from list {:?}", todos.len(), d_tag);
                             all_todos.extend(todos);
                         }
                         Err(e) => {
-                            eprintln!("❌ Failed to parse TODO list JSON from {:?}: {}", d_tag, e);
+                            log_error!("❌ Failed to parse TODO list JSON from {:?}: {}", d_tag, e);
                             // エラーは無視して次のリストを処理
                         }
                     }
                 }
                 Err(e) => {
-                    eprintln!("❌ Failed to decrypt TODO list {:?}: {}", d_tag, e);
+                    log_error!("❌ Failed to decrypt TODO list {:?}: {}", d_tag, e);
                     // エラーは無視して次のリストを処理
                 }
             }
         }
         
-        println!("✅ Total todos synced from all lists: {}", all_todos.len());
+        log_info!("✅ Total todos synced from all lists: {}", all_todos.len());
         Ok(all_todos)
     }
 
@@ -596,11 +795,10 @@ impl MeisoNostrClient {
     /// アプリ設定をNostrイベントとして作成（Kind 30078 - NIP-78）
     pub async fn create_app_settings(&self, settings: AppSettings) -> Result<EventSendResult> {
         if let ClientMode::Amber { .. } = self.mode {
-            return Err(anyhow::anyhow!("Cannot create app settings in Amber mode"));
+            return Err(MeisoError::unsupported_in_amber_mode("create_app_settings").into());
         }
         
-        let keys = self.keys.as_ref()
-            .context("Secret key required")?;
+        let keys = self.signing_keys()?;
         
         let settings_json = serde_json::to_string(&settings)?;
 
@@ -627,11 +825,10 @@ impl MeisoNostrClient {
         // リレーに送信するイベントをJSONとしてログ出力
         match serde_json::to_string_pretty(&event.as_json()) {
             Ok(event_json) => {
-                println!("📤 Nostr app settings event (Kind 30078) to relay:");
-                println!("{}", event_json);
+                log_trace!("📤 Nostr app settings event (Kind 30078) to relay:\n{}", event_json);
             }
             Err(e) => {
-                eprintln!("⚠️ Failed to serialize event to JSON: {}", e);
+                log_warn!("⚠️ Failed to serialize event to JSON: {}", e);
             }
         }
 
@@ -642,11 +839,10 @@ impl MeisoNostrClient {
     /// アプリ設定をNostrから同期（Kind 30078）
     pub async fn sync_app_settings(&self) -> Result<Option<AppSettings>> {
         if let ClientMode::Amber { .. } = self.mode {
-            return Err(anyhow::anyhow!("Cannot sync app settings in Amber mode"));
+            return Err(MeisoError::unsupported_in_amber_mode("sync_app_settings").into());
         }
         
-        let keys = self.keys.as_ref()
-            .context("Secret key required")?;
+        let keys = self.signing_keys()?;
         
         let filter = Filter::new()
             .kind(Kind::Custom(30078))
@@ -657,8 +853,7 @@ impl MeisoNostrClient {
             );
 
         let events = self
-            .client
-            .fetch_events(vec![filter], Some(Duration::from_secs(10)))
+            .fetch_events(filter)
             .await?;
 
         // 最新のイベントを取得（Replaceable eventなので1つだけのはず）
@@ -670,26 +865,25 @@ impl MeisoNostrClient {
                 &event.content,
             ) {
                 if let Ok(settings) = serde_json::from_str::<AppSettings>(&decrypted) {
-                    println!("✅ App settings synced from Nostr");
+                    log_info!("✅ App settings synced from Nostr");
                     return Ok(Some(settings));
                 }
             }
         }
 
-        println!("⚠️ No app settings found");
+        log_info!("⚠️ No app settings found");
         Ok(None)
     }
 
     /// リレーリストをNostrに保存（NIP-65 Kind 10002 - Relay List Metadata）
     pub async fn save_relay_list(&self, relays: Vec<String>) -> Result<EventSendResult> {
         if let ClientMode::Amber { .. } = self.mode {
-            return Err(anyhow::anyhow!("Cannot save relay list in Amber mode"));
+            return Err(MeisoError::unsupported_in_amber_mode("save_relay_list").into());
         }
         
-        let keys = self.keys.as_ref()
-            .context("Secret key required")?;
+        let keys = self.signing_keys()?;
         
-        println!("💾 Saving relay list to Nostr (Kind 10002)...");
+        log_info!("💾 Saving relay list to Nostr (Kind 10002)...");
         
         // NIP-65: リレーをタグとして追加
         let mut tags = Vec::new();
@@ -710,11 +904,10 @@ impl MeisoNostrClient {
         // リレーに送信するイベントをJSONとしてログ出力
         match serde_json::to_string_pretty(&event.as_json()) {
             Ok(event_json) => {
-                println!("📤 Nostr relay list event (Kind 10002) to relay:");
-                println!("{}", event_json);
+                log_trace!("📤 Nostr relay list event (Kind 10002) to relay:\n{}", event_json);
             }
             Err(e) => {
-                eprintln!("⚠️ Failed to serialize event to JSON: {}", e);
+                log_warn!("⚠️ Failed to serialize event to JSON: {}", e);
             }
         }
         
@@ -724,36 +917,35 @@ impl MeisoNostrClient {
 
     /// リレーリストをNostrから同期（NIP-65 Kind 10002）
     pub async fn sync_relay_list(&self) -> Result<Vec<String>> {
-        println!("🔄 Syncing relay list from Nostr (Kind 10002)...");
+        log_info!("🔄 Syncing relay list from Nostr (Kind 10002)...");
         
         // 公開鍵を取得（モードに応じて）
         let pubkey_hex = self.public_key_hex();
-        println!("📋 Looking for relay list from pubkey: {}", &pubkey_hex[..16]);
+        log_debug!("📋 Looking for relay list from pubkey: {}", &pubkey_hex[..16]);
         let pubkey = PublicKey::from_hex(&pubkey_hex)
-            .context("Failed to parse public key")?;
+            .map_err(MeisoError::invalid_key)?;
         
         let filter = Filter::new()
             .kind(Kind::RelayList)
             .author(pubkey);
 
-        println!("🔍 Fetching Kind 10002 events from relays...");
+        log_debug!("🔍 Fetching Kind 10002 events from relays...");
         let events = self
-            .client
-            .fetch_events(vec![filter], Some(Duration::from_secs(10)))
+            .fetch_events(filter)
             .await?;
 
-        println!("📥 Received {} Kind 10002 events", events.len());
+        log_info!("📥 Received {} Kind 10002 events", events.len());
 
         // 最新のイベントを取得（Replaceable eventなので1つだけのはず）
         if let Some(event) = events.first() {
-            println!("📝 Processing relay list event ID: {}", event.id.to_hex());
-            println!("📋 Event has {} tags", event.tags.len());
+            log_debug!("📝 Processing relay list event ID: {}", note_id(&event.id));
+            log_debug!("📋 Event has {} tags", event.tags.len());
             
             let mut relays = Vec::new();
             
             // "r" タグからリレーURLを抽出
             for (i, tag) in event.tags.iter().enumerate() {
-                println!("  Tag {}: kind={:?}, content={:?}", i, tag.kind(), tag.content());
+                log_debug!("  Tag {}: kind={:?}, content={:?}", i, tag.kind(), tag.content());
                 
                 // 複数の方法でタグをチェック
                 // 方法1: 標準化されたタグとして解析（以前の実装）
@@ -761,7 +953,7 @@ impl MeisoNostrClient {
                     use nostr_sdk::prelude::TagStandard;
                     if matches!(tag_std, TagStandard::Relay(_)) {
                         if let Some(relay_url) = tag.content() {
-                            println!("    ✅ Found relay (standardized): {}", relay_url);
+                            log_debug!("    ✅ Found relay (standardized): {}", relay_url);
                             relays.push(relay_url.to_string());
                             continue;
                         }
@@ -772,23 +964,23 @@ impl MeisoNostrClient {
                 use nostr_sdk::prelude::{SingleLetterTag, Alphabet};
                 if tag.kind() == TagKind::SingleLetter(SingleLetterTag::lowercase(Alphabet::R)) {
                     if let Some(relay_url) = tag.content() {
-                        println!("    ✅ Found relay (single letter): {}", relay_url);
+                        log_debug!("    ✅ Found relay (single letter): {}", relay_url);
                         relays.push(relay_url.to_string());
                     }
                 }
             }
             
-            println!("✅ Relay list synced: {} relays", relays.len());
+            log_info!("✅ Relay list synced: {} relays", relays.len());
             return Ok(relays);
         }
 
-        println!("⚠️ No relay list found (no Kind 10002 events)");
+        log_info!("⚠️ No relay list found (no Kind 10002 events)");
         Ok(Vec::new())
     }
 
     /// リレーリストを動的に更新（既存の接続を維持しつつ追加・削除）
     pub async fn update_relay_list(&self, new_relays: Vec<String>) -> Result<()> {
-        println!("🔄 Updating relay list dynamically...");
+        log_info!("🔄 Updating relay list dynamically...");
         
         // 現在のリレーリストを取得
         let current_relays: Vec<String> = self.client
@@ -798,16 +990,19 @@ impl MeisoNostrClient {
             .map(|url| url.to_string())
             .collect();
         
-        println!("📋 Current relays: {:?}", current_relays);
-        println!("📋 New relays: {:?}", new_relays);
+        log_debug!("📋 Current relays: {:?}", current_relays);
+        log_debug!("📋 New relays: {:?}", new_relays);
         
         // 削除するリレー（現在のリレーで新しいリストに含まれないもの）
         for relay_url in &current_relays {
             if !new_relays.contains(relay_url) {
-                println!("➖ Removing relay: {}", relay_url);
+                log_debug!("➖ Removing relay: {}", relay_url);
                 match self.client.remove_relay(relay_url).await {
-                    Ok(_) => println!("✅ Relay removed: {}", relay_url),
-                    Err(e) => eprintln!("⚠️ Failed to remove relay {}: {}", relay_url, e),
+                    Ok(_) => {
+                        log_debug!("✅ Relay removed: {}", relay_url);
+                        self.relay_errors.remove(relay_url);
+                    }
+                    Err(e) => log_warn!("⚠️ Failed to remove relay {}: {}", relay_url, e),
                 }
             }
         }
@@ -815,21 +1010,25 @@ impl MeisoNostrClient {
         // 追加するリレー（新しいリストで現在のリレーに含まれないもの）
         for relay_url in &new_relays {
             if !current_relays.contains(relay_url) {
-                println!("➕ Adding relay: {}", relay_url);
-                match self.client.add_relay(relay_url).await {
+                log_debug!("➕ Adding relay: {}", relay_url);
+                match Self::add_routed_relay(&self.client, self.proxy.as_ref(), relay_url).await {
                     Ok(_) => {
-                        println!("✅ Relay added: {}", relay_url);
+                        log_debug!("✅ Relay added: {}", relay_url);
                         // 新しいリレーに接続を試みる
                         if let Err(e) = self.client.connect_relay(relay_url).await {
-                            eprintln!("⚠️ Failed to connect to relay {}: {}", relay_url, e);
+                            log_warn!("⚠️ Failed to connect to relay {}: {}", relay_url, e);
+                            self.relay_errors.record(relay_url, &e);
                         }
                     },
-                    Err(e) => eprintln!("⚠️ Failed to add relay {}: {}", relay_url, e),
+                    Err(e) => {
+                        log_warn!("⚠️ Failed to add relay {}: {}", relay_url, e);
+                        self.relay_errors.record(relay_url, &e);
+                    }
                 }
             }
         }
         
-        println!("✅ Relay list updated successfully");
+        log_info!("✅ Relay list updated successfully");
         Ok(())
     }
     
@@ -839,7 +1038,7 @@ impl MeisoNostrClient {
     
     /// Subscriptionを開始（リアルタイム更新を受信）
     pub(crate) async fn subscribe(&self, filters: Vec<Filter>) -> Result<SubscriptionInfo> {
-        println!("📡 Starting subscription with {} filters", filters.len());
+        log_info!("📡 Starting subscription with {} filters", filters.len());
         
         // Subscriptionを開始
         let subscription_id = self.client.subscribe(filters.clone(), None).await?;
@@ -850,7 +1049,7 @@ impl MeisoNostrClient {
             .unwrap()
             .as_secs() as i64;
         
-        println!("✅ Subscription started: {}", subscription_id.to_string());
+        log_info!("✅ Subscription started: {}", subscription_id.to_string());
         
         Ok(SubscriptionInfo {
             subscription_id: subscription_id.to_string(),
@@ -861,20 +1060,20 @@ impl MeisoNostrClient {
     
     /// Subscriptionを停止
     pub(crate) async fn unsubscribe(&self, subscription_id: String) -> Result<()> {
-        println!("🛑 Stopping subscription: {}", subscription_id);
+        log_info!("🛑 Stopping subscription: {}", subscription_id);
         
         let sub_id = SubscriptionId::new(subscription_id);
         self.client.unsubscribe(sub_id).await;
         
-        println!("✅ Subscription stopped");
+        log_info!("✅ Subscription stopped");
         Ok(())
     }
     
     /// すべてのSubscriptionを停止
     pub(crate) async fn unsubscribe_all(&self) -> Result<()> {
-        println!("🛑 Stopping all subscriptions");
+        log_info!("🛑 Stopping all subscriptions");
         self.client.unsubscribe_all().await;
-        println!("✅ All subscriptions stopped");
+        log_info!("✅ All subscriptions stopped");
         Ok(())
     }
     
@@ -932,39 +1131,93 @@ impl MeisoNostrClient {
         }
         
         if !events.is_empty() {
-            println!("📥 Received {} events via subscription", events.len());
+            log_info!("📥 Received {} events via subscription", events.len());
         }
         
         Ok(events)
     }
     
+    /// イベントを取得（タイムアウト・再試行は`ClientConfig`に従う）
+    /// リレーが1つも登録されていない場合は`NoRelaysAvailable`を返す
+    pub(crate) async fn fetch_events(&self, filter: Filter) -> Result<Events> {
+        if self.client.relays().await.is_empty() {
+            return Err(MeisoError::NoRelaysAvailable.into());
+        }
+        let timeout = Duration::from_secs(self.config.fetch_timeout_secs);
+
+        let mut attempt = 0;
+        loop {
+            match self.client.fetch_events(vec![filter.clone()], Some(timeout)).await {
+                Ok(events) => return Ok(events),
+                Err(e) if attempt < self.config.fetch_retries => {
+                    attempt += 1;
+                    log_warn!("🔁 Retrying fetch ({}/{}): {}", attempt, self.config.fetch_retries, e);
+                    tokio::time::sleep(self.config.retry_delay()).await;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
     /// リレー接続状態をチェック
+    /// 1つ以上のリレーに実際に接続していればtrue（追加されているだけでは数えない）
     pub(crate) async fn check_connection_status(&self) -> Result<bool> {
-        // 接続されているリレー数を確認
-        let relays = self.client.relays().await;
-        let connected_count = relays.len();
-        
-        println!("🔌 Connected relays: {}", connected_count);
-        Ok(connected_count > 0)
+        let report = self.relay_status().await;
+        log_info!("🔌 Connected relays: {}/{}", report.connected_count, report.total_count);
+        Ok(report.online)
+    }
+
+    /// リレーごとの接続状態・統計
+    pub(crate) async fn relay_status(&self) -> RelayStatusReport {
+        crate::relay_status::collect_relay_status(&self.client, &self.relay_errors).await
     }
     
-    /// リレーに再接続
+    /// 切断中のリレーに再接続（接続中のリレーとSubscriptionはそのまま）
+    ///
+    /// バックオフを待たずに再接続を始め、全リレーが接続するまで待つ。
+    /// タイムアウトしても1つ以上接続していれば成功とし、残りは`ReconnectSupervisor`が引き続き再接続する
     pub(crate) async fn reconnect(&self) -> Result<()> {
-        println!("🔄 Reconnecting to relays...");
-        
-        // 一度切断
-        self.client.disconnect().await?;
-        
-        // 再接続（タイムアウト付き）
-        match tokio::time::timeout(Duration::from_secs(10), self.client.connect()).await {
-            Ok(_) => {
-                println!("✅ Reconnected to relays");
-                Ok(())
+        log_info!("🔄 Reconnecting to relays...");
+
+        // 厳格モードではプロキシが落ちている間は再接続しない
+        if let Some(proxy) = &self.proxy {
+            proxy
+                .ensure_available(Duration::from_secs(self.config.reconnect_timeout_secs))
+                .await?;
+        }
+
+        self.supervisor.reconnect_now().await;
+
+        let timeout = Duration::from_secs(self.config.reconnect_timeout_secs);
+        let all_connected = async {
+            loop {
+                let report = self.relay_status().await;
+                if report.connected_count == report.total_count {
+                    return report;
+                }
+                tokio::time::sleep(Duration::from_millis(200)).await;
             }
-            Err(_) => {
-                eprintln!("⚠️ Reconnection timeout");
-                Err(anyhow::anyhow!("Reconnection timeout"))
+        };
+        if let Ok(report) = tokio::time::timeout(timeout, all_connected).await {
+            log_info!("✅ Reconnected to relays ({}/{})", report.connected_count, report.total_count);
+            return Ok(());
+        }
+
+        let report = self.relay_status().await;
+        if report.online {
+            log_warn!(
+                "⚠️ Reconnection timeout - {}/{} relays connected, retrying the rest in the background",
+                report.connected_count,
+                report.total_count
+            );
+            Ok(())
+        } else {
+            log_warn!("⚠️ Reconnection timeout");
+            Err(MeisoError::Timeout {
+                operation: "reconnect".to_string(),
+                timeout_secs: self.config.reconnect_timeout_secs,
             }
+            .into())
         }
     }
 }
@@ -973,60 +1226,114 @@ impl MeisoNostrClient {
 // Flutter Rust Bridge API
 // ========================================
 
-/// グローバルTokioランタイム（一度だけ作成）
-static TOKIO_RUNTIME: once_cell::sync::Lazy<tokio::runtime::Runtime> =
-    once_cell::sync::Lazy::new(|| {
-        tokio::runtime::Runtime::new().expect("Failed to create Tokio runtime")
-    });
+// 公開関数はasync fnとして公開し、flutter_rust_bridgeのランタイム上で実行される
+// Argon2などCPUを占有する処理はブロッキング用スレッドに逃がす
 
 /// Nostrクライアントを初期化（hex公開鍵を返す）
 /// client_id を指定しない場合はデフォルトクライアントとして保存
-pub fn init_nostr_client(secret_key_hex: String, relays: Vec<String>) -> Result<String> {
-    init_nostr_client_with_id(DEFAULT_CLIENT_ID.to_string(), secret_key_hex, relays, None)
+pub async fn init_nostr_client(secret_key_hex: String, relays: Vec<String>) -> Result<String, MeisoError> {
+    init_nostr_client_with_id(DEFAULT_CLIENT_ID.to_string(), secret_key_hex, relays, None, None).await
 }
 
 /// Nostrクライアントを初期化（プロキシオプション付き）
-pub fn init_nostr_client_with_proxy(
+pub async fn init_nostr_client_with_proxy(
     secret_key_hex: String, 
     relays: Vec<String>,
     proxy_url: Option<String>,
-) -> Result<String> {
-    init_nostr_client_with_id(DEFAULT_CLIENT_ID.to_string(), secret_key_hex, relays, proxy_url)
+) -> Result<String, MeisoError> {
+    init_nostr_client_with_id(DEFAULT_CLIENT_ID.to_string(), secret_key_hex, relays, proxy_url, None).await
 }
 
 /// Nostrクライアントを初期化（client_id指定可能）
-pub fn init_nostr_client_with_id(
+/// config: タイムアウト・再試行の設定（Noneならデフォルト）
+pub async fn init_nostr_client_with_id(
     client_id: String,
     secret_key_hex: String, 
     relays: Vec<String>,
     proxy_url: Option<String>,
+    config: Option<ClientConfig>,
+) -> Result<String, MeisoError> {
+    // FFIから受け取った直後にラップし、以降はゼロ埋め対象として扱う
+    let secret_key = SecretString::from(secret_key_hex);
+    Ok(init_client_with_secret(client_id, secret_key, relays, proxy_url, None, config).await?)
+}
+
+/// 鍵ストアから秘密鍵を読み込んでNostrクライアントを初期化
+/// 秘密鍵はRust側から出ないため、Flutter側に平文で渡らない
+pub async fn init_nostr_client_from_keystore(
+    client_id: String,
+    storage_path: String,
+    password: String,
+    relays: Vec<String>,
+    proxy_url: Option<String>,
+    config: Option<ClientConfig>,
+) -> Result<String, MeisoError> {
+    let password = SecretString::from(password);
+    let store = SecureKeyStore::new(storage_path.clone());
+    let secret_key = store.load_encrypted_key(password.expose_secret()).await?;
+    Ok(init_client_with_secret(client_id, secret_key, relays, proxy_url, Some(storage_path), config).await?)
+}
+
+/// 鍵ストアのキースロット（パスワード・端末鍵・リカバリーコード）でアンロックしてクライアントを初期化
+/// 端末鍵スロットを使えば、コールドスタート時にパスワードを求めずに済む
+pub async fn init_nostr_client_from_keystore_with_unlock(
+    client_id: String,
+    storage_path: String,
+    unlock: UnlockMethod,
+    relays: Vec<String>,
+    proxy_url: Option<String>,
+    config: Option<ClientConfig>,
+) -> Result<String, MeisoError> {
+    let store = SecureKeyStore::new(storage_path.clone());
+    let secret_key = store.unlock(&unlock).await?;
+    Ok(init_client_with_secret(client_id, secret_key, relays, proxy_url, Some(storage_path), config).await?)
+}
+
+/// 秘密鍵モードのクライアントを作成して登録（公開鍵hexを返す）
+/// key_source: ロック解除時に秘密鍵を読み直す鍵ストア
+async fn init_client_with_secret(
+    client_id: String,
+    secret_key: SecretString,
+    relays: Vec<String>,
+    proxy_url: Option<String>,
+    key_source: Option<String>,
+    config: Option<ClientConfig>,
 ) -> Result<String> {
-    println!("🔧 Initializing Nostr client [{}]{}...", 
+    let config = config.unwrap_or_else(|| ClientConfig::defaults_for(false, proxy_url.is_some()));
+    log_info!("🔧 Initializing Nostr client [{}]{}...", 
         client_id,
         if proxy_url.is_some() { " with proxy" } else { "" });
-    println!("Secret key (first 10 chars): {}...", &secret_key_hex[..10.min(secret_key_hex.len())]);
-    println!("Relays: {:?}", relays);
+    log_debug!("Relays: {:?}", relays);
     if let Some(ref proxy) = proxy_url {
-        println!("Proxy: {}", proxy);
+        log_debug!("Proxy: {}", proxy);
     }
 
-    TOKIO_RUNTIME.block_on(async {
-        match MeisoNostrClient::new_with_proxy(&secret_key_hex, relays, proxy_url).await {
-            Ok(client) => {
-                let public_key = client.public_key_hex();
-                println!("✅ Nostr client [{}] initialized. Public key: {}", client_id, &public_key[..16]);
+    match MeisoNostrClient::new_with_proxy(&secret_key, relays, proxy_url, config).await {
+        Ok(mut client) => {
+            client.key_source = key_source;
+            let public_key = client.public_key_hex();
+            log_info!("✅ Nostr client [{}] initialized. Public key: {}", client_id, &public_key[..16]);
 
-                let mut clients = NOSTR_CLIENTS.lock().await;
-                clients.insert(client_id, client);
+            register_client(client_id, client).await;
+            crate::session::record_activity();
 
-                Ok(public_key)
-            }
-            Err(e) => {
-                eprintln!("❌ Failed to initialize Nostr client [{}]: {}", client_id, e);
-                Err(e)
-            }
+            Ok(public_key)
         }
-    })
+        Err(e) => {
+            log_error!("❌ Failed to initialize Nostr client [{}]: {}", client_id, e);
+            Err(e)
+        }
+    }
+}
+
+/// クライアントを登録（ヘルパー関数）
+/// 同じclient_idのクライアントがあれば置き換え、古い方はリレーから切断する
+async fn register_client(client_id: String, client: MeisoNostrClient) {
+    let replaced = NOSTR_CLIENTS.lock().await.insert(client_id.clone(), client);
+    if let Some(mut old) = replaced {
+        log_info!("♻️ Replacing Nostr client [{}] - shutting down the previous one", client_id);
+        old.shutdown().await;
+    }
 }
 
 /// クライアントを取得（ヘルパー関数）
@@ -1036,20 +1343,18 @@ async fn get_client(client_id: Option<String>) -> Result<MeisoNostrClient> {
     clients
         .get(&id)
         .cloned()
-        .with_context(|| format!("Nostrクライアント [{}] が初期化されていません", id))
+        .ok_or_else(|| MeisoError::NotInitialized { client_id: id }.into())
 }
 
 /// 公開鍵をnpub形式で取得
-pub fn get_public_key_npub() -> Result<String> {
-    get_public_key_npub_with_client_id(None)
+pub async fn get_public_key_npub() -> Result<String, MeisoError> {
+    get_public_key_npub_with_client_id(None).await
 }
 
 /// 公開鍵をnpub形式で取得（client_id指定可能）
-pub fn get_public_key_npub_with_client_id(client_id: Option<String>) -> Result<String> {
-    TOKIO_RUNTIME.block_on(async {
-        let client = get_client(client_id).await?;
-        Ok(client.public_key_npub())
-    })
+pub async fn get_public_key_npub_with_client_id(client_id: Option<String>) -> Result<String, MeisoError> {
+    let client = get_client(client_id).await?;
+    Ok(client.public_key_npub())
 }
 
 /// 新しい秘密鍵を生成（hex形式）
@@ -1058,7 +1363,7 @@ pub fn generate_secret_key() -> String {
 }
 
 /// 鍵ペアを生成（nsec/npub形式で返す）
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct KeyPair {
     pub private_key_nsec: String,
     pub public_key_npub: String,
@@ -1066,57 +1371,136 @@ pub struct KeyPair {
     pub public_key_hex: String,
 }
 
-pub fn generate_keypair() -> Result<KeyPair> {
-    let keys = Keys::generate();
-    
-    let private_key_hex = keys.secret_key().to_secret_hex();
-    let public_key_hex = keys.public_key().to_hex();
-    
-    // nsec形式
-    let private_key_nsec = keys.secret_key().to_bech32()
-        .map_err(|e| anyhow::anyhow!("Failed to convert private key to nsec format: {}", e))?;
-    
-    // npub形式
-    let public_key_npub = keys.public_key().to_bech32()
-        .map_err(|e| anyhow::anyhow!("Failed to convert public key to npub format: {}", e))?;
-    
-    println!("🔑 Generated new keypair:");
-    println!("  Private (nsec): {}...", &private_key_nsec[..20]);
-    println!("  Public (npub): {}", &public_key_npub);
-    
-    Ok(KeyPair {
-        private_key_nsec,
-        public_key_npub,
-        private_key_hex,
-        public_key_hex,
+impl std::fmt::Debug for KeyPair {
+    /// 秘密鍵はログに出さない
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyPair")
+            .field("private_key_nsec", &"[REDACTED]")
+            .field("public_key_npub", &self.public_key_npub)
+            .field("private_key_hex", &"[REDACTED]")
+            .field("public_key_hex", &self.public_key_hex)
+            .finish()
+    }
+}
+
+impl KeyPair {
+    /// Keysから各形式の鍵ペアを作成
+    fn from_keys(keys: &Keys) -> Result<Self> {
+        let private_key_hex = keys.secret_key().to_secret_hex();
+        let public_key_hex = keys.public_key().to_hex();
+        
+        // nsec形式
+        let private_key_nsec = keys.secret_key().to_bech32()
+            .map_err(|e| anyhow::anyhow!("Failed to convert private key to nsec format: {}", e))?;
+        
+        // npub形式
+        let public_key_npub = keys.public_key().to_bech32()
+            .map_err(|e| anyhow::anyhow!("Failed to convert public key to npub format: {}", e))?;
+        
+        Ok(KeyPair {
+            private_key_nsec,
+            public_key_npub,
+            private_key_hex,
+            public_key_hex,
+        })
+    }
+}
+
+pub fn generate_keypair() -> Result<KeyPair, MeisoError> {
+    let keypair = KeyPair::from_keys(&Keys::generate())?;
+    log_info!("🔑 Generated new keypair: {}", &keypair.public_key_npub);
+    Ok(keypair)
+}
+
+/// バックアップ用ニーモニック付きの鍵ペア（NIP-06）
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MnemonicKeyPair {
+    /// BIP-39ニーモニック（英語、スペース区切り）
+    pub mnemonic: String,
+    pub keypair: KeyPair,
+}
+
+impl std::fmt::Debug for MnemonicKeyPair {
+    /// ニーモニックはログに出さない
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MnemonicKeyPair")
+            .field("mnemonic", &"[REDACTED]")
+            .field("keypair", &self.keypair)
+            .finish()
+    }
+}
+
+/// ニーモニック（12語 or 24語）を生成し、NIP-06で鍵ペアを導出
+/// passphrase: BIP-39のオプションパスフレーズ（復元時にも同じものが必要）
+pub fn generate_mnemonic_keypair(
+    word_count: u32,
+    passphrase: Option<String>,
+) -> Result<MnemonicKeyPair, MeisoError> {
+    let passphrase = passphrase.map(SecretString::from);
+    let mnemonic = crate::mnemonic::generate_mnemonic(word_count as usize)?;
+    let keys = crate::mnemonic::derive_keys(
+        mnemonic.expose_secret(),
+        passphrase.as_ref().map(|p| p.expose_secret()),
+        0,
+    )?;
+
+    let keypair = KeyPair::from_keys(&keys)?;
+    log_info!("🔑 Generated new keypair from {}-word mnemonic: {}", word_count, &keypair.public_key_npub);
+
+    Ok(MnemonicKeyPair {
+        mnemonic: mnemonic.expose_secret().to_string(),
+        keypair,
     })
 }
 
+/// ニーモニックから鍵ペアを復元（NIP-06: m/44'/1237'/<account>'/0/0）
+/// 不明な単語やチェックサム不一致の場合はエラー
+pub fn restore_keypair_from_mnemonic(
+    mnemonic: String,
+    passphrase: Option<String>,
+    account: Option<u32>,
+) -> Result<KeyPair, MeisoError> {
+    let mnemonic = SecretString::from(mnemonic);
+    let passphrase = passphrase.map(SecretString::from);
+    let keys = crate::mnemonic::derive_keys(
+        mnemonic.expose_secret(),
+        passphrase.as_ref().map(|p| p.expose_secret()),
+        account.unwrap_or(0),
+    )?;
+
+    let keypair = KeyPair::from_keys(&keys)?;
+    log_info!("🔑 Restored keypair from mnemonic: {}", &keypair.public_key_npub);
+    Ok(keypair)
+}
+
+/// ニーモニックを検証のみ行う（入力中のフィードバック用）
+pub fn validate_mnemonic(mnemonic: String) -> Result<(), MeisoError> {
+    let mnemonic = SecretString::from(mnemonic);
+    crate::mnemonic::parse_mnemonic(mnemonic.expose_secret())?;
+    Ok(())
+}
+
 
 /// 全Todoを同期（Kind 30001 - 新実装）
-pub fn sync_todo_list() -> Result<Vec<TodoData>> {
-    sync_todo_list_with_client_id(None)
+pub async fn sync_todo_list() -> Result<Vec<TodoData>, MeisoError> {
+    sync_todo_list_with_client_id(None).await
 }
 
 /// 全Todoを同期（client_id指定可能）
-pub fn sync_todo_list_with_client_id(client_id: Option<String>) -> Result<Vec<TodoData>> {
-    TOKIO_RUNTIME.block_on(async {
-        let client = get_client(client_id).await?;
-        client.sync_todo_list().await
-    })
+pub async fn sync_todo_list_with_client_id(client_id: Option<String>) -> Result<Vec<TodoData>, MeisoError> {
+    let client = get_client(client_id).await?;
+    Ok(client.sync_todo_list().await?)
 }
 
 /// Todoリストを作成（Kind 30001）
-pub fn create_todo_list(todos: Vec<TodoData>) -> Result<EventSendResult> {
-    create_todo_list_with_client_id(todos, None)
+pub async fn create_todo_list(todos: Vec<TodoData>) -> Result<EventSendResult, MeisoError> {
+    create_todo_list_with_client_id(todos, None).await
 }
 
 /// Todoリストを作成（client_id指定可能）
-pub fn create_todo_list_with_client_id(todos: Vec<TodoData>, client_id: Option<String>) -> Result<EventSendResult> {
-    TOKIO_RUNTIME.block_on(async {
-        let client = get_client(client_id).await?;
-        client.create_todo_list(todos).await
-    })
+pub async fn create_todo_list_with_client_id(todos: Vec<TodoData>, client_id: Option<String>) -> Result<EventSendResult, MeisoError> {
+    let client = get_client(client_id).await?;
+    Ok(client.create_todo_list(todos).await?)
 }
 
 
@@ -1124,181 +1508,824 @@ pub fn create_todo_list_with_client_id(todos: Vec<TodoData>, client_id: Option<S
 // 鍵管理API (SecureKeyStore)
 // ========================================
 
-use crate::key_store::SecureKeyStore;
+use crate::key_store::{
+    run_blocking, KdfCalibration, KdfParams, KdfProfile, KeySlotInfo, KeystoreReport, SecureKeyStore, UnlockMethod,
+    UnlockPolicy, UnlockStatus,
+};
 
 /// 秘密鍵を暗号化して保存（パスワードベース）
-pub fn save_encrypted_secret_key(
+pub async fn save_encrypted_secret_key(
     storage_path: String,
     secret_key: String,
     password: String,
-) -> Result<()> {
-    TOKIO_RUNTIME.block_on(async {
-        let store = SecureKeyStore::new(storage_path);
-        store.save_encrypted_key(&secret_key, &password).await
-    })
+) -> Result<(), MeisoError> {
+    let secret_key = SecretString::from(secret_key);
+    let password = SecretString::from(password);
+    let store = SecureKeyStore::new(storage_path);
+    Ok(store.save_encrypted_key(&secret_key, password.expose_secret()).await?)
+}
+
+/// Argon2パラメータを指定して秘密鍵を暗号化保存
+/// パラメータは鍵ファイルに記録され、読み込み時はその値が使われる
+pub async fn save_encrypted_secret_key_with_kdf(
+    storage_path: String,
+    secret_key: String,
+    password: String,
+    kdf: KdfParams,
+) -> Result<(), MeisoError> {
+    let secret_key = SecretString::from(secret_key);
+    let password = SecretString::from(password);
+    let store = SecureKeyStore::new(storage_path).with_kdf_params(kdf)?;
+    Ok(store.save_encrypted_key(&secret_key, password.expose_secret()).await?)
+}
+
+/// 名前付きプロファイルのArgon2パラメータを取得
+pub fn get_kdf_profile_params(profile: KdfProfile) -> KdfParams {
+    profile.params()
+}
+
+/// この端末でベンチマークし、目標アンロック時間に合うArgon2パラメータを選ぶ
+/// max_memory_mibを省略した場合は256 MiBまで
+pub async fn calibrate_kdf(target_ms: u64, max_memory_mib: Option<u32>) -> Result<KdfCalibration, MeisoError> {
+    let max_memory_kib = max_memory_mib.map(|mib| mib.saturating_mul(1024));
+    Ok(run_blocking(move || crate::key_store::calibrate_kdf(target_ms, max_memory_kib)).await?)
 }
 
 /// 暗号化された秘密鍵を読み込み
-pub fn load_encrypted_secret_key(
+/// 戻り値はFlutter側に平文で渡るため、可能な限り init_nostr_client_from_keystore を使うこと
+pub async fn load_encrypted_secret_key(
     storage_path: String,
     password: String,
-) -> Result<String> {
-    TOKIO_RUNTIME.block_on(async {
-        let store = SecureKeyStore::new(storage_path);
-        store.load_encrypted_key(&password).await
-    })
+) -> Result<String, MeisoError> {
+    let password = SecretString::from(password);
+    let store = SecureKeyStore::new(storage_path);
+    let secret_key = store.load_encrypted_key(password.expose_secret()).await?;
+    Ok(secret_key.expose_secret().to_string())
+}
+
+/// 秘密鍵のパスワードを変更（アトミックに再暗号化）
+pub async fn change_secret_key_password(
+    storage_path: String,
+    old_password: String,
+    new_password: String,
+) -> Result<(), MeisoError> {
+    let old_password = SecretString::from(old_password);
+    let new_password = SecretString::from(new_password);
+    let store = SecureKeyStore::new(storage_path);
+    Ok(store
+        .change_password(old_password.expose_secret(), new_password.expose_secret())
+        .await?)
+}
+
+/// 鍵ストアの秘密鍵をNIP-49形式（ncryptsec）でエクスポート
+/// log_n: scryptのコストパラメータ（16〜22）
+pub async fn export_ncryptsec(
+    storage_path: String,
+    password: String,
+    log_n: u8,
+) -> Result<String, MeisoError> {
+    let password = SecretString::from(password);
+    let store = SecureKeyStore::new(storage_path);
+    Ok(store.export_ncryptsec(password.expose_secret(), log_n).await?)
+}
+
+/// NIP-49形式（ncryptsec）の秘密鍵を鍵ストアにインポート（公開鍵hexを返す）
+/// 復号した秘密鍵はFlutter側に返さず、同じパスワードで鍵ストアに保存する
+pub async fn import_ncryptsec(
+    storage_path: String,
+    ncryptsec: String,
+    password: String,
+) -> Result<String, MeisoError> {
+    let password = SecretString::from(password);
+    let store = SecureKeyStore::new(storage_path);
+    Ok(store.import_ncryptsec(&ncryptsec, password.expose_secret()).await?)
+}
+
+/// パスワード間違いが続いた場合のポリシーを設定（バックオフ・自動削除）
+pub fn set_unlock_policy(policy: UnlockPolicy) {
+    crate::key_store::set_unlock_policy(policy);
+}
+
+/// 現在のアンロックポリシーを取得
+pub fn get_unlock_policy() -> UnlockPolicy {
+    crate::key_store::unlock_policy()
+}
+
+/// 失敗回数の記録の保存先を設定（Noneで鍵ファイルと同じ場所）
+///
+/// `register_key_storage_backend`で登録したKeychain/Keystoreのバックエンドを
+/// `callback://<backend_id>`で指定すると、鍵ファイルを書き換えられる相手でも記録を戻せなくなる
+pub fn set_unlock_attempts_backend(location: Option<String>) {
+    crate::key_store::set_unlock_attempts_backend(location);
+}
+
+/// 失敗回数と次に試行できるまでの秒数を取得（パスワード不要）
+pub async fn get_unlock_status(storage_path: String) -> Result<UnlockStatus, MeisoError> {
+    let store = SecureKeyStore::new(storage_path);
+    Ok(store.unlock_status().await?)
 }
 
 /// 公開鍵を保存（Amber使用時）
-pub fn save_public_key(
+pub async fn save_public_key(
     storage_path: String,
     public_key: String,
-) -> Result<()> {
-    TOKIO_RUNTIME.block_on(async {
-        let store = SecureKeyStore::new(storage_path);
-        store.save_public_key(&public_key).await
-    })
+) -> Result<(), MeisoError> {
+    let store = SecureKeyStore::new(storage_path);
+    Ok(store.save_public_key(&public_key).await?)
 }
 
 /// 公開鍵を読み込み
-pub fn load_public_key(
+pub async fn load_public_key(
     storage_path: String,
-) -> Result<Option<String>> {
-    TOKIO_RUNTIME.block_on(async {
-        let store = SecureKeyStore::new(storage_path);
-        store.load_public_key().await
-    })
+) -> Result<Option<String>, MeisoError> {
+    let store = SecureKeyStore::new(storage_path);
+    Ok(store.load_public_key().await?)
+}
+
+/// 保存されている公開鍵（hex）をパスワードなしで取得
+/// 秘密鍵モードでは暗号化ファイルのヘッダー、Amberモードでは公開鍵ファイルから読む
+pub async fn get_stored_public_key(
+    storage_path: String,
+) -> Result<Option<String>, MeisoError> {
+    let store = SecureKeyStore::new(storage_path);
+    Ok(store.stored_public_key().await?)
 }
 
 /// 保存された鍵を削除
-pub fn delete_stored_keys(
+pub async fn delete_stored_keys(
     storage_path: String,
-) -> Result<()> {
-    TOKIO_RUNTIME.block_on(async {
-        let store = SecureKeyStore::new(storage_path);
-        store.delete_keys().await
-    })
+) -> Result<(), MeisoError> {
+    let store = SecureKeyStore::new(storage_path);
+    Ok(store.delete_keys().await?)
 }
 
 /// 暗号化された秘密鍵が存在するか確認
-pub fn has_encrypted_key(
+pub async fn has_encrypted_key(
     storage_path: String,
 ) -> bool {
-    TOKIO_RUNTIME.block_on(async {
-        let store = SecureKeyStore::new(storage_path);
-        store.has_encrypted_key().await
-    })
+    let store = SecureKeyStore::new(storage_path);
+    store.has_encrypted_key().await
 }
 
 /// 公開鍵が存在するか確認
-pub fn has_public_key(
+pub async fn has_public_key(
     storage_path: String,
 ) -> bool {
-    TOKIO_RUNTIME.block_on(async {
-        let store = SecureKeyStore::new(storage_path);
-        store.has_public_key().await
-    })
+    let store = SecureKeyStore::new(storage_path);
+    store.has_public_key().await
 }
 
-// ========================================
-// Amber連携API
-// ========================================
+/// 鍵ストアを診断（パスワード不要）
+/// フォーマット・KDFパラメータ・公開鍵・パーミッション・構造の妥当性を返す
+pub async fn inspect_keystore(
+    storage_path: String,
+) -> Result<KeystoreReport, MeisoError> {
+    let store = SecureKeyStore::new(storage_path);
+    Ok(store.inspect().await?)
+}
 
-/// Amberから受け取った署名済みイベントを検証
-pub fn verify_amber_signature(event_json: String) -> Result<bool> {
-    let event: Event = serde_json::from_str(&event_json)
-        .context("Failed to parse event JSON")?;
-    
-    match event.verify() {
-        Ok(_) => {
-            println!("✅ Amber signature verified successfully");
-            Ok(true)
-        }
-        Err(e) => {
-            eprintln!("❌ Amber signature verification failed: {}", e);
-            Ok(false)
-        }
-    }
+/// キースロットの一覧を取得（パスワード不要）
+pub async fn list_key_slots(
+    storage_path: String,
+) -> Result<Vec<KeySlotInfo>, MeisoError> {
+    let store = SecureKeyStore::new(storage_path);
+    Ok(store.list_key_slots().await?)
 }
 
-/// 公開鍵のみでNostrクライアントを初期化（Amber使用時）
-/// 署名が必要な操作はAmber経由で行う
-pub fn init_nostr_client_with_pubkey(
-    public_key_hex: String,
-    relays: Vec<String>,
-) -> Result<String> {
-    init_nostr_client_with_pubkey_and_id(DEFAULT_CLIENT_ID.to_string(), public_key_hex, relays, None)
+/// キースロットを追加（既存のスロットでアンロック、追加したスロット番号を返す）
+/// 端末鍵はプラットフォームのKeystoreなどで保護された16バイト以上の値を渡す
+pub async fn add_key_slot(
+    storage_path: String,
+    unlock: UnlockMethod,
+    new_slot: UnlockMethod,
+) -> Result<u32, MeisoError> {
+    let store = SecureKeyStore::new(storage_path);
+    Ok(store.add_key_slot(&unlock, &new_slot).await?)
 }
 
-/// Amberモードで初期化（プロキシオプション付き）
-pub fn init_nostr_client_with_pubkey_and_proxy(
-    public_key_hex: String,
-    relays: Vec<String>,
-    proxy_url: Option<String>,
-) -> Result<String> {
-    init_nostr_client_with_pubkey_and_id(DEFAULT_CLIENT_ID.to_string(), public_key_hex, relays, proxy_url)
+/// リカバリーコードのスロットを追加し、印刷・書き写し用のコードを返す
+/// コードは再表示できないため、呼び出し側でユーザーに確実に控えてもらうこと
+pub async fn add_recovery_code_slot(
+    storage_path: String,
+    unlock: UnlockMethod,
+) -> Result<String, MeisoError> {
+    let store = SecureKeyStore::new(storage_path);
+    let code = store.add_recovery_code_slot(&unlock).await?;
+    Ok(code.expose_secret().to_string())
 }
 
-/// Amberモードで初期化（client_id指定可能）
-pub fn init_nostr_client_with_pubkey_and_id(
-    client_id: String,
-    public_key_hex: String,
-    relays: Vec<String>,
-    proxy_url: Option<String>,
-) -> Result<String> {
-    println!("🔧 Initializing Nostr client [{}] with public key only (Amber mode){}...",
-        client_id,
-        if proxy_url.is_some() { " with proxy" } else { "" });
-    println!("Public key: {}...", &public_key_hex[..16.min(public_key_hex.len())]);
-    println!("Relays: {:?}", relays);
-    if let Some(ref proxy) = proxy_url {
-        println!("Proxy: {}", proxy);
-    }
-    
-    TOKIO_RUNTIME.block_on(async {
-        match MeisoNostrClient::new_amber_mode(public_key_hex.clone(), relays, proxy_url).await {
-            Ok(client) => {
-                println!("✅ Nostr client [{}] initialized in Amber mode", client_id);
-                
-                let mut clients = NOSTR_CLIENTS.lock().await;
-                clients.insert(client_id, client);
-                
-                Ok(public_key_hex)
-            }
-            Err(e) => {
-                eprintln!("❌ Failed to initialize Nostr client [{}] in Amber mode: {}", client_id, e);
-                Err(e)
-            }
-        }
-    })
+/// キースロットを削除（最後の1つは削除できない）
+pub async fn remove_key_slot(
+    storage_path: String,
+    unlock: UnlockMethod,
+    index: u32,
+) -> Result<(), MeisoError> {
+    let store = SecureKeyStore::new(storage_path);
+    Ok(store.remove_key_slot(&unlock, index).await?)
 }
 
+// ========================================
+// シャミア秘密分散によるバックアップAPI
+// ========================================
+
+use crate::shamir::ShareInfo;
 
-/// 署名済みイベントをリレーに送信
-pub fn send_signed_event(event_json: String) -> Result<EventSendResult> {
-    send_signed_event_with_client_id(event_json, None)
+/// 鍵ストアの秘密鍵をシェアに分割（threshold個のシェアで復元可能）
+/// 各シェアは家族や別の端末に渡すための書き写せる文字列
+pub async fn split_secret_key_to_shares(
+    storage_path: String,
+    password: String,
+    threshold: u8,
+    share_count: u8,
+) -> Result<Vec<String>, MeisoError> {
+    let password = SecretString::from(password);
+    let store = SecureKeyStore::new(storage_path);
+    let secret_key = store.load_encrypted_key(password.expose_secret()).await?;
+    let shares = crate::shamir::split_secret_key(&secret_key, threshold, share_count)?;
+    Ok(shares.iter().map(|share| share.expose_secret().to_string()).collect())
 }
 
-/// 署名済みイベントをリレーに送信（client_id指定可能）
-pub fn send_signed_event_with_client_id(event_json: String, client_id: Option<String>) -> Result<EventSendResult> {
-    TOKIO_RUNTIME.block_on(async {
-        let client = get_client(client_id).await?;
-        
-        // イベントをパース
-        let event: Event = serde_json::from_str(&event_json)
-            .context("Failed to parse signed event JSON")?;
-        
-        // 署名を検証
-        event.verify().context("Invalid event signature")?;
-        
-        println!("📤 Sending signed event to relays...");
-        println!("🔍 Event kind: {}", event.kind);
-        println!("🔍 Event ID: {}", event.id.to_hex());
-        println!("🔍 Event pubkey: {}...", &event.pubkey.to_hex()[..16]);
-        
-        // リレーに送信（改善されたエラーハンドリング）
-        client.send_event_with_result(event).await
-    })
+/// シェアの情報を取得（入力途中の書き間違いチェック・進捗表示用）
+pub fn inspect_backup_share(share: String) -> Result<ShareInfo, MeisoError> {
+    Ok(crate::shamir::inspect_share(&share)?)
+}
+
+/// シェアから秘密鍵を復元して鍵ストアに保存（公開鍵hexを返す）
+/// 復元した秘密鍵はFlutter側に返さない
+pub async fn restore_secret_key_from_shares(
+    storage_path: String,
+    shares: Vec<String>,
+    password: String,
+) -> Result<String, MeisoError> {
+    let password = SecretString::from(password);
+    let secret_key = crate::shamir::combine_shares(&shares)?;
+    let keys = Keys::parse(secret_key.expose_secret()).map_err(MeisoError::invalid_key)?;
+
+    let store = SecureKeyStore::new(storage_path);
+    store.save_encrypted_key(&secret_key, password.expose_secret()).await?;
+    Ok(keys.public_key().to_hex())
+}
+
+// ========================================
+// 端末間の鍵移行API
+// ========================================
+
+/// 移行元で作成した転送ペイロード
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferPairing {
+    /// QRコードに表示する文字列
+    pub payload: String,
+    /// 移行先で入力するワンタイムコード（QRコードとは別に表示する）
+    pub code: String,
+    /// 有効期限（UNIX秒）
+    pub expires_at: i64,
+}
+
+/// 移行先での取り込み結果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferImport {
+    /// 公開鍵（hex）
+    pub public_key_hex: String,
+    /// 移行元のリレーリスト
+    pub relays: Vec<String>,
+    /// 移行元のアプリ設定（JSON）
+    pub settings_json: Option<String>,
+}
+
+/// 鍵ストアの秘密鍵・リレーリスト・設定から転送ペイロードを作成（移行元）
+/// ttl_secs: 有効期限（秒、省略時は5分、最大15分）
+pub async fn create_transfer_payload(
+    storage_path: String,
+    password: String,
+    relays: Vec<String>,
+    settings_json: Option<String>,
+    ttl_secs: Option<u64>,
+) -> Result<TransferPairing, MeisoError> {
+    let password = SecretString::from(password);
+    let store = SecureKeyStore::new(storage_path);
+    let secret_key = store.load_encrypted_key(password.expose_secret()).await?;
+    let ttl = ttl_secs
+        .map(Duration::from_secs)
+        .unwrap_or(crate::transfer::DEFAULT_TRANSFER_TTL);
+    let created = run_blocking(move || {
+        crate::transfer::create_transfer_payload(&secret_key, relays, settings_json, ttl)
+    })
+    .await?;
+    Ok(TransferPairing {
+        payload: created.payload,
+        code: created.code.expose_secret().to_string(),
+        expires_at: created.expires_at,
+    })
+}
+
+/// 転送ペイロードを取り込み、鍵ストアに保存してNostrクライアントを初期化（移行先）
+/// 秘密鍵はFlutter側に返さない
+pub async fn import_transfer_payload(
+    client_id: String,
+    payload: String,
+    code: String,
+    storage_path: String,
+    password: String,
+    proxy_url: Option<String>,
+) -> Result<TransferImport, MeisoError> {
+    let code = SecretString::from(code);
+    let password = SecretString::from(password);
+    let contents =
+        run_blocking(move || crate::transfer::open_transfer_payload(&payload, code.expose_secret())).await?;
+
+    let store = SecureKeyStore::new(storage_path.clone());
+    store.save_encrypted_key(&contents.secret_key, password.expose_secret()).await?;
+    store.save_public_key(&contents.public_key_hex).await?;
+
+    let public_key_hex = init_client_with_secret(
+        client_id,
+        contents.secret_key,
+        contents.relays.clone(),
+        proxy_url,
+        Some(storage_path),
+        None,
+    )
+    .await?;
+
+    Ok(TransferImport {
+        public_key_hex,
+        relays: contents.relays,
+        settings_json: contents.settings_json,
+    })
+}
+
+// ========================================
+// 鍵ストレージバックエンドAPI
+// ========================================
+
+use crate::storage::CallbackStorage;
+use flutter_rust_bridge::DartFnFuture;
+
+/// Flutter側で実装したストレージバックエンドを登録
+///
+/// 登録後は`callback://<backend_id>/<name>`をstorage_pathとして鍵管理APIに渡せる。
+/// get: blobを返す（存在しない場合はnull）、put: 成功したらtrue、delete: 削除したらtrue
+pub fn register_key_storage_backend(
+    backend_id: String,
+    get: impl Fn(String) -> DartFnFuture<Option<Vec<u8>>> + Send + Sync + 'static,
+    put: impl Fn(String, Vec<u8>) -> DartFnFuture<bool> + Send + Sync + 'static,
+    delete: impl Fn(String) -> DartFnFuture<bool> + Send + Sync + 'static,
+) {
+    crate::storage::register_callback_storage(&backend_id, CallbackStorage::new(get, put, delete));
+}
+
+/// ストレージバックエンドの登録を解除
+pub fn unregister_key_storage_backend(backend_id: String) -> bool {
+    crate::storage::unregister_callback_storage(&backend_id)
+}
+
+// ========================================
+// キーリングAPI（複数アイデンティティ）
+// ========================================
+
+use crate::keyring::{Keyring, KeyringIdentity};
+
+/// キーリングに登録されたアイデンティティの一覧
+pub async fn keyring_list_identities(keyring_dir: String) -> Result<Vec<KeyringIdentity>, MeisoError> {
+    Ok(Keyring::open(keyring_dir).await?.list().await?)
+}
+
+/// 秘密鍵を持つアイデンティティを追加（鍵はパスワードで暗号化保存）
+pub async fn keyring_add_local_identity(
+    keyring_dir: String,
+    label: String,
+    secret_key: String,
+    password: String,
+) -> Result<KeyringIdentity, MeisoError> {
+    let secret_key = SecretString::from(secret_key);
+    let password = SecretString::from(password);
+    let keyring = Keyring::open(keyring_dir).await?;
+    Ok(keyring.add_local(&label, &secret_key, password.expose_secret()).await?)
+}
+
+/// 公開鍵のみのアイデンティティを追加（Amber使用時、hex/npubどちらでも可）
+pub async fn keyring_add_amber_identity(
+    keyring_dir: String,
+    label: String,
+    public_key: String,
+) -> Result<KeyringIdentity, MeisoError> {
+    Ok(Keyring::open(keyring_dir).await?.add_amber(&label, &public_key).await?)
+}
+
+/// アイデンティティのラベルを変更
+pub async fn keyring_rename_identity(
+    keyring_dir: String,
+    identity_id: String,
+    label: String,
+) -> Result<KeyringIdentity, MeisoError> {
+    Ok(Keyring::open(keyring_dir).await?.rename(&identity_id, &label).await?)
+}
+
+/// アイデンティティを削除（保存された鍵も削除）
+pub async fn keyring_remove_identity(keyring_dir: String, identity_id: String) -> Result<(), MeisoError> {
+    Ok(Keyring::open(keyring_dir).await?.remove(&identity_id).await?)
+}
+
+/// アクティブなアイデンティティを切り替え
+pub async fn keyring_set_active_identity(
+    keyring_dir: String,
+    identity_id: String,
+) -> Result<KeyringIdentity, MeisoError> {
+    Ok(Keyring::open(keyring_dir).await?.set_active(&identity_id).await?)
+}
+
+/// アクティブなアイデンティティを取得
+pub async fn keyring_get_active_identity(keyring_dir: String) -> Result<Option<KeyringIdentity>, MeisoError> {
+    Ok(Keyring::open(keyring_dir).await?.active().await?)
+}
+
+/// アイデンティティに対応するNostrクライアントを初期化（client_idはアイデンティティごと）
+/// LocalKeyモードはパスワードが必要、Amberモードは不要
+pub async fn init_nostr_client_for_identity(
+    keyring_dir: String,
+    identity_id: String,
+    password: Option<String>,
+    relays: Vec<String>,
+    proxy_url: Option<String>,
+    config: Option<ClientConfig>,
+) -> Result<String, MeisoError> {
+    use crate::keyring::IdentityMode;
+
+    let password = password.map(SecretString::from);
+    let keyring = Keyring::open(keyring_dir).await?;
+    let identity = keyring.get(&identity_id).await?;
+
+    match identity.mode {
+        IdentityMode::LocalKey => {
+            let password = password
+                .ok_or_else(|| MeisoError::invalid_argument("Password required for local identity"))?;
+            let secret_key = keyring
+                .key_store(&identity.id)
+                .load_encrypted_key(password.expose_secret())
+                .await?;
+            let key_source = keyring.key_store_path(&identity.id);
+            Ok(init_client_with_secret(identity.client_id, secret_key, relays, proxy_url, Some(key_source), config).await?)
+        }
+        IdentityMode::Amber => {
+            Ok(init_client_with_pubkey(identity.client_id, identity.public_key_hex, relays, proxy_url, config).await?)
+        }
+    }
+}
+
+// ========================================
+// クライアント管理API
+// ========================================
+
+/// 登録中のクライアントの情報
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientInfo {
+    pub client_id: String,
+    pub mode: ClientMode,
+    pub public_key_hex: String,
+    /// セッションロック中か
+    pub is_locked: bool,
+    /// 追加されているリレー数
+    pub relay_count: u32,
+}
+
+/// 登録中のクライアント一覧（client_id順）
+pub async fn list_clients() -> Result<Vec<ClientInfo>, MeisoError> {
+    let clients = NOSTR_CLIENTS.lock().await;
+    let mut infos = Vec::with_capacity(clients.len());
+    for (client_id, client) in clients.iter() {
+        infos.push(ClientInfo {
+            client_id: client_id.clone(),
+            mode: client.mode.clone(),
+            public_key_hex: client.public_key_hex(),
+            is_locked: client.is_locked(),
+            relay_count: client.client.relays().await.len() as u32,
+        });
+    }
+    infos.sort_by(|a, b| a.client_id.cmp(&b.client_id));
+    Ok(infos)
+}
+
+/// クライアントが実際に使っているタイムアウト・再試行の設定
+pub async fn get_client_config(client_id: Option<String>) -> Result<ClientConfig, MeisoError> {
+    let client = get_client(client_id).await?;
+    Ok(client.config().clone())
+}
+
+/// 初期化時にconfigを省略した場合のデフォルト設定
+/// amber: Amberモードか、with_proxy: プロキシ経由か
+pub fn default_client_config(amber: bool, with_proxy: bool) -> ClientConfig {
+    ClientConfig::defaults_for(amber, with_proxy)
+}
+
+/// プロキシ診断の各段階のタイムアウト（Tor回線の確立を待てる長さ）
+const PROXY_PROBE_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// プロキシを診断（設定画面の表示用）
+///
+/// SOCKS5のハンドシェイクのレイテンシを測り、test_relayを指定した場合は
+/// プロキシ経由でリレーに接続してWebSocketアップグレードまで確認する。
+/// 診断の失敗はエラーではなく結果の`failed_stage`/`error`で返す
+pub async fn probe_proxy(proxy_url: String, test_relay: Option<String>) -> ProxyProbeResult {
+    let result = crate::proxy::probe_proxy(&proxy_url, test_relay.as_deref(), PROXY_PROBE_TIMEOUT).await;
+    match &result.failed_stage {
+        None => log_info!("✅ Proxy probe succeeded: {:?}", result),
+        Some(stage) => log_warn!("⚠️ Proxy probe failed at {:?}: {:?}", stage, result.error),
+    }
+    result
+}
+
+/// クライアントを停止して登録を解除
+/// Subscriptionを止めてリレーから切断し、メモリ上の秘密鍵を破棄する
+///
+/// 戻り値: クライアントが登録されていたか
+pub async fn shutdown_client(client_id: String) -> Result<bool, MeisoError> {
+    let removed = NOSTR_CLIENTS.lock().await.remove(&client_id);
+    match removed {
+        Some(mut client) => {
+            client.shutdown().await;
+            log_info!("🛑 Nostr client [{}] shut down", client_id);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// すべてのクライアントを停止して登録を解除
+pub async fn shutdown_all() -> Result<(), MeisoError> {
+    let clients: Vec<_> = NOSTR_CLIENTS.lock().await.drain().collect();
+    for (client_id, mut client) in clients {
+        client.shutdown().await;
+        log_info!("🛑 Nostr client [{}] shut down", client_id);
+    }
+    Ok(())
+}
+
+/// サインアウト（クライアントを停止し、必要なら鍵ストアも削除）
+///
+/// delete_key_store: trueの場合、クライアントの初期化に使った鍵ストアの鍵ファイルを削除する
+/// （秘密鍵を直接渡して初期化したクライアントやAmberモードには鍵ストアがない）。
+/// キーリングのアイデンティティの鍵ストアだった場合は、アイデンティティもキーリングから削除する
+///
+/// 戻り値: 鍵ファイルを削除したか
+pub async fn sign_out(client_id: String, delete_key_store: bool) -> Result<bool, MeisoError> {
+    let removed = NOSTR_CLIENTS.lock().await.remove(&client_id);
+    let Some(mut client) = removed else {
+        return Err(MeisoError::NotInitialized { client_id });
+    };
+
+    client.shutdown().await;
+    log_info!("👋 Signed out Nostr client [{}]", client_id);
+
+    if !delete_key_store {
+        return Ok(false);
+    }
+    let Some(storage_path) = client.key_source else {
+        log_warn!("⚠️ Nostr client [{}] has no key store - nothing to delete", client_id);
+        return Ok(false);
+    };
+
+    // アイデンティティだけが残ると、次回の初期化で鍵ファイルが見つからなくなる
+    match Keyring::find_by_key_store_path(&storage_path).await? {
+        Some((keyring, identity)) => keyring.remove(&identity.id).await?,
+        None => SecureKeyStore::new(storage_path).delete_keys().await?,
+    }
+    Ok(true)
+}
+
+// ========================================
+// セッションロックAPI（自動ロック）
+// ========================================
+
+/// すべてのクライアントから秘密鍵を破棄してロック
+/// リレー接続は維持されるので、購読や読み取りは継続できる
+pub async fn lock_session() -> Result<(), MeisoError> {
+    lock_all_clients().await;
+    Ok(())
+}
+
+/// パスワードでクライアントのロックを解除（鍵ストアから秘密鍵を読み直す）
+/// 同じ鍵ストアを使うロック中のクライアントもまとめて解除し、そのclient_idの一覧を返す
+///
+/// 他のアイデンティティの鍵ストアは試さない（失敗回数が別の鍵に加算されないように）
+pub async fn unlock_session(client_id: String, password: String) -> Result<Vec<String>, MeisoError> {
+    unlock_session_with(client_id, UnlockMethod::Password(password)).await
+}
+
+/// キースロット（パスワード・端末鍵・リカバリーコード）でクライアントのロックを解除
+pub async fn unlock_session_with(client_id: String, unlock: UnlockMethod) -> Result<Vec<String>, MeisoError> {
+    unlock_clients_sharing_store(client_id, &unlock).await
+}
+
+/// 自動ロックまでの無操作時間を設定（秒、Noneまたは0で無効）
+pub async fn set_auto_lock_timeout(timeout_secs: Option<u64>) -> Result<(), MeisoError> {
+    let timeout = timeout_secs.filter(|secs| *secs > 0).map(Duration::from_secs);
+    crate::session::set_idle_timeout(&tokio::runtime::Handle::current(), timeout, lock_all_clients);
+    Ok(())
+}
+
+/// 自動ロックまでの無操作時間を取得（秒、無効ならNone）
+pub fn get_auto_lock_timeout() -> Option<u64> {
+    crate::session::idle_timeout().map(|timeout| timeout.as_secs())
+}
+
+/// ユーザー操作を通知（自動ロックまでの時間をリセット）
+/// 画面操作時にFlutter側から呼ぶ。バックグラウンド同期では呼ばないこと
+pub fn notify_session_activity() {
+    crate::session::record_activity();
+}
+
+/// ロック中のクライアントがあるか
+pub async fn is_session_locked() -> Result<bool, MeisoError> {
+    let clients = NOSTR_CLIENTS.lock().await;
+    Ok(clients.values().any(|client| client.is_locked()))
+}
+
+/// すべての秘密鍵モードのクライアントをロック
+async fn lock_all_clients() {
+    let mut clients = NOSTR_CLIENTS.lock().await;
+    for (client_id, client) in clients.iter_mut() {
+        if client.has_secret_key() {
+            client.lock().await;
+            log_info!("🔒 Nostr client [{}] locked", client_id);
+        }
+    }
+}
+
+/// 指定したクライアントの鍵ストアを一度だけ復号し、同じ鍵ストアを使うロック中のクライアントに秘密鍵を戻す
+///
+/// 復号（Argon2）の間は`NOSTR_CLIENTS`のロックを離し、他のAPI呼び出しを止めない
+async fn unlock_clients_sharing_store(client_id: String, unlock: &UnlockMethod) -> Result<Vec<String>, MeisoError> {
+    let (storage_path, client_ids) = {
+        let clients = NOSTR_CLIENTS.lock().await;
+        let client = clients
+            .get(&client_id)
+            .ok_or_else(|| MeisoError::NotInitialized { client_id: client_id.clone() })?;
+        if !client.is_locked() {
+            return Ok(Vec::new());
+        }
+        let Some(storage_path) = client.key_source.clone() else {
+            log_warn!("⚠️ Nostr client [{}] was initialized without a key store - re-initialize it to unlock", client_id);
+            return Err(MeisoError::invalid_argument(
+                "No key store to unlock from. Re-initialize the client with a key store",
+            ));
+        };
+        let client_ids: Vec<String> = clients
+            .iter()
+            .filter(|(_, client)| client.is_locked() && client.key_source.as_deref() == Some(storage_path.as_str()))
+            .map(|(client_id, _)| client_id.clone())
+            .collect();
+        (storage_path, client_ids)
+    };
+
+    let secret_key = SecureKeyStore::new(storage_path.clone()).unlock(unlock).await.map_err(|e| {
+        log_error!("❌ Failed to unlock {:?}: {}", client_ids, e);
+        e
+    })?;
+    let keys = Keys::parse(secret_key.expose_secret())
+        .map_err(|e| MeisoError::invalid_key(format!("Failed to parse secret key from key store: {}", e)))?;
+
+    // 復号中に置き換え・停止されたクライアントは対象外
+    let mut unlocked = Vec::new();
+    let mut first_error = None;
+    let mut clients = NOSTR_CLIENTS.lock().await;
+    for client_id in client_ids {
+        let Some(client) = clients
+            .get_mut(&client_id)
+            .filter(|client| client.is_locked() && client.key_source.as_deref() == Some(storage_path.as_str()))
+        else {
+            log_debug!("Nostr client [{}] changed while unlocking - skipping", client_id);
+            continue;
+        };
+        match client.restore_keys(keys.clone()).await {
+            Ok(()) => {
+                log_info!("🔓 Nostr client [{}] unlocked", client_id);
+                unlocked.push(client_id);
+            }
+            Err(e) => {
+                log_error!("❌ Failed to unlock Nostr client [{}]: {}", client_id, e);
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    drop(clients);
+
+    if unlocked.is_empty() {
+        if let Some(e) = first_error {
+            return Err(e.into());
+        }
+    }
+
+    crate::session::record_activity();
+    Ok(unlocked)
+}
+
+// ========================================
+// Amber連携API
+// ========================================
+
+/// Amberから受け取った署名済みイベントを検証
+pub fn verify_amber_signature(event_json: String) -> Result<bool, MeisoError> {
+    let event: Event = serde_json::from_str(&event_json)
+        .context("Failed to parse event JSON")?;
+    
+    match event.verify() {
+        Ok(_) => {
+            log_info!("✅ Amber signature verified successfully");
+            Ok(true)
+        }
+        Err(e) => {
+            log_error!("❌ Amber signature verification failed: {}", e);
+            Ok(false)
+        }
+    }
+}
+
+/// 公開鍵のみでNostrクライアントを初期化（Amber使用時）
+/// 署名が必要な操作はAmber経由で行う
+pub async fn init_nostr_client_with_pubkey(
+    public_key_hex: String,
+    relays: Vec<String>,
+) -> Result<String, MeisoError> {
+    init_nostr_client_with_pubkey_and_id(DEFAULT_CLIENT_ID.to_string(), public_key_hex, relays, None, None).await
+}
+
+/// Amberモードで初期化（プロキシオプション付き）
+pub async fn init_nostr_client_with_pubkey_and_proxy(
+    public_key_hex: String,
+    relays: Vec<String>,
+    proxy_url: Option<String>,
+) -> Result<String, MeisoError> {
+    init_nostr_client_with_pubkey_and_id(DEFAULT_CLIENT_ID.to_string(), public_key_hex, relays, proxy_url, None).await
+}
+
+/// Amberモードで初期化（client_id指定可能）
+pub async fn init_nostr_client_with_pubkey_and_id(
+    client_id: String,
+    public_key_hex: String,
+    relays: Vec<String>,
+    proxy_url: Option<String>,
+    config: Option<ClientConfig>,
+) -> Result<String, MeisoError> {
+    Ok(init_client_with_pubkey(client_id, public_key_hex, relays, proxy_url, config).await?)
+}
+
+/// Amberモードのクライアントを作成して登録（公開鍵hexを返す）
+async fn init_client_with_pubkey(
+    client_id: String,
+    public_key_hex: String,
+    relays: Vec<String>,
+    proxy_url: Option<String>,
+    config: Option<ClientConfig>,
+) -> Result<String> {
+    let config = config.unwrap_or_else(|| ClientConfig::defaults_for(true, proxy_url.is_some()));
+    log_info!("🔧 Initializing Nostr client [{}] with public key only (Amber mode){}...",
+        client_id,
+        if proxy_url.is_some() { " with proxy" } else { "" });
+    log_debug!("Public key: {}...", &public_key_hex[..16.min(public_key_hex.len())]);
+    log_debug!("Relays: {:?}", relays);
+    if let Some(ref proxy) = proxy_url {
+        log_debug!("Proxy: {}", proxy);
+    }
+    
+    match MeisoNostrClient::new_amber_mode(public_key_hex.clone(), relays, proxy_url, config).await {
+        Ok(client) => {
+            log_info!("✅ Nostr client [{}] initialized in Amber mode", client_id);
+            
+            register_client(client_id, client).await;
+            
+            Ok(public_key_hex)
+        }
+        Err(e) => {
+            log_error!("❌ Failed to initialize Nostr client [{}] in Amber mode: {}", client_id, e);
+            Err(e)
+        }
+    }
+}
+
+
+/// 署名済みイベントをリレーに送信
+pub async fn send_signed_event(event_json: String) -> Result<EventSendResult, MeisoError> {
+    send_signed_event_with_client_id(event_json, None).await
+}
+
+/// 署名済みイベントをリレーに送信（client_id指定可能）
+pub async fn send_signed_event_with_client_id(event_json: String, client_id: Option<String>) -> Result<EventSendResult, MeisoError> {
+    let client = get_client(client_id).await?;
+    
+    // イベントをパース
+    let event: Event = serde_json::from_str(&event_json)
+        .context("Failed to parse signed event JSON")?;
+    
+    // 署名を検証
+    event.verify().context("Invalid event signature")?;
+    
+    log_info!("📤 Sending signed event to relays...");
+    log_debug!("🔍 Event kind: {}", event.kind);
+    log_debug!("🔍 Event ID: {}", note_id(&event.id));
+    log_debug!("🔍 Event pubkey: {}...", &event.pubkey.to_hex()[..16]);
+    
+    // リレーに送信（改善されたエラーハンドリング）
+    Ok(client.send_event_with_result(event).await?)
 }
 
 /// 暗号化済みcontentで未署名Todoリストイベントを作成（Kind 30001 - Amber暗号化済み用）
@@ -1313,12 +2340,12 @@ pub fn create_unsigned_encrypted_todo_list_event_with_list_id(
     public_key_hex: String,
     list_id: Option<String>,
     list_title: Option<String>,
-) -> Result<String> {
+) -> Result<String, MeisoError> {
     use serde_json::json;
     
     // 公開鍵をパース
     let public_key = PublicKey::from_hex(&public_key_hex)
-        .context("Failed to parse public key")?;
+        .map_err(MeisoError::invalid_key)?;
     
     // 現在のタイムスタンプ
     let created_at = std::time::SystemTime::now()
@@ -1351,9 +2378,9 @@ pub fn create_unsigned_encrypted_todo_list_event_with_list_id(
         "content": encrypted_content,
     });
     
-    let event_json = serde_json::to_string(&unsigned_event)?;
+    let event_json = serde_json::to_string(&unsigned_event).context("Failed to serialize unsigned event")?;
     
-    println!("📝 Created unsigned encrypted TODO list event (d='{}') for Amber signing", d_tag_value);
+    log_info!("📝 Created unsigned encrypted TODO list event (d='{}') for Amber signing", d_tag_value);
     Ok(event_json)
 }
 
@@ -1362,7 +2389,7 @@ pub fn create_unsigned_encrypted_todo_list_event_with_list_id(
 pub fn create_unsigned_encrypted_todo_list_event(
     encrypted_content: String,
     public_key_hex: String,
-) -> Result<String> {
+) -> Result<String, MeisoError> {
     create_unsigned_encrypted_todo_list_event_with_list_id(
         encrypted_content,
         public_key_hex,
@@ -1376,12 +2403,12 @@ pub fn create_unsigned_encrypted_todo_event(
     todo_id: String,
     encrypted_content: String,
     public_key_hex: String,
-) -> Result<String> {
+) -> Result<String, MeisoError> {
     use serde_json::json;
     
     // 公開鍵をパース
     let public_key = PublicKey::from_hex(&public_key_hex)
-        .context("Failed to parse public key")?;
+        .map_err(MeisoError::invalid_key)?;
     
     // 現在のタイムスタンプ
     let created_at = std::time::SystemTime::now()
@@ -1403,9 +2430,9 @@ pub fn create_unsigned_encrypted_todo_event(
         "content": encrypted_content,
     });
     
-    let event_json = serde_json::to_string(&unsigned_event)?;
+    let event_json = serde_json::to_string(&unsigned_event).context("Failed to serialize unsigned event")?;
     
-    println!("📝 Created unsigned encrypted event for Amber signing");
+    log_info!("📝 Created unsigned encrypted event for Amber signing");
     Ok(event_json)
 }
 
@@ -1437,255 +2464,245 @@ pub struct TodoListMetadata {
 }
 
 /// すべてのTodoリスト（デフォルト + カスタムリスト）を取得
-pub fn fetch_all_encrypted_todo_lists_for_pubkey(
+pub async fn fetch_all_encrypted_todo_lists_for_pubkey(
     public_key_hex: String,
-) -> Result<Vec<EncryptedTodoListEvent>> {
-    fetch_all_encrypted_todo_lists_for_pubkey_with_client_id(public_key_hex, None)
+) -> Result<Vec<EncryptedTodoListEvent>, MeisoError> {
+    fetch_all_encrypted_todo_lists_for_pubkey_with_client_id(public_key_hex, None).await
 }
 
-pub fn fetch_all_encrypted_todo_lists_for_pubkey_with_client_id(
+pub async fn fetch_all_encrypted_todo_lists_for_pubkey_with_client_id(
     public_key_hex: String,
     client_id: Option<String>,
-) -> Result<Vec<EncryptedTodoListEvent>> {
-    TOKIO_RUNTIME.block_on(async {
-        let client = get_client(client_id).await?;
-        
-        // 公開鍵をパース
-        let public_key = PublicKey::from_hex(&public_key_hex)
-            .context("Failed to parse public key")?;
-        
-        // すべてのKind 30001イベントを取得（meiso-todos + meiso-list-*）
-        let filter = Filter::new()
-            .kind(Kind::Custom(30001))
-            .author(public_key);
-        
-        let events = client
-            .client
-            .fetch_events(vec![filter], Some(Duration::from_secs(10)))
-            .await?;
-        
-        if events.is_empty() {
-            println!("⚠️ No encrypted TODO list events found");
-            return Ok(Vec::new());
-        }
-        
-        println!("📥 Found {} encrypted TODO list events", events.len());
-        
-        // 同じd tagを持つイベントが複数ある場合、最新のもの（created_atが最大）のみを保持
-        use std::collections::HashMap;
-        let mut latest_events: HashMap<String, Event> = HashMap::new();
-        
-        for event in events {
-            // d タグを取得
-            let d_tag = event.tags.iter()
-                .find(|tag| tag.kind() == TagKind::SingleLetter(SingleLetterTag::lowercase(Alphabet::D)))
-                .and_then(|tag| tag.content())
-                .map(|s| s.to_string());
-            
-            println!("🔍 Found event: d_tag={:?}, event_id={}, created_at={}", 
-                d_tag, event.id.to_hex(), event.created_at.as_u64());
-            
-            // meiso-todos または meiso-list-* のみを処理（meiso-settings等は除外）
-            if let Some(ref d_value) = d_tag {
-                if d_value.starts_with("meiso-todos") || d_value.starts_with("meiso-list-") {
-                    // 既存のイベントと比較して、新しい方を保持
-                    if let Some(existing_event) = latest_events.get(d_value) {
-                        if event.created_at > existing_event.created_at {
-                            println!("🔄 Replacing older event for d='{}' (old: {}, new: {})", 
-                                d_value, existing_event.created_at.as_u64(), event.created_at.as_u64());
-                            latest_events.insert(d_value.clone(), event);
-                        } else {
-                            println!("⏭️  Skipping older event for d='{}' (keeping: {})", 
-                                d_value, existing_event.created_at.as_u64());
-                        }
-                    } else {
-                        println!("✅ Adding TODO list event: d='{}', event_id={}, created_at={}", 
-                            d_value, event.id.to_hex(), event.created_at.as_u64());
+) -> Result<Vec<EncryptedTodoListEvent>, MeisoError> {
+    let client = get_client(client_id).await?;
+    
+    // 公開鍵をパース
+    let public_key = PublicKey::from_hex(&public_key_hex)
+        .map_err(MeisoError::invalid_key)?;
+    
+    // すべてのKind 30001イベントを取得（meiso-todos + meiso-list-*）
+    let filter = Filter::new()
+        .kind(Kind::Custom(30001))
+        .author(public_key);
+    
+    let events = client
+        .fetch_events(filter)
+        .await?;
+    
+    if events.is_empty() {
+        log_info!("⚠️ No encrypted TODO list events found");
+        return Ok(Vec::new());
+    }
+    
+    log_info!("📥 Found {} encrypted TODO list events", events.len());
+    
+    // 同じd tagを持つイベントが複数ある場合、最新のもの（created_atが最大）のみを保持
+    use std::collections::HashMap;
+    let mut latest_events: HashMap<String, Event> = HashMap::new();
+    
+    for event in events {
+        // d タグを取得
+        let d_tag = event.tags.iter()
+            .find(|tag| tag.kind() == TagKind::SingleLetter(SingleLetterTag::lowercase(Alphabet::D)))
+            .and_then(|tag| tag.content())
+            .map(|s| s.to_string());
+        
+        log_debug!("🔍 Found event: d_tag={:?}, event_id={}, created_at={}", 
+            d_tag, note_id(&event.id), event.created_at.as_u64());
+        
+        // meiso-todos または meiso-list-* のみを処理（meiso-settings等は除外）
+        if let Some(ref d_value) = d_tag {
+            if d_value.starts_with("meiso-todos") || d_value.starts_with("meiso-list-") {
+                // 既存のイベントと比較して、新しい方を保持
+                if let Some(existing_event) = latest_events.get(d_value) {
+                    if event.created_at > existing_event.created_at {
+                        log_debug!("🔄 Replacing older event for d='{}' (old: {}, new: {})", 
+                            d_value, existing_event.created_at.as_u64(), event.created_at.as_u64());
                         latest_events.insert(d_value.clone(), event);
+                    } else {
+                        log_debug!("⏭️  Skipping older event for d='{}' (keeping: {})", 
+                            d_value, existing_event.created_at.as_u64());
                     }
                 } else {
-                    println!("⏭️  Skipping event with d='{}' (not a TODO list)", d_value);
+                    log_debug!("✅ Adding TODO list event: d='{}', event_id={}, created_at={}", 
+                        d_value, note_id(&event.id), event.created_at.as_u64());
+                    latest_events.insert(d_value.clone(), event);
                 }
             } else {
-                println!("⏭️  Skipping event with no d tag");
+                log_debug!("⏭️  Skipping event with d='{}' (not a TODO list)", d_value);
             }
+        } else {
+            log_debug!("⏭️  Skipping event with no d tag");
         }
-        
-        println!("📋 After deduplication: {} unique TODO lists", latest_events.len());
-        
-        // 最新のイベントのみを返す
-        let list_events: Vec<EncryptedTodoListEvent> = latest_events.into_iter()
-            .map(|(d_tag, event)| {
-                // title タグを取得
-                let title = event.tags.iter()
-                    .find(|tag| tag.kind() == TagKind::Custom(std::borrow::Cow::Borrowed("title")))
-                    .and_then(|tag| tag.content())
-                    .map(|s| s.to_string());
+    }
+    
+    log_debug!("📋 After deduplication: {} unique TODO lists", latest_events.len());
+    
+    // 最新のイベントのみを返す
+    let list_events: Vec<EncryptedTodoListEvent> = latest_events.into_iter()
+        .map(|(d_tag, event)| {
+            // title タグを取得
+            let title = event.tags.iter()
+                .find(|tag| tag.kind() == TagKind::Custom(std::borrow::Cow::Borrowed("title")))
+                .and_then(|tag| tag.content())
+                .map(|s| s.to_string());
+            
+            log_debug!("📤 Final event: d='{}', title={:?}, event_id={}, created_at={}", 
+                d_tag, title, note_id(&event.id), event.created_at.as_u64());
                 
-                println!("📤 Final event: d='{}', title={:?}, event_id={}, created_at={}", 
-                    d_tag, title, event.id.to_hex(), event.created_at.as_u64());
-                    
-                EncryptedTodoListEvent {
-                    event_id: event.id.to_hex(),
-                    encrypted_content: event.content.clone(),
-                    created_at: event.created_at.as_u64() as i64,
-                    list_id: Some(d_tag),
-                    title,
-                }
-            })
-            .collect();
-        
-        println!("✅ Fetched {} TODO list events for decryption", list_events.len());
-        Ok(list_events)
-    })
+            EncryptedTodoListEvent {
+                event_id: event.id.to_hex(),
+                encrypted_content: event.content.clone(),
+                created_at: event.created_at.as_u64() as i64,
+                list_id: Some(d_tag),
+                title,
+            }
+        })
+        .collect();
+    
+    log_info!("✅ Fetched {} TODO list events for decryption", list_events.len());
+    Ok(list_events)
 }
 
 /// すべてのTodoリストのメタデータ（d tag, title）を取得（通常モード用）
-pub fn fetch_all_todo_list_metadata() -> Result<Vec<TodoListMetadata>> {
-    fetch_all_todo_list_metadata_with_client_id(None)
+pub async fn fetch_all_todo_list_metadata() -> Result<Vec<TodoListMetadata>, MeisoError> {
+    fetch_all_todo_list_metadata_with_client_id(None).await
 }
 
-pub fn fetch_all_todo_list_metadata_with_client_id(
+pub async fn fetch_all_todo_list_metadata_with_client_id(
     client_id: Option<String>,
-) -> Result<Vec<TodoListMetadata>> {
-    TOKIO_RUNTIME.block_on(async {
-        let client = get_client(client_id).await?;
-        
-        // 秘密鍵モードのみサポート（Amberモードでは使用しない）
-        let keys = client.keys.as_ref()
-            .context("Secret key required for fetching metadata")?;
-        
-        // すべてのKind 30001イベントを取得（meiso-todos + meiso-list-*）
-        let filter = Filter::new()
-            .kind(Kind::Custom(30001))
-            .author(keys.public_key());
-        
-        let events = client
-            .client
-            .fetch_events(vec![filter], Some(Duration::from_secs(10)))
-            .await?;
-        
-        if events.is_empty() {
-            println!("⚠️ No TODO list events found");
-            return Ok(Vec::new());
-        }
-        
-        println!("📥 Found {} TODO list events", events.len());
-        
-        // 同じd tagを持つイベントが複数ある場合、最新のもの（created_atが最大）のみを保持
-        use std::collections::HashMap;
-        let mut latest_events: HashMap<String, Event> = HashMap::new();
-        
-        for event in events {
-            // d タグを取得
-            let d_tag = event.tags.iter()
-                .find(|tag| tag.kind() == TagKind::SingleLetter(SingleLetterTag::lowercase(Alphabet::D)))
-                .and_then(|tag| tag.content())
-                .map(|s| s.to_string());
-            
-            println!("🔍 Found event: d_tag={:?}, event_id={}, created_at={}", 
-                d_tag, event.id.to_hex(), event.created_at.as_u64());
-            
-            // meiso-todos または meiso-list-* のみを処理（meiso-settings等は除外）
-            if let Some(ref d_value) = d_tag {
-                if d_value.starts_with("meiso-todos") || d_value.starts_with("meiso-list-") {
-                    // 既存のイベントと比較して、新しい方を保持
-                    if let Some(existing_event) = latest_events.get(d_value) {
-                        if event.created_at > existing_event.created_at {
-                            println!("🔄 Replacing older event for d='{}' (old: {}, new: {})", 
-                                d_value, existing_event.created_at.as_u64(), event.created_at.as_u64());
-                            latest_events.insert(d_value.clone(), event);
-                        } else {
-                            println!("⏭️  Skipping older event for d='{}' (keeping: {})", 
-                                d_value, existing_event.created_at.as_u64());
-                        }
-                    } else {
-                        println!("✅ Adding TODO list event: d='{}', event_id={}, created_at={}", 
-                            d_value, event.id.to_hex(), event.created_at.as_u64());
+) -> Result<Vec<TodoListMetadata>, MeisoError> {
+    let client = get_client(client_id).await?;
+    
+    // 秘密鍵モードのみサポート（Amberモードでは使用しない）
+    let keys = client.signing_keys()?;
+    
+    // すべてのKind 30001イベントを取得（meiso-todos + meiso-list-*）
+    let filter = Filter::new()
+        .kind(Kind::Custom(30001))
+        .author(keys.public_key());
+    
+    let events = client
+        .fetch_events(filter)
+        .await?;
+    
+    if events.is_empty() {
+        log_info!("⚠️ No TODO list events found");
+        return Ok(Vec::new());
+    }
+    
+    log_info!("📥 Found {} TODO list events", events.len());
+    
+    // 同じd tagを持つイベントが複数ある場合、最新のもの（created_atが最大）のみを保持
+    use std::collections::HashMap;
+    let mut latest_events: HashMap<String, Event> = HashMap::new();
+    
+    for event in events {
+        // d タグを取得
+        let d_tag = event.tags.iter()
+            .find(|tag| tag.kind() == TagKind::SingleLetter(SingleLetterTag::lowercase(Alphabet::D)))
+            .and_then(|tag| tag.content())
+            .map(|s| s.to_string());
+        
+        log_debug!("🔍 Found event: d_tag={:?}, event_id={}, created_at={}", 
+            d_tag, note_id(&event.id), event.created_at.as_u64());
+        
+        // meiso-todos または meiso-list-* のみを処理（meiso-settings等は除外）
+        if let Some(ref d_value) = d_tag {
+            if d_value.starts_with("meiso-todos") || d_value.starts_with("meiso-list-") {
+                // 既存のイベントと比較して、新しい方を保持
+                if let Some(existing_event) = latest_events.get(d_value) {
+                    if event.created_at > existing_event.created_at {
+                        log_debug!("🔄 Replacing older event for d='{}' (old: {}, new: {})", 
+                            d_value, existing_event.created_at.as_u64(), event.created_at.as_u64());
                         latest_events.insert(d_value.clone(), event);
+                    } else {
+                        log_debug!("⏭️  Skipping older event for d='{}' (keeping: {})", 
+                            d_value, existing_event.created_at.as_u64());
                     }
                 } else {
-                    println!("⏭️  Skipping event with d='{}' (not a TODO list)", d_value);
+                    log_debug!("✅ Adding TODO list event: d='{}', event_id={}, created_at={}", 
+                        d_value, note_id(&event.id), event.created_at.as_u64());
+                    latest_events.insert(d_value.clone(), event);
                 }
             } else {
-                println!("⏭️  Skipping event with no d tag");
+                log_debug!("⏭️  Skipping event with d='{}' (not a TODO list)", d_value);
             }
+        } else {
+            log_debug!("⏭️  Skipping event with no d tag");
         }
-        
-        println!("📋 After deduplication: {} unique TODO lists", latest_events.len());
-        
-        // メタデータのみを返す
-        let metadata_list: Vec<TodoListMetadata> = latest_events.into_iter()
-            .map(|(d_tag, event)| {
-                // title タグを取得
-                let title = event.tags.iter()
-                    .find(|tag| tag.kind() == TagKind::Custom(std::borrow::Cow::Borrowed("title")))
-                    .and_then(|tag| tag.content())
-                    .map(|s| s.to_string());
+    }
+    
+    log_debug!("📋 After deduplication: {} unique TODO lists", latest_events.len());
+    
+    // メタデータのみを返す
+    let metadata_list: Vec<TodoListMetadata> = latest_events.into_iter()
+        .map(|(d_tag, event)| {
+            // title タグを取得
+            let title = event.tags.iter()
+                .find(|tag| tag.kind() == TagKind::Custom(std::borrow::Cow::Borrowed("title")))
+                .and_then(|tag| tag.content())
+                .map(|s| s.to_string());
+            
+            log_debug!("📤 Metadata: d='{}', title={:?}, event_id={}, created_at={}", 
+                d_tag, title, note_id(&event.id), event.created_at.as_u64());
                 
-                println!("📤 Metadata: d='{}', title={:?}, event_id={}, created_at={}", 
-                    d_tag, title, event.id.to_hex(), event.created_at.as_u64());
-                    
-                TodoListMetadata {
-                    event_id: event.id.to_hex(),
-                    created_at: event.created_at.as_u64() as i64,
-                    list_id: Some(d_tag),
-                    title,
-                }
-            })
-            .collect();
-        
-        println!("✅ Fetched {} TODO list metadata", metadata_list.len());
-        Ok(metadata_list)
-    })
+            TodoListMetadata {
+                event_id: event.id.to_hex(),
+                created_at: event.created_at.as_u64() as i64,
+                list_id: Some(d_tag),
+                title,
+            }
+        })
+        .collect();
+    
+    log_info!("✅ Fetched {} TODO list metadata", metadata_list.len());
+    Ok(metadata_list)
 }
 
 /// デフォルトTodoリスト（meiso-todos）のみを取得（互換性のため残す）
-pub fn fetch_encrypted_todo_list_for_pubkey(
+pub async fn fetch_encrypted_todo_list_for_pubkey(
     public_key_hex: String,
-) -> Result<Option<EncryptedTodoListEvent>> {
-    fetch_encrypted_todo_list_for_pubkey_with_client_id(public_key_hex, None)
+) -> Result<Option<EncryptedTodoListEvent>, MeisoError> {
+    fetch_encrypted_todo_list_for_pubkey_with_client_id(public_key_hex, None).await
 }
 
-pub fn fetch_encrypted_todo_list_for_pubkey_with_client_id(
+pub async fn fetch_encrypted_todo_list_for_pubkey_with_client_id(
     public_key_hex: String,
     client_id: Option<String>,
-) -> Result<Option<EncryptedTodoListEvent>> {
-    TOKIO_RUNTIME.block_on(async {
-        let client = get_client(client_id).await?;
-        
-        // 公開鍵をパース
-        let public_key = PublicKey::from_hex(&public_key_hex)
-            .context("Failed to parse public key")?;
-        
-        let filter = Filter::new()
-            .kind(Kind::Custom(30001))
-            .author(public_key)
-            .custom_tag(
-                SingleLetterTag::lowercase(Alphabet::D),
-                vec!["meiso-todos".to_string()],
-            );
-        
-        let events = client
-            .client
-            .fetch_events(vec![filter], Some(Duration::from_secs(10)))
-            .await?;
-        
-        // 最新のイベント（Replaceable eventなので1つだけのはず）
-        if let Some(event) = events.first() {
-            println!("📥 Fetched encrypted TODO list event (default list only)");
-            Ok(Some(EncryptedTodoListEvent {
-                event_id: event.id.to_hex(),
-                encrypted_content: event.content.clone(),
-                created_at: event.created_at.as_u64() as i64,
-                list_id: Some("meiso-todos".to_string()),
-                title: Some("My TODO List".to_string()),
-            }))
-        } else {
-            println!("⚠️ No encrypted TODO list event found (default list)");
-            Ok(None)
-        }
-    })
+) -> Result<Option<EncryptedTodoListEvent>, MeisoError> {
+    let client = get_client(client_id).await?;
+    
+    // 公開鍵をパース
+    let public_key = PublicKey::from_hex(&public_key_hex)
+        .map_err(MeisoError::invalid_key)?;
+    
+    let filter = Filter::new()
+        .kind(Kind::Custom(30001))
+        .author(public_key)
+        .custom_tag(
+            SingleLetterTag::lowercase(Alphabet::D),
+            vec!["meiso-todos".to_string()],
+        );
+    
+    let events = client
+        .fetch_events(filter)
+        .await?;
+    
+    // 最新のイベント（Replaceable eventなので1つだけのはず）
+    if let Some(event) = events.first() {
+        log_info!("📥 Fetched encrypted TODO list event (default list only)");
+        Ok(Some(EncryptedTodoListEvent {
+            event_id: event.id.to_hex(),
+            encrypted_content: event.content.clone(),
+            created_at: event.created_at.as_u64() as i64,
+            list_id: Some("meiso-todos".to_string()),
+            title: Some("My TODO List".to_string()),
+        }))
+    } else {
+        log_info!("⚠️ No encrypted TODO list event found (default list)");
+        Ok(None)
+    }
 }
 
 /// 公開鍵だけで暗号化されたTodoイベントを取得（Amber復号化用 - 旧実装 Kind 30078）
@@ -1698,91 +2715,89 @@ pub struct EncryptedTodoEvent {
     pub d_tag: String,
 }
 
-pub fn fetch_encrypted_todos_for_pubkey(
+pub async fn fetch_encrypted_todos_for_pubkey(
     public_key_hex: String,
-) -> Result<Vec<EncryptedTodoEvent>> {
-    fetch_encrypted_todos_for_pubkey_with_client_id(public_key_hex, None)
+) -> Result<Vec<EncryptedTodoEvent>, MeisoError> {
+    fetch_encrypted_todos_for_pubkey_with_client_id(public_key_hex, None).await
 }
 
-pub fn fetch_encrypted_todos_for_pubkey_with_client_id(
+pub async fn fetch_encrypted_todos_for_pubkey_with_client_id(
     public_key_hex: String,
     client_id: Option<String>,
-) -> Result<Vec<EncryptedTodoEvent>> {
-    TOKIO_RUNTIME.block_on(async {
-        let client = get_client(client_id).await?;
-        
-        // 公開鍵をパース
-        let public_key = PublicKey::from_hex(&public_key_hex)
-            .context("Failed to parse public key")?;
-        
-        let filter = Filter::new()
-            .kind(Kind::Custom(30078))
-            .author(public_key);
-        
-        let events = client
-            .client
-            .fetch_events(vec![filter], Some(Duration::from_secs(10)))
-            .await?;
-        
-        let mut encrypted_todos = Vec::new();
-        
-        for event in events {
-            // dタグを取得
-            let d_tag = event
-                .tags
-                .iter()
-                .find(|tag| tag.kind() == TagKind::SingleLetter(SingleLetterTag::lowercase(Alphabet::D)))
-                .and_then(|tag| tag.content())
-                .unwrap_or("")
-                .to_string();
-            
-            // `todo-`で始まるdタグのイベントはスキップ
-            if d_tag.starts_with("todo-") {
-                println!("⏭️  Skipping Kind 30078 event with d tag starting with 'todo-': {}", event.id.to_hex());
-                continue;
-            }
-            
-            encrypted_todos.push(EncryptedTodoEvent {
-                event_id: event.id.to_hex(),
-                encrypted_content: event.content.clone(),
-                created_at: event.created_at.as_u64() as i64,
-                d_tag,
-            });
+) -> Result<Vec<EncryptedTodoEvent>, MeisoError> {
+    let client = get_client(client_id).await?;
+    
+    // 公開鍵をパース
+    let public_key = PublicKey::from_hex(&public_key_hex)
+        .map_err(MeisoError::invalid_key)?;
+    
+    let filter = Filter::new()
+        .kind(Kind::Custom(30078))
+        .author(public_key);
+    
+    let events = client
+        .fetch_events(filter)
+        .await?;
+    
+    let mut encrypted_todos = Vec::new();
+    
+    for event in events {
+        // dタグを取得
+        let d_tag = event
+            .tags
+            .iter()
+            .find(|tag| tag.kind() == TagKind::SingleLetter(SingleLetterTag::lowercase(Alphabet::D)))
+            .and_then(|tag| tag.content())
+            .unwrap_or("")
+            .to_string();
+        
+        // `todo-`で始まるdタグのイベントはスキップ
+        if d_tag.starts_with("todo-") {
+            log_debug!("⏭️  Skipping Kind 30078 event with d tag starting with 'todo-': {}", note_id(&event.id));
+            continue;
         }
         
-        println!("📥 Fetched {} encrypted todo events (after filtering)", encrypted_todos.len());
-        Ok(encrypted_todos)
-    })
+        encrypted_todos.push(EncryptedTodoEvent {
+            event_id: event.id.to_hex(),
+            encrypted_content: event.content.clone(),
+            created_at: event.created_at.as_u64() as i64,
+            d_tag,
+        });
+    }
+    
+    log_info!("📥 Fetched {} encrypted todo events (after filtering)", encrypted_todos.len());
+    Ok(encrypted_todos)
 }
 
 /// npub形式の公開鍵をhex形式に変換
-pub fn npub_to_hex(npub: String) -> Result<String> {
+pub fn npub_to_hex(npub: String) -> Result<String, MeisoError> {
     // npub形式でない場合（すでにhex形式の可能性）
     if !npub.starts_with("npub1") {
         // 64文字のhex文字列かチェック
         if npub.len() == 64 && npub.chars().all(|c| c.is_ascii_hexdigit()) {
             return Ok(npub); // すでにhex形式
         }
-        return Err(anyhow::anyhow!("Invalid public key format: expected npub1... or 64-char hex, got: {}", &npub[..10.min(npub.len())]));
+        return Err(MeisoError::invalid_key(format!(
+            "expected npub1... or 64-char hex, got: {}",
+            &npub[..10.min(npub.len())]
+        )));
     }
     
-    let public_key = PublicKey::parse(&npub)
-        .context("Failed to parse npub format public key")?;
+    let public_key = PublicKey::parse(&npub).map_err(MeisoError::invalid_key)?;
     
     Ok(public_key.to_hex())
 }
 
 /// hex形式の公開鍵をnpub形式に変換
-pub fn hex_to_npub(hex: String) -> Result<String> {
+pub fn hex_to_npub(hex: String) -> Result<String, MeisoError> {
     // すでにnpub形式の場合
     if hex.starts_with("npub1") {
         return Ok(hex);
     }
     
-    let public_key = PublicKey::from_hex(&hex)
-        .context("Failed to parse hex format public key")?;
+    let public_key = PublicKey::from_hex(&hex).map_err(MeisoError::invalid_key)?;
     
-    Ok(public_key.to_bech32()?)
+    public_key.to_bech32().map_err(MeisoError::invalid_key)
 }
 
 // ========================================
@@ -1790,41 +2805,37 @@ pub fn hex_to_npub(hex: String) -> Result<String> {
 // ========================================
 
 /// アプリ設定を保存（Kind 30078 - Application-specific data）
-pub fn save_app_settings(settings: AppSettings) -> Result<EventSendResult> {
-    save_app_settings_with_client_id(settings, None)
+pub async fn save_app_settings(settings: AppSettings) -> Result<EventSendResult, MeisoError> {
+    save_app_settings_with_client_id(settings, None).await
 }
 
 /// アプリ設定を保存（client_id指定可能）
-pub fn save_app_settings_with_client_id(settings: AppSettings, client_id: Option<String>) -> Result<EventSendResult> {
-    TOKIO_RUNTIME.block_on(async {
-        let client = get_client(client_id).await?;
-        client.create_app_settings(settings).await
-    })
+pub async fn save_app_settings_with_client_id(settings: AppSettings, client_id: Option<String>) -> Result<EventSendResult, MeisoError> {
+    let client = get_client(client_id).await?;
+    Ok(client.create_app_settings(settings).await?)
 }
 
 /// アプリ設定を同期（Kind 30078）
-pub fn sync_app_settings() -> Result<Option<AppSettings>> {
-    sync_app_settings_with_client_id(None)
+pub async fn sync_app_settings() -> Result<Option<AppSettings>, MeisoError> {
+    sync_app_settings_with_client_id(None).await
 }
 
 /// アプリ設定を同期（client_id指定可能）
-pub fn sync_app_settings_with_client_id(client_id: Option<String>) -> Result<Option<AppSettings>> {
-    TOKIO_RUNTIME.block_on(async {
-        let client = get_client(client_id).await?;
-        client.sync_app_settings().await
-    })
+pub async fn sync_app_settings_with_client_id(client_id: Option<String>) -> Result<Option<AppSettings>, MeisoError> {
+    let client = get_client(client_id).await?;
+    Ok(client.sync_app_settings().await?)
 }
 
 /// 暗号化済みcontentで未署名アプリ設定イベントを作成（Amber暗号化済み用）
 pub fn create_unsigned_encrypted_app_settings_event(
     encrypted_content: String,
     public_key_hex: String,
-) -> Result<String> {
+) -> Result<String, MeisoError> {
     use serde_json::json;
     
     // 公開鍵をパース
     let public_key = PublicKey::from_hex(&public_key_hex)
-        .context("Failed to parse public key")?;
+        .map_err(MeisoError::invalid_key)?;
     
     // 現在のタイムスタンプ
     let created_at = std::time::SystemTime::now()
@@ -1846,9 +2857,9 @@ pub fn create_unsigned_encrypted_app_settings_event(
         "content": encrypted_content,
     });
     
-    let event_json = serde_json::to_string(&unsigned_event)?;
+    let event_json = serde_json::to_string(&unsigned_event).context("Failed to serialize unsigned event")?;
     
-    println!("📝 Created unsigned encrypted app settings event (Kind 30078) for Amber signing");
+    log_info!("📝 Created unsigned encrypted app settings event (Kind 30078) for Amber signing");
     Ok(event_json)
 }
 
@@ -1856,12 +2867,12 @@ pub fn create_unsigned_encrypted_app_settings_event(
 pub fn create_unsigned_relay_list_event(
     relays: Vec<String>,
     public_key_hex: String,
-) -> Result<String> {
+) -> Result<String, MeisoError> {
     use serde_json::json;
     
     // 公開鍵をパース
     let public_key = PublicKey::from_hex(&public_key_hex)
-        .context("Failed to parse public key")?;
+        .map_err(MeisoError::invalid_key)?;
     
     // 現在のタイムスタンプ
     let created_at = std::time::SystemTime::now()
@@ -1886,9 +2897,9 @@ pub fn create_unsigned_relay_list_event(
         "content": "",
     });
     
-    let event_json = serde_json::to_string(&unsigned_event)?;
+    let event_json = serde_json::to_string(&unsigned_event).context("Failed to serialize unsigned event")?;
     
-    println!("📝 Created unsigned relay list event (Kind 10002) for Amber signing");
+    log_info!("📝 Created unsigned relay list event (Kind 10002) for Amber signing");
     Ok(event_json)
 }
 
@@ -1900,49 +2911,46 @@ pub struct EncryptedAppSettingsEvent {
     pub created_at: i64,
 }
 
-pub fn fetch_encrypted_app_settings_for_pubkey(
+pub async fn fetch_encrypted_app_settings_for_pubkey(
     public_key_hex: String,
-) -> Result<Option<EncryptedAppSettingsEvent>> {
-    fetch_encrypted_app_settings_for_pubkey_with_client_id(public_key_hex, None)
+) -> Result<Option<EncryptedAppSettingsEvent>, MeisoError> {
+    fetch_encrypted_app_settings_for_pubkey_with_client_id(public_key_hex, None).await
 }
 
-pub fn fetch_encrypted_app_settings_for_pubkey_with_client_id(
+pub async fn fetch_encrypted_app_settings_for_pubkey_with_client_id(
     public_key_hex: String,
     client_id: Option<String>,
-) -> Result<Option<EncryptedAppSettingsEvent>> {
-    TOKIO_RUNTIME.block_on(async {
-        let client = get_client(client_id).await?;
-        
-        // 公開鍵をパース
-        let public_key = PublicKey::from_hex(&public_key_hex)
-            .context("Failed to parse public key")?;
-        
-        let filter = Filter::new()
-            .kind(Kind::Custom(30078))
-            .author(public_key)
-            .custom_tag(
-                SingleLetterTag::lowercase(Alphabet::D),
-                vec!["meiso-settings".to_string()],
-            );
-        
-        let events = client
-            .client
-            .fetch_events(vec![filter], Some(Duration::from_secs(10)))
-            .await?;
-        
-        // 最新のイベント（Replaceable eventなので1つだけのはず）
-        if let Some(event) = events.first() {
-            println!("📥 Fetched encrypted app settings event");
-            Ok(Some(EncryptedAppSettingsEvent {
-                event_id: event.id.to_hex(),
-                encrypted_content: event.content.clone(),
-                created_at: event.created_at.as_u64() as i64,
-            }))
-        } else {
-            println!("⚠️ No encrypted app settings event found");
-            Ok(None)
-        }
-    })
+) -> Result<Option<EncryptedAppSettingsEvent>, MeisoError> {
+    let client = get_client(client_id).await?;
+    
+    // 公開鍵をパース
+    let public_key = PublicKey::from_hex(&public_key_hex)
+        .map_err(MeisoError::invalid_key)?;
+    
+    let filter = Filter::new()
+        .kind(Kind::Custom(30078))
+        .author(public_key)
+        .custom_tag(
+            SingleLetterTag::lowercase(Alphabet::D),
+            vec!["meiso-settings".to_string()],
+        );
+    
+    let events = client
+        .fetch_events(filter)
+        .await?;
+    
+    // 最新のイベント（Replaceable eventなので1つだけのはず）
+    if let Some(event) = events.first() {
+        log_info!("📥 Fetched encrypted app settings event");
+        Ok(Some(EncryptedAppSettingsEvent {
+            event_id: event.id.to_hex(),
+            encrypted_content: event.content.clone(),
+            created_at: event.created_at.as_u64() as i64,
+        }))
+    } else {
+        log_info!("⚠️ No encrypted app settings event found");
+        Ok(None)
+    }
 }
 
 // ========================================
@@ -1950,42 +2958,36 @@ pub fn fetch_encrypted_app_settings_for_pubkey_with_client_id(
 // ========================================
 
 /// リレーリストをNostrに保存（Kind 10002 - Relay List Metadata）
-pub fn save_relay_list(relays: Vec<String>) -> Result<EventSendResult> {
-    save_relay_list_with_client_id(relays, None)
+pub async fn save_relay_list(relays: Vec<String>) -> Result<EventSendResult, MeisoError> {
+    save_relay_list_with_client_id(relays, None).await
 }
 
 /// リレーリストをNostrに保存（client_id指定可能）
-pub fn save_relay_list_with_client_id(relays: Vec<String>, client_id: Option<String>) -> Result<EventSendResult> {
-    TOKIO_RUNTIME.block_on(async {
-        let client = get_client(client_id).await?;
-        client.save_relay_list(relays).await
-    })
+pub async fn save_relay_list_with_client_id(relays: Vec<String>, client_id: Option<String>) -> Result<EventSendResult, MeisoError> {
+    let client = get_client(client_id).await?;
+    Ok(client.save_relay_list(relays).await?)
 }
 
 /// リレーリストをNostrから同期（Kind 10002）
-pub fn sync_relay_list() -> Result<Vec<String>> {
-    sync_relay_list_with_client_id(None)
+pub async fn sync_relay_list() -> Result<Vec<String>, MeisoError> {
+    sync_relay_list_with_client_id(None).await
 }
 
 /// リレーリストをNostrから同期（client_id指定可能）
-pub fn sync_relay_list_with_client_id(client_id: Option<String>) -> Result<Vec<String>> {
-    TOKIO_RUNTIME.block_on(async {
-        let client = get_client(client_id).await?;
-        client.sync_relay_list().await
-    })
+pub async fn sync_relay_list_with_client_id(client_id: Option<String>) -> Result<Vec<String>, MeisoError> {
+    let client = get_client(client_id).await?;
+    Ok(client.sync_relay_list().await?)
 }
 
 /// リレーリストを動的に更新（リアルタイム反映）
-pub fn update_relay_list(relays: Vec<String>) -> Result<()> {
-    update_relay_list_with_client_id(relays, None)
+pub async fn update_relay_list(relays: Vec<String>) -> Result<(), MeisoError> {
+    update_relay_list_with_client_id(relays, None).await
 }
 
 /// リレーリストを動的に更新（client_id指定可能）
-pub fn update_relay_list_with_client_id(relays: Vec<String>, client_id: Option<String>) -> Result<()> {
-    TOKIO_RUNTIME.block_on(async {
-        let client = get_client(client_id).await?;
-        client.update_relay_list(relays).await
-    })
+pub async fn update_relay_list_with_client_id(relays: Vec<String>, client_id: Option<String>) -> Result<(), MeisoError> {
+    let client = get_client(client_id).await?;
+    Ok(client.update_relay_list(relays).await?)
 }
 
 // ========================================
@@ -1993,70 +2995,68 @@ pub fn update_relay_list_with_client_id(relays: Vec<String>, client_id: Option<S
 // ========================================
 
 /// 指定したイベントIDのリストを削除（Kind 5削除イベントを送信）
-pub fn delete_events(
+pub async fn delete_events(
     event_ids: Vec<String>,
     reason: Option<String>,
-) -> Result<EventSendResult> {
-    delete_events_with_client_id(event_ids, reason, None)
+) -> Result<EventSendResult, MeisoError> {
+    delete_events_with_client_id(event_ids, reason, None).await
 }
 
 /// イベント削除（client_id指定可能）
-pub fn delete_events_with_client_id(
+pub async fn delete_events_with_client_id(
     event_ids: Vec<String>,
     reason: Option<String>,
     client_id: Option<String>,
-) -> Result<EventSendResult> {
-    TOKIO_RUNTIME.block_on(async {
-        let client = get_client(client_id).await?;
-        
-        if event_ids.is_empty() {
-            return Err(anyhow::anyhow!("削除するイベントIDが指定されていません"));
-        }
-        
-        println!("🗑️ Deleting {} events...", event_ids.len());
-        
-        // イベントIDをEventIdに変換
-        let mut event_id_objects = Vec::new();
-        for id_str in &event_ids {
-            match EventId::from_hex(id_str) {
-                Ok(event_id) => event_id_objects.push(event_id),
-                Err(e) => {
-                    eprintln!("⚠️ Invalid event ID {}: {}", id_str, e);
-                    continue;
-                }
+) -> Result<EventSendResult, MeisoError> {
+    let client = get_client(client_id).await?;
+    
+    if event_ids.is_empty() {
+        return Err(MeisoError::invalid_argument("no event IDs to delete"));
+    }
+    
+    log_info!("🗑️ Deleting {} events...", event_ids.len());
+    
+    // イベントIDをEventIdに変換
+    let mut event_id_objects = Vec::new();
+    for id_str in &event_ids {
+        match EventId::from_hex(id_str) {
+            Ok(event_id) => event_id_objects.push(event_id),
+            Err(e) => {
+                log_warn!("⚠️ Invalid event ID {}: {}", id_str, e);
+                continue;
             }
         }
-        
-        if event_id_objects.is_empty() {
-            return Err(anyhow::anyhow!("有効なイベントIDがありません"));
-        }
-        
-        if let ClientMode::Amber { .. } = client.mode {
-            return Err(anyhow::anyhow!("Cannot delete events in Amber mode"));
-        }
-        
-        let keys = client.keys.as_ref()
-            .context("Secret key required for deletion")?;
-        
-        // Kind 5削除イベントを作成
-        let content = reason.unwrap_or_default();
-        
-        // イベントIDを'e'タグとして追加
-        let tags: Vec<Tag> = event_id_objects
-            .iter()
-            .map(|id| Tag::event(*id))
-            .collect();
-        
-        let event = EventBuilder::new(Kind::EventDeletion, content)
-            .tags(tags)
-            .sign(keys)
-            .await?;
-        
-        println!("📤 Sending Kind 5 deletion event...");
-        
-        // リレーに送信（改善されたエラーハンドリング）
-        client.send_event_with_result(event).await
-    })
+    }
+    
+    if event_id_objects.is_empty() {
+        return Err(MeisoError::invalid_argument("no valid event IDs to delete"));
+    }
+    
+    if let ClientMode::Amber { .. } = client.mode {
+        return Err(MeisoError::unsupported_in_amber_mode("delete_events"));
+    }
+    
+    let keys = client.signing_keys()?;
+    
+    // Kind 5削除イベントを作成
+    let content = reason.unwrap_or_default();
+    
+    // イベントIDを'e'タグとして追加
+    let tags: Vec<Tag> = event_id_objects
+        .iter()
+        .map(|id| Tag::event(*id))
+        .collect();
+    
+    let event = EventBuilder::new(Kind::EventDeletion, content)
+        .tags(tags)
+        .sign(keys)
+        .await
+        .context("Failed to sign deletion event")?;
+    
+    log_info!("📤 Sending Kind 5 deletion event...");
+    
+    // リレーに送信（改善されたエラーハンドリング）
+    Ok(client.send_event_with_result(event).await?)
 }
 
 // ========================================
@@ -2064,103 +3064,102 @@ pub fn delete_events_with_client_id(
 // ========================================
 
 /// Subscriptionを開始（Todo/設定などのリアルタイム更新）
-pub fn start_subscription(filters_json: String) -> Result<SubscriptionInfo> {
-    start_subscription_with_client_id(filters_json, None)
+pub async fn start_subscription(filters_json: String) -> Result<SubscriptionInfo, MeisoError> {
+    start_subscription_with_client_id(filters_json, None).await
 }
 
 /// Subscriptionを開始（client_id指定可能）
-pub fn start_subscription_with_client_id(
+pub async fn start_subscription_with_client_id(
     filters_json: String,
     client_id: Option<String>,
-) -> Result<SubscriptionInfo> {
-    TOKIO_RUNTIME.block_on(async {
-        let client = get_client(client_id).await?;
-        
-        // JSON文字列からFilterのリストをパース
-        let filters: Vec<Filter> = serde_json::from_str(&filters_json)
-            .context("Failed to parse filters JSON")?;
-        
-        client.subscribe(filters).await
-    })
+) -> Result<SubscriptionInfo, MeisoError> {
+    let client = get_client(client_id).await?;
+    
+    // JSON文字列からFilterのリストをパース
+    let filters: Vec<Filter> = serde_json::from_str(&filters_json)
+        .context("Failed to parse filters JSON")?;
+    
+    Ok(client.subscribe(filters).await?)
 }
 
 /// Subscriptionを停止
-pub fn stop_subscription(subscription_id: String) -> Result<()> {
-    stop_subscription_with_client_id(subscription_id, None)
+pub async fn stop_subscription(subscription_id: String) -> Result<(), MeisoError> {
+    stop_subscription_with_client_id(subscription_id, None).await
 }
 
 /// Subscriptionを停止（client_id指定可能）
-pub fn stop_subscription_with_client_id(
+pub async fn stop_subscription_with_client_id(
     subscription_id: String,
     client_id: Option<String>,
-) -> Result<()> {
-    TOKIO_RUNTIME.block_on(async {
-        let client = get_client(client_id).await?;
-        client.unsubscribe(subscription_id).await
-    })
+) -> Result<(), MeisoError> {
+    let client = get_client(client_id).await?;
+    Ok(client.unsubscribe(subscription_id).await?)
 }
 
 /// すべてのSubscriptionを停止
-pub fn stop_all_subscriptions() -> Result<()> {
-    stop_all_subscriptions_with_client_id(None)
+pub async fn stop_all_subscriptions() -> Result<(), MeisoError> {
+    stop_all_subscriptions_with_client_id(None).await
 }
 
 /// すべてのSubscriptionを停止（client_id指定可能）
-pub fn stop_all_subscriptions_with_client_id(client_id: Option<String>) -> Result<()> {
-    TOKIO_RUNTIME.block_on(async {
-        let client = get_client(client_id).await?;
-        client.unsubscribe_all().await
-    })
+pub async fn stop_all_subscriptions_with_client_id(client_id: Option<String>) -> Result<(), MeisoError> {
+    let client = get_client(client_id).await?;
+    Ok(client.unsubscribe_all().await?)
 }
 
 /// Subscription経由でイベントを受信
 /// timeout_ms: タイムアウト（ミリ秒）
-pub fn receive_subscription_events(timeout_ms: u64) -> Result<Vec<ReceivedEvent>> {
-    receive_subscription_events_with_client_id(timeout_ms, None)
+pub async fn receive_subscription_events(timeout_ms: u64) -> Result<Vec<ReceivedEvent>, MeisoError> {
+    receive_subscription_events_with_client_id(timeout_ms, None).await
 }
 
 /// Subscription経由でイベントを受信（client_id指定可能）
-pub fn receive_subscription_events_with_client_id(
+pub async fn receive_subscription_events_with_client_id(
     timeout_ms: u64,
     client_id: Option<String>,
-) -> Result<Vec<ReceivedEvent>> {
-    TOKIO_RUNTIME.block_on(async {
-        let client = get_client(client_id).await?;
-        client.receive_subscription_events(timeout_ms).await
-    })
+) -> Result<Vec<ReceivedEvent>, MeisoError> {
+    let client = get_client(client_id).await?;
+    Ok(client.receive_subscription_events(timeout_ms).await?)
 }
 
 /// リレー接続状態をチェック
-pub fn check_connection_status() -> Result<bool> {
-    check_connection_status_with_client_id(None)
+pub async fn check_connection_status() -> Result<bool, MeisoError> {
+    check_connection_status_with_client_id(None).await
 }
 
 /// リレー接続状態をチェック（client_id指定可能）
-pub fn check_connection_status_with_client_id(client_id: Option<String>) -> Result<bool> {
-    TOKIO_RUNTIME.block_on(async {
-        let client = get_client(client_id).await?;
-        client.check_connection_status().await
-    })
+pub async fn check_connection_status_with_client_id(client_id: Option<String>) -> Result<bool, MeisoError> {
+    let client = get_client(client_id).await?;
+    Ok(client.check_connection_status().await?)
+}
+
+/// リレーごとの接続状態・統計（状態、接続時間、最後のエラー、再接続回数、レイテンシ）
+pub async fn relay_status_report() -> Result<RelayStatusReport, MeisoError> {
+    relay_status_report_with_client_id(None).await
+}
+
+/// リレーごとの接続状態・統計（client_id指定可能）
+pub async fn relay_status_report_with_client_id(client_id: Option<String>) -> Result<RelayStatusReport, MeisoError> {
+    let client = get_client(client_id).await?;
+    Ok(client.relay_status().await)
 }
 
-/// リレーに再接続
-pub fn reconnect_to_relays() -> Result<()> {
-    reconnect_to_relays_with_client_id(None)
+/// 切断中のリレーに再接続（接続中のリレーとSubscriptionはそのまま）
+pub async fn reconnect_to_relays() -> Result<(), MeisoError> {
+    reconnect_to_relays_with_client_id(None).await
 }
 
 /// リレーに再接続（client_id指定可能）
-pub fn reconnect_to_relays_with_client_id(client_id: Option<String>) -> Result<()> {
-    TOKIO_RUNTIME.block_on(async {
-        let client = get_client(client_id).await?;
-        client.reconnect().await
-    })
+pub async fn reconnect_to_relays_with_client_id(client_id: Option<String>) -> Result<(), MeisoError> {
+    let client = get_client(client_id).await?;
+    Ok(client.reconnect().await?)
 }
 
 /// イベントJSONからキャッシュ情報を作成（Event型を使わずに）
 pub fn create_cache_info(
     event_json: String,
     ttl_seconds: u64,
-) -> Result<CachedEventInfo> {
+) -> Result<CachedEventInfo, MeisoError> {
     // JSONからイベント情報を抽出（nostr-sdkの Event型を経由せずに）
     let json_value: serde_json::Value = serde_json::from_str(&event_json)
         .context("Failed to parse event JSON")?;
@@ -2213,3 +3212,71 @@ pub fn is_cache_valid(cache_info: CachedEventInfo) -> bool {
     cache_info.is_valid()
 }
 
+
+// ========================================
+// ログAPI
+// ========================================
+
+use crate::frb_generated::StreamSink;
+use crate::logging::{LogLevel, LogRecord};
+
+/// Rust側のログを受け取るストリームを作成（Talkerへの転送用）
+///
+/// 秘密鍵・暗号文はマスク済み。再度呼ぶと以前のストリームは置き換えられる
+pub fn create_log_stream(sink: StreamSink<LogRecord>) {
+    crate::logging::set_sink(Some(std::sync::Arc::new(move |record| {
+        // Dart側でストリームが閉じられていても無視する
+        let _ = sink.add(record);
+    })));
+}
+
+/// ログストリームへの転送を停止
+pub fn close_log_stream() {
+    crate::logging::set_sink(None);
+}
+
+/// 出力するログレベルを設定（実行中に変更可能）
+pub fn set_log_level(level: LogLevel) {
+    crate::logging::set_max_level(level);
+}
+
+/// 現在のログレベル
+pub fn get_log_level() -> LogLevel {
+    crate::logging::max_level()
+}
+
+// ========================================
+// リレー再接続API
+// ========================================
+
+use crate::reconnect::{AppLifecycle, RelayStateChange};
+
+/// リレーの状態変化を受け取るストリームを作成（再接続の監視タスクから通知される）
+///
+/// 通知されるのは変化のみなので、現在の状態は`relay_status_report`で取得する。
+/// 再度呼ぶと以前のストリームは置き換えられる
+pub async fn create_relay_state_stream(
+    sink: StreamSink<RelayStateChange>,
+    client_id: Option<String>,
+) -> Result<(), MeisoError> {
+    let client = get_client(client_id).await?;
+    client.supervisor.set_listener(Some(std::sync::Arc::new(move |change| {
+        // Dart側でストリームが閉じられていても無視する
+        let _ = sink.add(change);
+    })));
+    Ok(())
+}
+
+/// リレー状態ストリームへの通知を停止
+pub async fn close_relay_state_stream(client_id: Option<String>) -> Result<(), MeisoError> {
+    let client = get_client(client_id).await?;
+    client.supervisor.set_listener(None);
+    Ok(())
+}
+
+/// アプリの表示状態を通知（全クライアント共通）
+///
+/// バックグラウンド中は再接続を止め、フォアグラウンドに戻ったら切断中のリレーをすぐ再接続する
+pub fn set_app_lifecycle(state: AppLifecycle) {
+    crate::reconnect::set_app_lifecycle(state);
+}
@@ -0,0 +1,95 @@
+use anyhow::Result;
+
+/// Crockford Base32の文字セット（I, L, O, Uを含まないので書き写しやすい）
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// バイト列をCrockford Base32にエンコード（パディングなし、大文字）
+pub fn encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity((data.len() * 8).div_ceil(5));
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for &byte in data {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    out
+}
+
+/// Crockford Base32をデコード
+///
+/// 大文字小文字を区別せず、ハイフンと空白は無視する。
+/// 読み間違えやすい文字は O→0、I/L→1 として扱う
+pub fn decode(text: &str) -> Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(text.len() * 5 / 8);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for (position, c) in text.chars().enumerate() {
+        if c == '-' || c.is_whitespace() {
+            continue;
+        }
+        let normalized = match c.to_ascii_uppercase() {
+            'O' => '0',
+            'I' | 'L' => '1',
+            other => other,
+        };
+        let value = ALPHABET
+            .iter()
+            .position(|&a| a as char == normalized)
+            .ok_or_else(|| {
+                anyhow::anyhow!("Invalid character '{}' at position {}", c, position + 1)
+            })?;
+        buffer = (buffer << 5) | value as u32;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    // 余りのビットは0でなければならない（末尾の書き間違いを検出）
+    if bits >= 5 || buffer & ((1 << bits) - 1) != 0 {
+        anyhow::bail!("Invalid base32 length");
+    }
+    Ok(out)
+}
+
+/// 読みやすいように指定文字数ごとにハイフンで区切る
+pub fn group(encoded: &str, size: usize) -> String {
+    encoded
+        .as_bytes()
+        .chunks(size)
+        .map(|chunk| std::str::from_utf8(chunk).expect("base32 output is ASCII"))
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_and_normalization() {
+        for len in 0..40 {
+            let data: Vec<u8> = (0..len).map(|i| (i * 37 + 11) as u8).collect();
+            assert_eq!(decode(&encode(&data)).unwrap(), data);
+        }
+
+        let encoded = group(&encode(b"meiso recovery"), 4);
+        assert!(encoded.contains('-'));
+        let sloppy = encoded
+            .to_lowercase()
+            .replace('0', "o")
+            .replace('1', "l")
+            .replace('-', " ");
+        assert_eq!(decode(&sloppy).unwrap(), b"meiso recovery");
+
+        assert!(decode("ABC!").is_err());
+        assert!(decode("A").is_err());
+    }
+}
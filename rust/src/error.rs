@@ -0,0 +1,208 @@
+use serde::{Deserialize, Serialize};
+
+use crate::key_store::{PublicKeyMismatch, UnlockError, WrongPassword};
+use crate::session::SessionLocked;
+use crate::transfer::{TransferExpired, WrongTransferCode};
+
+/// ブリッジAPIが返すエラー
+///
+/// Flutter側はメッセージ文字列ではなく`code()`とフィールドで判定し、
+/// 表示メッセージはアプリ側でローカライズする
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, thiserror::Error)]
+pub enum MeisoError {
+    /// クライアントが初期化されていない
+    #[error("Nostr client [{client_id}] is not initialized")]
+    NotInitialized { client_id: String },
+
+    /// 現在のモード（Amberなど）では使えない操作
+    #[error("{operation} is not supported in {mode} mode")]
+    UnsupportedInMode { operation: String, mode: String },
+
+    /// パスワード（またはアンロック方法）が違う
+    #[error("Wrong password")]
+    WrongPassword,
+
+    /// 鍵ファイルが存在しない
+    #[error("Encrypted key file not found")]
+    KeyNotFound,
+
+    /// 鍵ファイルの構造が壊れている（パスワードに関係なく開けない）
+    #[error("Key file is corrupted: {reason}")]
+    CorruptKeyFile { reason: String },
+
+    /// 暗号文の復号に失敗した（NIP-44・転送ペイロードなど）
+    #[error("Failed to decrypt: {reason}")]
+    DecryptFailed { reason: String },
+
+    /// 時間内に完了しなかった
+    #[error("{operation} timed out after {timeout_secs}s")]
+    Timeout {
+        operation: String,
+        timeout_secs: u64,
+    },
+
+    /// 接続先のリレーがない
+    #[error("No relays available")]
+    NoRelaysAvailable,
+
+    /// 厳格モードでSOCKS5プロキシ（Tor）に接続できない。リレーには接続していない
+    #[error("Proxy {proxy} is unavailable: {reason}")]
+    ProxyUnavailable { proxy: String, reason: String },
+
+    /// 秘密鍵・公開鍵の形式が不正
+    #[error("Invalid key: {reason}")]
+    InvalidKey { reason: String },
+
+    /// セッションがロックされている（`unlock_session`が必要）
+    #[error("Session is locked")]
+    Locked,
+
+    /// 連続失敗によるバックオフ中
+    #[error("Too many failed unlock attempts. Try again in {retry_after_secs} seconds")]
+    Throttled { retry_after_secs: u64 },
+
+    /// 連続失敗により鍵が削除された
+    #[error("Secret key was wiped after {attempts} consecutive failed unlock attempts")]
+    KeyWiped { attempts: u32 },
+
+    /// 保存された公開鍵が秘密鍵と一致しない
+    #[error("Stored public key {stored} does not match the secret key's public key {expected}")]
+    PublicKeyMismatch { stored: String, expected: String },
+
+    /// 転送ペイロードの有効期限切れ
+    #[error("Transfer payload expired at {expires_at}")]
+    TransferExpired { expires_at: i64 },
+
+    /// 引数が不正
+    #[error("Invalid argument: {reason}")]
+    InvalidArgument { reason: String },
+
+    /// 上記以外（リレー・I/Oなど）
+    #[error("{message}")]
+    Other { message: String },
+}
+
+impl MeisoError {
+    /// Flutter側で判定に使う安定したエラーコード
+    pub fn code(&self) -> &'static str {
+        match self {
+            MeisoError::NotInitialized { .. } => "not_initialized",
+            MeisoError::UnsupportedInMode { .. } => "unsupported_in_mode",
+            MeisoError::WrongPassword => "wrong_password",
+            MeisoError::KeyNotFound => "key_not_found",
+            MeisoError::CorruptKeyFile { .. } => "corrupt_key_file",
+            MeisoError::DecryptFailed { .. } => "decrypt_failed",
+            MeisoError::Timeout { .. } => "timeout",
+            MeisoError::NoRelaysAvailable => "no_relays_available",
+            MeisoError::ProxyUnavailable { .. } => "proxy_unavailable",
+            MeisoError::InvalidKey { .. } => "invalid_key",
+            MeisoError::Locked => "locked",
+            MeisoError::Throttled { .. } => "throttled",
+            MeisoError::KeyWiped { .. } => "key_wiped",
+            MeisoError::PublicKeyMismatch { .. } => "public_key_mismatch",
+            MeisoError::TransferExpired { .. } => "transfer_expired",
+            MeisoError::InvalidArgument { .. } => "invalid_argument",
+            MeisoError::Other { .. } => "other",
+        }
+    }
+
+    pub fn unsupported_in_amber_mode(operation: &str) -> Self {
+        MeisoError::UnsupportedInMode {
+            operation: operation.to_string(),
+            mode: "amber".to_string(),
+        }
+    }
+
+    pub fn invalid_key(reason: impl std::fmt::Display) -> Self {
+        MeisoError::InvalidKey {
+            reason: reason.to_string(),
+        }
+    }
+
+    pub fn corrupt_key_file(reason: impl std::fmt::Display) -> Self {
+        MeisoError::CorruptKeyFile {
+            reason: reason.to_string(),
+        }
+    }
+
+    pub fn invalid_argument(reason: impl std::fmt::Display) -> Self {
+        MeisoError::InvalidArgument {
+            reason: reason.to_string(),
+        }
+    }
+}
+
+/// 内部の`anyhow::Error`をブリッジ用のエラーに変換
+///
+/// エラーチェーンを辿り、既知の型があればその種類で返す。
+/// 見つからない場合はコンテキストを含めたメッセージで`Other`になる
+impl From<anyhow::Error> for MeisoError {
+    fn from(error: anyhow::Error) -> Self {
+        for cause in error.chain() {
+            if let Some(e) = cause.downcast_ref::<MeisoError>() {
+                return e.clone();
+            }
+            if cause.is::<WrongPassword>() || cause.is::<WrongTransferCode>() {
+                return MeisoError::WrongPassword;
+            }
+            if cause.is::<SessionLocked>() {
+                return MeisoError::Locked;
+            }
+            if let Some(e) = cause.downcast_ref::<UnlockError>() {
+                return match *e {
+                    UnlockError::Throttled { retry_after_secs } => {
+                        MeisoError::Throttled { retry_after_secs }
+                    }
+                    UnlockError::Wiped { attempts } => MeisoError::KeyWiped { attempts },
+                };
+            }
+            if let Some(e) = cause.downcast_ref::<PublicKeyMismatch>() {
+                return MeisoError::PublicKeyMismatch {
+                    stored: e.stored.clone(),
+                    expected: e.expected.clone(),
+                };
+            }
+            if let Some(e) = cause.downcast_ref::<TransferExpired>() {
+                return MeisoError::TransferExpired {
+                    expires_at: e.expires_at,
+                };
+            }
+        }
+        MeisoError::Other {
+            message: format!("{:#}", error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_from_anyhow_finds_known_causes() {
+        let wrapped = Err::<(), _>(anyhow::Error::from(WrongPassword))
+            .context("Failed to unlock")
+            .unwrap_err();
+        assert_eq!(MeisoError::from(wrapped), MeisoError::WrongPassword);
+
+        let throttled = anyhow::Error::from(UnlockError::Throttled {
+            retry_after_secs: 30,
+        });
+        let error = MeisoError::from(throttled);
+        assert_eq!(
+            error,
+            MeisoError::Throttled {
+                retry_after_secs: 30
+            }
+        );
+        assert_eq!(error.code(), "throttled");
+
+        let typed = anyhow::Error::from(MeisoError::NoRelaysAvailable).context("Failed to fetch");
+        assert_eq!(MeisoError::from(typed), MeisoError::NoRelaysAvailable);
+
+        let other = MeisoError::from(anyhow::anyhow!("socket closed").context("Relay error"));
+        assert_eq!(other.code(), "other");
+        assert_eq!(other.to_string(), "Relay error: socket closed");
+    }
+}
@@ -1,171 +1,1731 @@
 use aes_gcm::{
-    aead::{Aead, KeyInit, OsRng},
+    aead::{Aead, KeyInit, OsRng, Payload},
     Aes256Gcm, Nonce,
 };
 use anyhow::{Context, Result};
+use ::base64::Engine;
+use hmac::{Hmac, Mac};
+use nostr_sdk::prelude::*;
 use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use zeroize::Zeroizing;
+
+use crate::error::MeisoError;
+use crate::logging::{log_debug, log_error, log_info, log_warn};
+use crate::secret::SecretString;
+use crate::storage::{resolve_storage, KeyStorage};
+
+/// 鍵ファイルのマジックヘッダー
+const KEY_FILE_MAGIC: &[u8; 8] = b"MEISOKEY";
+/// 現在の鍵ファイルフォーマットバージョン
+/// v1: Argon2パラメータを記録 / v2: 公開鍵をヘッダーに追加 / v3: キースロット方式
+const KEY_FILE_VERSION: u8 = 3;
+/// KDF識別子: Argon2id (v0x13)
+const KDF_ID_ARGON2ID: u8 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const PUBKEY_LEN: usize = 32;
+/// magic(8) + version(1) + kdf(1) + m_cost(4) + t_cost(4) + p_cost(4)
+const HEADER_PREFIX_LEN: usize = 8 + 1 + 1 + 4 + 4 + 4;
+/// v1ヘッダー長: prefix + salt(16) + nonce(12)
+const HEADER_LEN_V1: usize = HEADER_PREFIX_LEN + SALT_LEN + NONCE_LEN;
+/// v2ヘッダー長: prefix + pubkey(32) + salt(16) + nonce(12)
+const HEADER_LEN_V2: usize = HEADER_PREFIX_LEN + PUBKEY_LEN + SALT_LEN + NONCE_LEN;
+/// 旧フォーマット（ヘッダーなし）の最小長: salt(16) + nonce(12)
+const LEGACY_HEADER_LEN: usize = SALT_LEN + NONCE_LEN;
+/// AES-GCMの認証タグ長
+const TAG_LEN: usize = 16;
+/// 保存される秘密鍵の長さ（nsec: 63文字、hex: 64文字）
+const SECRET_KEY_LENS: [usize; 2] = [63, 64];
+/// v3: 秘密鍵を暗号化するデータ鍵の長さ
+const DATA_KEY_LEN: usize = 32;
+/// v3: 公開鍵の位置（magic + version の後）
+const PUBKEY_OFFSET_V3: usize = 8 + 1;
+/// v3: スロット数の位置
+const SLOT_COUNT_OFFSET_V3: usize = PUBKEY_OFFSET_V3 + PUBKEY_LEN;
+/// v3: スロットのうちAADとして認証される部分: kind(1) + m_cost(4) + t_cost(4) + p_cost(4) + salt(16)
+const SLOT_PARAMS_LEN: usize = 1 + 4 + 4 + 4 + SALT_LEN;
+/// v3: スロット長: params + nonce(12) + wrapped data key(32 + tag 16)
+const SLOT_LEN: usize = SLOT_PARAMS_LEN + NONCE_LEN + DATA_KEY_LEN + TAG_LEN;
+/// 1つの鍵ファイルに登録できるスロット数の上限
+pub const MAX_KEY_SLOTS: usize = 8;
+/// 端末鍵スロットに渡される鍵の最小長
+const DEVICE_KEY_MIN_LEN: usize = 16;
+/// 端末鍵から包み鍵を導出するときのコンテキスト
+const DEVICE_SLOT_CONTEXT: &[u8] = b"meiso-device-slot-v1";
+/// リカバリーコードのエントロピー（160bit = Base32で32文字）
+const RECOVERY_CODE_BYTES: usize = 20;
+
+/// 鍵ファイルから読み込むArgon2パラメータの上限（細工されたファイルによるDoS対策）
+const MAX_MEMORY_KIB: u32 = 1024 * 1024;
+const MAX_ITERATIONS: u32 = 64;
+const MAX_PARALLELISM: u32 = 16;
+
+/// NIP-49エクスポート時に許可するscryptのLOG_N範囲
+/// 16未満は弱すぎ、22を超えるとモバイル端末でメモリ不足になる
+pub const NCRYPTSEC_MIN_LOG_N: u8 = 16;
+pub const NCRYPTSEC_MAX_LOG_N: u8 = 22;
+
+/// Argon2idのパラメータ（鍵ファイルのヘッダーに記録される）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    /// メモリコスト（KiB）
+    pub memory_kib: u32,
+    /// 反復回数
+    pub iterations: u32,
+    /// 並列度
+    pub parallelism: u32,
+}
+
+impl KdfParams {
+    /// 旧フォーマットで使われていたパラメータ
+    /// argon2 0.5 の Params::default() と同値（依存の更新で変わらないよう固定値で持つ）
+    pub const LEGACY: KdfParams = KdfParams {
+        memory_kib: 19 * 1024,
+        iterations: 2,
+        parallelism: 1,
+    };
+
+    /// 新規保存時に使うパラメータ（Interactiveプロファイル）
+    pub const DEFAULT: KdfParams = Self::LEGACY;
+
+    /// 値が妥当かチェック（鍵ファイルから読み込んだ値・呼び出し側から渡された値の両方に使う）
+    pub fn validate(&self) -> Result<()> {
+        if self.memory_kib > MAX_MEMORY_KIB
+            || self.iterations == 0
+            || self.iterations > MAX_ITERATIONS
+            || self.parallelism == 0
+            || self.parallelism > MAX_PARALLELISM
+        {
+            anyhow::bail!("Unsupported Argon2 parameters: {:?}", self);
+        }
+        Ok(())
+    }
+}
+
+/// 名前付きのArgon2idコストプロファイル
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KdfProfile {
+    /// 19 MiB / 2回（OWASP推奨の最小値、低スペック端末向け）
+    Interactive,
+    /// 64 MiB / 3回
+    Moderate,
+    /// 256 MiB / 4回（メモリの少ない端末ではアンロックに失敗する可能性がある）
+    Paranoid,
+}
+
+impl KdfProfile {
+    pub fn params(self) -> KdfParams {
+        match self {
+            KdfProfile::Interactive => KdfParams::DEFAULT,
+            KdfProfile::Moderate => KdfParams {
+                memory_kib: 64 * 1024,
+                iterations: 3,
+                parallelism: 1,
+            },
+            KdfProfile::Paranoid => KdfParams {
+                memory_kib: 256 * 1024,
+                iterations: 4,
+                parallelism: 1,
+            },
+        }
+    }
+}
+
+/// キャリブレーション結果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KdfCalibration {
+    pub kdf: KdfParams,
+    /// この端末での推定アンロック時間（ミリ秒）
+    pub estimated_ms: u64,
+}
+
+/// キャリブレーションで使うメモリの上限（指定がない場合）
+pub const CALIBRATION_DEFAULT_MAX_MEMORY_KIB: u32 = 256 * 1024;
+
+/// この端末でベンチマークし、目標時間に近いArgon2idパラメータを選ぶ
+///
+/// まずメモリコストを上限まで倍々に増やし、残りの時間を反復回数で埋める。
+/// Interactiveプロファイルより弱いパラメータは返さない。
+/// CPUを目標時間の数倍使うので、UIスレッドから直接呼ばないこと
+pub fn calibrate_kdf(target_ms: u64, max_memory_kib: Option<u32>) -> Result<KdfCalibration> {
+    let floor = KdfParams::DEFAULT;
+    let max_memory_kib = max_memory_kib
+        .unwrap_or(CALIBRATION_DEFAULT_MAX_MEMORY_KIB)
+        .clamp(floor.memory_kib, MAX_MEMORY_KIB);
+
+    let measure = |kdf: &KdfParams| -> Result<u64> {
+        let started = std::time::Instant::now();
+        SecureKeyStore::derive_key_from_password("calibration", &[0u8; SALT_LEN], kdf)?;
+        Ok(started.elapsed().as_millis() as u64)
+    };
+
+    // 1. 反復1回でメモリを増やす（1回の時間が目標の1/4を超えるか上限まで）
+    let mut kdf = KdfParams {
+        iterations: 1,
+        ..floor
+    };
+    let mut elapsed = measure(&kdf)?;
+    while elapsed * 4 < target_ms && kdf.memory_kib * 2 <= max_memory_kib {
+        kdf.memory_kib *= 2;
+        elapsed = measure(&kdf)?;
+    }
+
+    // 2. 反復回数で目標時間に合わせる（時間は反復回数にほぼ比例する）
+    let per_iteration = elapsed.max(1);
+    kdf.iterations = (target_ms / per_iteration).clamp(1, MAX_ITERATIONS as u64) as u32;
+    if kdf.memory_kib <= floor.memory_kib {
+        kdf.iterations = kdf.iterations.max(floor.iterations);
+    }
+    kdf.validate()?;
+
+    let calibration = KdfCalibration {
+        kdf,
+        estimated_ms: per_iteration * kdf.iterations as u64,
+    };
+    log_info!(
+        "⏱️ Argon2 calibrated for {}ms: {} KiB x {} (~{}ms)",
+        target_ms, kdf.memory_kib, kdf.iterations, calibration.estimated_ms
+    );
+    Ok(calibration)
+}
+
+/// 鍵ファイルのヘッダー（AES-GCMのAADとして認証される）
+///
+/// v1: [magic(8B)] + [version(1B)] + [kdf(1B)] + [m_cost(4B LE)] + [t_cost(4B LE)]
+///     + [p_cost(4B LE)] + [salt(16B)] + [nonce(12B)]
+/// v2: v1のp_costの後に [pubkey(32B)] を追加
+struct KeyFileHeader {
+    version: u8,
+    kdf: KdfParams,
+    /// 秘密鍵に対応する公開鍵（v2以降）
+    public_key: Option<[u8; PUBKEY_LEN]>,
+    salt: [u8; SALT_LEN],
+    nonce: [u8; NONCE_LEN],
+}
+
+impl KeyFileHeader {
+    fn len(&self) -> usize {
+        if self.public_key.is_some() {
+            HEADER_LEN_V2
+        } else {
+            HEADER_LEN_V1
+        }
+    }
+
+    /// v1/v2のヘッダーを作成（新規保存はv3なので、旧フォーマットのテストでのみ使う）
+    #[cfg(test)]
+    fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.len());
+        out.extend_from_slice(KEY_FILE_MAGIC);
+        out.push(self.version);
+        out.push(KDF_ID_ARGON2ID);
+        out.extend_from_slice(&self.kdf.memory_kib.to_le_bytes());
+        out.extend_from_slice(&self.kdf.iterations.to_le_bytes());
+        out.extend_from_slice(&self.kdf.parallelism.to_le_bytes());
+        if let Some(public_key) = &self.public_key {
+            out.extend_from_slice(public_key);
+        }
+        out.extend_from_slice(&self.salt);
+        out.extend_from_slice(&self.nonce);
+        out
+    }
+
+    fn decode(data: &[u8]) -> Result<Self> {
+        if data.len() < HEADER_PREFIX_LEN {
+            return Err(MeisoError::corrupt_key_file("header is truncated").into());
+        }
+        let version = data[8];
+        let header_len = match version {
+            1 => HEADER_LEN_V1,
+            2 => HEADER_LEN_V2,
+            _ => return Err(MeisoError::corrupt_key_file(format!("unsupported version {}", version)).into()),
+        };
+        if data.len() < header_len {
+            return Err(MeisoError::corrupt_key_file("header is truncated").into());
+        }
+        if data[9] != KDF_ID_ARGON2ID {
+            return Err(MeisoError::corrupt_key_file(format!("unsupported key derivation function id {}", data[9])).into());
+        }
+
+        let read_u32 = |offset: usize| {
+            u32::from_le_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]])
+        };
+        let kdf = KdfParams {
+            memory_kib: read_u32(10),
+            iterations: read_u32(14),
+            parallelism: read_u32(18),
+        };
+        kdf.validate()?;
+
+        let mut offset = HEADER_PREFIX_LEN;
+        let public_key = if version >= 2 {
+            let mut public_key = [0u8; PUBKEY_LEN];
+            public_key.copy_from_slice(&data[offset..offset + PUBKEY_LEN]);
+            offset += PUBKEY_LEN;
+            Some(public_key)
+        } else {
+            None
+        };
+
+        let mut salt = [0u8; SALT_LEN];
+        salt.copy_from_slice(&data[offset..offset + SALT_LEN]);
+        offset += SALT_LEN;
+        let mut nonce = [0u8; NONCE_LEN];
+        nonce.copy_from_slice(&data[offset..offset + NONCE_LEN]);
+
+        Ok(Self {
+            version,
+            kdf,
+            public_key,
+            salt,
+            nonce,
+        })
+    }
+}
+
+/// 読み込んだ鍵ファイルの中身
+struct ParsedKeyFile<'a> {
+    /// フォーマットバージョン（ヘッダーなしの旧フォーマットは0）
+    version: u8,
+    kdf: KdfParams,
+    public_key: Option<[u8; PUBKEY_LEN]>,
+    salt: [u8; SALT_LEN],
+    nonce: [u8; NONCE_LEN],
+    /// AAD（旧フォーマットの場合は空）
+    aad: &'a [u8],
+    ciphertext: &'a [u8],
+}
+
+impl<'a> ParsedKeyFile<'a> {
+    fn parse(data: &'a [u8]) -> Result<Self> {
+        if data.starts_with(KEY_FILE_MAGIC) {
+            let header = KeyFileHeader::decode(data)?;
+            let header_len = header.len();
+            return Ok(Self {
+                version: header.version,
+                kdf: header.kdf,
+                public_key: header.public_key,
+                salt: header.salt,
+                nonce: header.nonce,
+                aad: &data[..header_len],
+                ciphertext: &data[header_len..],
+            });
+        }
+
+        // 旧フォーマット: salt(16B) + nonce(12B) + ciphertext
+        if data.len() < LEGACY_HEADER_LEN {
+            return Err(MeisoError::corrupt_key_file("file is too short").into());
+        }
+        let mut salt = [0u8; SALT_LEN];
+        salt.copy_from_slice(&data[..SALT_LEN]);
+        let mut nonce = [0u8; NONCE_LEN];
+        nonce.copy_from_slice(&data[SALT_LEN..LEGACY_HEADER_LEN]);
+
+        Ok(Self {
+            version: 0,
+            kdf: KdfParams::LEGACY,
+            public_key: None,
+            salt,
+            nonce,
+            aad: &[],
+            ciphertext: &data[LEGACY_HEADER_LEN..],
+        })
+    }
+}
+
+/// キースロットの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KeySlotKind {
+    /// パスワード（Argon2id）
+    Password,
+    /// 端末固有の鍵（プラットフォームのKeystoreなどからブリッジ経由で渡される）
+    DeviceKey,
+    /// 印刷して保管するリカバリーコード（Argon2id）
+    RecoveryCode,
+}
+
+impl KeySlotKind {
+    fn id(self) -> u8 {
+        match self {
+            KeySlotKind::Password => 1,
+            KeySlotKind::DeviceKey => 2,
+            KeySlotKind::RecoveryCode => 3,
+        }
+    }
+
+    fn from_id(id: u8) -> Result<Self> {
+        match id {
+            1 => Ok(KeySlotKind::Password),
+            2 => Ok(KeySlotKind::DeviceKey),
+            3 => Ok(KeySlotKind::RecoveryCode),
+            _ => Err(MeisoError::corrupt_key_file(format!("unknown key slot kind {}", id)).into()),
+        }
+    }
+
+    /// Argon2で包み鍵を導出する種類か
+    fn uses_kdf(self) -> bool {
+        !matches!(self, KeySlotKind::DeviceKey)
+    }
+}
+
+/// 鍵ファイルのアンロック方法（中身はDrop時にゼロ埋めされる）
+#[derive(Clone)]
+pub enum UnlockMethod {
+    Password(String),
+    /// プラットフォームから渡される端末固有の鍵（16バイト以上）
+    DeviceKey(Vec<u8>),
+    /// `generate_recovery_code`で作成したコード（ハイフン・大文字小文字は問わない）
+    RecoveryCode(String),
+}
+
+impl UnlockMethod {
+    pub fn kind(&self) -> KeySlotKind {
+        match self {
+            UnlockMethod::Password(_) => KeySlotKind::Password,
+            UnlockMethod::DeviceKey(_) => KeySlotKind::DeviceKey,
+            UnlockMethod::RecoveryCode(_) => KeySlotKind::RecoveryCode,
+        }
+    }
+
+    /// スロットのデータ鍵を包む鍵を導出
+    fn wrapping_key(&self, kdf: &KdfParams, salt: &[u8]) -> Result<Zeroizing<[u8; 32]>> {
+        match self {
+            UnlockMethod::Password(password) => SecureKeyStore::derive_key_from_password(password, salt, kdf),
+            UnlockMethod::RecoveryCode(code) => {
+                let canonical = normalize_recovery_code(code)?;
+                SecureKeyStore::derive_key_from_password(&canonical, salt, kdf)
+            }
+            UnlockMethod::DeviceKey(device_key) => {
+                if device_key.len() < DEVICE_KEY_MIN_LEN {
+                    return Err(MeisoError::invalid_argument(format!("device key must be at least {} bytes", DEVICE_KEY_MIN_LEN)).into());
+                }
+                let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(device_key)
+                    .expect("HMAC accepts keys of any length");
+                mac.update(DEVICE_SLOT_CONTEXT);
+                mac.update(salt);
+                let mut key = Zeroizing::new([0u8; 32]);
+                key.copy_from_slice(&mac.finalize().into_bytes());
+                Ok(key)
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for UnlockMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "UnlockMethod::{:?}([REDACTED])", self.kind())
+    }
+}
+
+impl Drop for UnlockMethod {
+    fn drop(&mut self) {
+        use zeroize::Zeroize;
+        match self {
+            UnlockMethod::Password(secret) | UnlockMethod::RecoveryCode(secret) => secret.zeroize(),
+            UnlockMethod::DeviceKey(key) => key.zeroize(),
+        }
+    }
+}
+
+/// 印刷用のリカバリーコードを生成（XXXX-XXXX-…の8グループ、160bit）
+pub fn generate_recovery_code() -> SecretString {
+    let mut entropy = Zeroizing::new([0u8; RECOVERY_CODE_BYTES]);
+    OsRng.fill_bytes(entropy.as_mut_slice());
+    let encoded = Zeroizing::new(crate::base32::encode(entropy.as_slice()));
+    SecretString::from(crate::base32::group(&encoded, 4))
+}
+
+/// 入力されたリカバリーコードを正規化（書き間違えやすい文字・区切りを吸収）
+fn normalize_recovery_code(code: &str) -> Result<Zeroizing<String>> {
+    let entropy = Zeroizing::new(
+        crate::base32::decode(code).map_err(|e| MeisoError::invalid_argument(format!("invalid recovery code: {}", e)))?,
+    );
+    if entropy.len() != RECOVERY_CODE_BYTES {
+        return Err(MeisoError::invalid_argument("invalid recovery code length").into());
+    }
+    Ok(Zeroizing::new(crate::base32::encode(&entropy)))
+}
+
+/// キースロットの情報（パスワード不要で取得できる）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeySlotInfo {
+    pub index: u32,
+    pub kind: KeySlotKind,
+    /// Argon2パラメータ（端末鍵スロットはNone）
+    pub kdf: Option<KdfParams>,
+}
+
+/// v3のキースロット（データ鍵を包んだもの）
+///
+/// [kind(1B)] + [m_cost(4B LE)] + [t_cost(4B LE)] + [p_cost(4B LE)] + [salt(16B)]
+/// + [nonce(12B)] + [wrapped data key(48B)]
+#[derive(Clone)]
+struct KeySlot {
+    kind: KeySlotKind,
+    kdf: KdfParams,
+    salt: [u8; SALT_LEN],
+    nonce: [u8; NONCE_LEN],
+    wrapped: [u8; DATA_KEY_LEN + TAG_LEN],
+}
+
+impl KeySlot {
+    /// データ鍵を包んで新しいスロットを作成
+    fn seal(method: &UnlockMethod, kdf: KdfParams, data_key: &[u8; DATA_KEY_LEN], file_prefix: &[u8]) -> Result<Self> {
+        let kind = method.kind();
+        let kdf = if kind.uses_kdf() {
+            kdf
+        } else {
+            KdfParams {
+                memory_kib: 0,
+                iterations: 0,
+                parallelism: 0,
+            }
+        };
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+
+        let mut slot = Self {
+            kind,
+            kdf,
+            salt,
+            nonce,
+            wrapped: [0u8; DATA_KEY_LEN + TAG_LEN],
+        };
+        let key = method.wrapping_key(&slot.kdf, &slot.salt)?;
+        let wrapped = Aes256Gcm::new((&*key).into())
+            .encrypt(&Nonce::from(nonce), Payload { msg: data_key, aad: &slot.aad(file_prefix) })
+            .map_err(|e| anyhow::anyhow!("Failed to wrap data key: {:?}", e))?;
+        slot.wrapped.copy_from_slice(&wrapped);
+        Ok(slot)
+    }
+
+    /// スロットを開いてデータ鍵を取り出す（鍵が違う場合はNone）
+    fn open(&self, method: &UnlockMethod, file_prefix: &[u8]) -> Result<Option<Zeroizing<[u8; DATA_KEY_LEN]>>> {
+        let key = method.wrapping_key(&self.kdf, &self.salt)?;
+        let Ok(plaintext) = Aes256Gcm::new((&*key).into()).decrypt(
+            &Nonce::from(self.nonce),
+            Payload { msg: &self.wrapped, aad: &self.aad(file_prefix) },
+        ) else {
+            return Ok(None);
+        };
+        let plaintext = Zeroizing::new(plaintext);
+        let mut data_key = Zeroizing::new([0u8; DATA_KEY_LEN]);
+        data_key.copy_from_slice(&plaintext);
+        Ok(Some(data_key))
+    }
+
+    /// 包み鍵のAAD: ファイル先頭（magic + version + pubkey）+ スロットのパラメータ
+    fn aad(&self, file_prefix: &[u8]) -> Vec<u8> {
+        let mut aad = file_prefix.to_vec();
+        self.encode_params(&mut aad);
+        aad
+    }
+
+    fn encode_params(&self, out: &mut Vec<u8>) {
+        out.push(self.kind.id());
+        out.extend_from_slice(&self.kdf.memory_kib.to_le_bytes());
+        out.extend_from_slice(&self.kdf.iterations.to_le_bytes());
+        out.extend_from_slice(&self.kdf.parallelism.to_le_bytes());
+        out.extend_from_slice(&self.salt);
+    }
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.encode_params(out);
+        out.extend_from_slice(&self.nonce);
+        out.extend_from_slice(&self.wrapped);
+    }
+
+    fn decode(data: &[u8]) -> Result<Self> {
+        let kind = KeySlotKind::from_id(data[0])?;
+        let read_u32 = |offset: usize| {
+            u32::from_le_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]])
+        };
+        let kdf = KdfParams {
+            memory_kib: read_u32(1),
+            iterations: read_u32(5),
+            parallelism: read_u32(9),
+        };
+        if kind.uses_kdf() {
+            kdf.validate()?;
+        }
+
+        let mut offset = 13;
+        let mut salt = [0u8; SALT_LEN];
+        salt.copy_from_slice(&data[offset..offset + SALT_LEN]);
+        offset += SALT_LEN;
+        let mut nonce = [0u8; NONCE_LEN];
+        nonce.copy_from_slice(&data[offset..offset + NONCE_LEN]);
+        offset += NONCE_LEN;
+        let mut wrapped = [0u8; DATA_KEY_LEN + TAG_LEN];
+        wrapped.copy_from_slice(&data[offset..offset + DATA_KEY_LEN + TAG_LEN]);
+
+        Ok(Self {
+            kind,
+            kdf,
+            salt,
+            nonce,
+            wrapped,
+        })
+    }
+
+    fn info(&self, index: usize) -> KeySlotInfo {
+        KeySlotInfo {
+            index: index as u32,
+            kind: self.kind,
+            kdf: self.kind.uses_kdf().then_some(self.kdf),
+        }
+    }
+}
+
+/// v3の鍵ファイル（キースロット方式）
+///
+/// [magic(8B)] + [version(1B)] + [pubkey(32B)] + [slot数(1B)] + [slot(89B) × N] + [nonce(12B)] + [ciphertext]
+///
+/// 秘密鍵はランダムなデータ鍵で暗号化され、各スロットがそのデータ鍵を独立に包む。
+/// ciphertextより前の全体がAADとして認証されるため、スロットの追加・削除にはデータ鍵が必要
+struct SlottedKeyFile {
+    public_key: [u8; PUBKEY_LEN],
+    slots: Vec<KeySlot>,
+    nonce: [u8; NONCE_LEN],
+    ciphertext: Vec<u8>,
+}
+
+impl SlottedKeyFile {
+    fn is_slotted(data: &[u8]) -> bool {
+        data.starts_with(KEY_FILE_MAGIC) && data.get(8) == Some(&KEY_FILE_VERSION)
+    }
+
+    /// スロットのAADに使うファイル先頭部分
+    fn prefix(public_key: &[u8; PUBKEY_LEN]) -> Vec<u8> {
+        let mut prefix = Vec::with_capacity(SLOT_COUNT_OFFSET_V3);
+        prefix.extend_from_slice(KEY_FILE_MAGIC);
+        prefix.push(KEY_FILE_VERSION);
+        prefix.extend_from_slice(public_key);
+        prefix
+    }
+
+    /// ciphertextより前の部分（AAD）
+    fn header(&self) -> Vec<u8> {
+        let mut header = Self::prefix(&self.public_key);
+        header.push(self.slots.len() as u8);
+        for slot in &self.slots {
+            slot.encode(&mut header);
+        }
+        header.extend_from_slice(&self.nonce);
+        header
+    }
+
+    fn parse(data: &[u8]) -> Result<Self> {
+        if !Self::is_slotted(data) {
+            anyhow::bail!("Not a v{} key file", KEY_FILE_VERSION);
+        }
+        let Some(&slot_count) = data.get(SLOT_COUNT_OFFSET_V3) else {
+            return Err(MeisoError::corrupt_key_file("header is truncated").into());
+        };
+        let slot_count = slot_count as usize;
+        if slot_count == 0 || slot_count > MAX_KEY_SLOTS {
+            return Err(MeisoError::corrupt_key_file(format!("invalid key slot count {}", slot_count)).into());
+        }
+        let slots_start = SLOT_COUNT_OFFSET_V3 + 1;
+        let header_len = slots_start + slot_count * SLOT_LEN + NONCE_LEN;
+        if data.len() < header_len {
+            return Err(MeisoError::corrupt_key_file("header is truncated").into());
+        }
+
+        let mut public_key = [0u8; PUBKEY_LEN];
+        public_key.copy_from_slice(&data[PUBKEY_OFFSET_V3..SLOT_COUNT_OFFSET_V3]);
+        let slots = data[slots_start..slots_start + slot_count * SLOT_LEN]
+            .chunks(SLOT_LEN)
+            .map(KeySlot::decode)
+            .collect::<Result<Vec<_>>>()?;
+        let mut nonce = [0u8; NONCE_LEN];
+        nonce.copy_from_slice(&data[header_len - NONCE_LEN..header_len]);
+
+        Ok(Self {
+            public_key,
+            slots,
+            nonce,
+            ciphertext: data[header_len..].to_vec(),
+        })
+    }
+
+    /// データ鍵で秘密鍵を暗号化して鍵ファイルを作成（nonceは新規生成）
+    fn seal(
+        secret_key: &SecretString,
+        public_key: [u8; PUBKEY_LEN],
+        data_key: &[u8; DATA_KEY_LEN],
+        slots: Vec<KeySlot>,
+    ) -> Result<Vec<u8>> {
+        if slots.is_empty() || slots.len() > MAX_KEY_SLOTS {
+            anyhow::bail!("A key file needs between 1 and {} key slots", MAX_KEY_SLOTS);
+        }
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+        let file = Self {
+            public_key,
+            slots,
+            nonce,
+            ciphertext: Vec::new(),
+        };
+        let header = file.header();
+        let ciphertext = Aes256Gcm::new(data_key.into())
+            .encrypt(
+                &Nonce::from(nonce),
+                Payload { msg: secret_key.expose_secret().as_bytes(), aad: &header },
+            )
+            .map_err(|e| anyhow::anyhow!("Failed to encrypt secret key with AES-256-GCM: {:?}", e))?;
+
+        let mut data = header;
+        data.extend_from_slice(&ciphertext);
+        Ok(data)
+    }
+
+    /// 指定の方法で開けるスロットを探してデータ鍵を取り出す
+    fn open(&self, method: &UnlockMethod) -> Result<(usize, Zeroizing<[u8; DATA_KEY_LEN]>)> {
+        let prefix = Self::prefix(&self.public_key);
+        let mut candidates = 0;
+        for (index, slot) in self.slots.iter().enumerate() {
+            if slot.kind != method.kind() {
+                continue;
+            }
+            candidates += 1;
+            if let Some(data_key) = slot.open(method, &prefix)? {
+                return Ok((index, data_key));
+            }
+        }
+        if candidates == 0 {
+            return Err(MeisoError::invalid_argument(format!("key file has no {:?} slot", method.kind())).into());
+        }
+        Err(WrongPassword.into())
+    }
+
+    /// データ鍵で秘密鍵を復号化
+    fn decrypt(&self, data_key: &[u8; DATA_KEY_LEN]) -> Result<SecretString> {
+        let plaintext = Zeroizing::new(
+            Aes256Gcm::new(data_key.into())
+                .decrypt(
+                    &Nonce::from(self.nonce),
+                    Payload { msg: &self.ciphertext, aad: &self.header() },
+                )
+                // スロットは開けたのに復号できない = ファイルの改ざん・破損
+                .map_err(|_| MeisoError::corrupt_key_file("authentication failed"))?,
+        );
+        std::str::from_utf8(&plaintext)
+            .map(SecretString::from)
+            .map_err(|_| MeisoError::corrupt_key_file("decrypted data is not valid UTF-8").into())
+    }
+}
+
+/// `edit_slots`でスロットを編集するための状態
+struct SlotEdit {
+    slots: Vec<KeySlot>,
+    /// アンロックに使われたスロット
+    unlocked_slot: usize,
+    data_key: Zeroizing<[u8; DATA_KEY_LEN]>,
+    public_key: [u8; PUBKEY_LEN],
+    /// 新しいスロットに使うArgon2パラメータ
+    kdf: KdfParams,
+}
+
+impl SlotEdit {
+    /// 同じデータ鍵を包む新しいスロットを作成
+    fn seal(&self, method: &UnlockMethod) -> Result<KeySlot> {
+        KeySlot::seal(method, self.kdf, &self.data_key, &SlottedKeyFile::prefix(&self.public_key))
+    }
+
+    /// スロットを置き換える新しいスロットを作成
+    /// 置き換えるスロットのArgon2パラメータを引き継ぐ（調整済み・Paranoidのスロットが既定値に下がらないように）
+    fn reseal(&self, index: usize, method: &UnlockMethod) -> Result<KeySlot> {
+        let replaced = &self.slots[index];
+        let kdf = if replaced.kind.uses_kdf() { replaced.kdf } else { self.kdf };
+        KeySlot::seal(method, kdf, &self.data_key, &SlottedKeyFile::prefix(&self.public_key))
+    }
+}
+
+/// パスワードなしで読める鍵ファイルの概要
+struct KeyFileSummary {
+    /// フォーマットバージョン（ヘッダーなしの旧フォーマットは0）
+    version: u8,
+    public_key: Option<[u8; PUBKEY_LEN]>,
+    ciphertext_len: usize,
+    slots: Vec<KeySlotInfo>,
+}
+
+impl KeyFileSummary {
+    fn parse(data: &[u8]) -> Result<Self> {
+        if SlottedKeyFile::is_slotted(data) {
+            let file = SlottedKeyFile::parse(data)?;
+            return Ok(Self {
+                version: KEY_FILE_VERSION,
+                public_key: Some(file.public_key),
+                ciphertext_len: file.ciphertext.len(),
+                slots: file.slots.iter().enumerate().map(|(i, slot)| slot.info(i)).collect(),
+            });
+        }
+
+        // v2以前はパスワードのスロットが1つあるのと同じ
+        let parsed = ParsedKeyFile::parse(data)?;
+        Ok(Self {
+            version: parsed.version,
+            public_key: parsed.public_key,
+            ciphertext_len: parsed.ciphertext.len(),
+            slots: vec![KeySlotInfo {
+                index: 0,
+                kind: KeySlotKind::Password,
+                kdf: Some(parsed.kdf),
+            }],
+        })
+    }
+}
+
+/// 公開鍵をヘッダー用の32バイトに変換
+fn public_key_to_bytes(public_key: &PublicKey) -> [u8; PUBKEY_LEN] {
+    let hex = public_key.to_hex();
+    let mut bytes = [0u8; PUBKEY_LEN];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).expect("PublicKey::to_hex returns valid hex");
+    }
+    bytes
+}
+
+/// ヘッダーの32バイトから公開鍵（hex）に変換
+fn public_key_hex_from_bytes(bytes: &[u8; PUBKEY_LEN]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// 公開鍵文字列（hex / npub）を検証してhex形式に正規化
+pub fn normalize_public_key(public_key: &str) -> Result<String> {
+    PublicKey::parse(public_key.trim())
+        .map(|pk| pk.to_hex())
+        .map_err(|e| MeisoError::invalid_key(format!("expected hex or npub public key: {}", e)).into())
+}
+
+/// 保存された公開鍵が秘密鍵と一致しない
+#[derive(Debug, thiserror::Error)]
+#[error("Stored public key {stored} does not match the secret key's public key {expected}")]
+pub struct PublicKeyMismatch {
+    /// 保存されていた公開鍵（hex）
+    pub stored: String,
+    /// 秘密鍵から導出した公開鍵（hex）
+    pub expected: String,
+}
+
+/// 復号化した鍵ファイルの中身
+struct DecryptedKeyFile {
+    secret_key: SecretString,
+    /// 秘密鍵から導出した公開鍵（hex）
+    public_key_hex: String,
+    /// 現行フォーマットより古いか
+    needs_upgrade: bool,
+    /// v3のデータ鍵（旧フォーマットの場合はNone）
+    data_key: Option<Zeroizing<[u8; DATA_KEY_LEN]>>,
+    /// アンロックに使われたスロット（旧フォーマットの場合は0）
+    slot_index: usize,
+}
+
+/// パスワード間違い（AES-GCMの認証失敗）
+#[derive(Debug, thiserror::Error)]
+#[error("Failed to decrypt secret key (wrong password?)")]
+pub struct WrongPassword;
+
+/// 連続失敗によるアンロック拒否
+#[derive(Debug, thiserror::Error)]
+pub enum UnlockError {
+    /// バックオフ中（retry_after_secs秒後に再試行可能）
+    #[error("Too many failed unlock attempts. Try again in {retry_after_secs} seconds")]
+    Throttled { retry_after_secs: u64 },
+    /// ポリシーにより鍵が削除された
+    #[error("Secret key was wiped after {attempts} consecutive failed unlock attempts")]
+    Wiped { attempts: u32 },
+}
+
+/// アンロック失敗時のポリシー
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnlockPolicy {
+    /// 待ち時間なしで許可する連続失敗回数
+    pub free_attempts: u32,
+    /// 最初の待ち時間（秒）。以降は失敗ごとに倍になる
+    pub base_delay_secs: u64,
+    /// 待ち時間の上限（秒）
+    pub max_delay_secs: u64,
+    /// 連続失敗がこの回数に達したら鍵を削除（Noneの場合は削除しない）
+    pub wipe_after: Option<u32>,
+}
+
+impl Default for UnlockPolicy {
+    fn default() -> Self {
+        Self {
+            free_attempts: 3,
+            base_delay_secs: 2,
+            max_delay_secs: 15 * 60,
+            wipe_after: None,
+        }
+    }
+}
+
+/// 新しく作成するSecureKeyStoreに適用するポリシー（アプリ設定から変更可能）
+static UNLOCK_POLICY: once_cell::sync::Lazy<RwLock<UnlockPolicy>> =
+    once_cell::sync::Lazy::new(|| RwLock::new(UnlockPolicy::default()));
+
+/// アンロックポリシーを設定
+pub fn set_unlock_policy(policy: UnlockPolicy) {
+    *UNLOCK_POLICY.write().unwrap_or_else(|e| e.into_inner()) = policy;
+}
+
+/// 現在のアンロックポリシーを取得
+pub fn unlock_policy() -> UnlockPolicy {
+    *UNLOCK_POLICY.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// 失敗回数の記録を保存するバックエンド（Noneの場合は鍵ファイルと同じバックエンド）
+static UNLOCK_ATTEMPTS_BACKEND: once_cell::sync::Lazy<RwLock<Option<String>>> =
+    once_cell::sync::Lazy::new(|| RwLock::new(None));
+
+/// 失敗回数の記録を保存するバックエンドを設定（`callback://<backend_id>`・`memory://`）
+///
+/// 鍵ファイルと同じ場所に書き込める相手は記録も削除・書き換えできるので、
+/// 失敗回数を確実に残すにはKeychain/Keystoreなど呼び出し側が保護するバックエンドを指定する。
+/// 変更前の記録は引き継がれない
+pub fn set_unlock_attempts_backend(location: Option<String>) {
+    *UNLOCK_ATTEMPTS_BACKEND.write().unwrap_or_else(|e| e.into_inner()) = location;
+}
+
+/// 記録のパスごとのアンロック用ロック（`SecureKeyStore::unlock_guard`）
+static UNLOCK_GUARDS: once_cell::sync::Lazy<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 失敗回数の記録（[path].attempts にJSONで保存）
+///
+/// macは鍵ファイルの公開鍵から導出したキーによるHMAC-SHA256で、記録をその鍵に紐付ける
+/// （他の鍵の記録をコピーしても無効になる）。
+/// キーは秘密ではないので、記録は参考値でしかない。ストレージに書き込める相手は
+/// 記録の削除や回数0の記録の偽造ができるため、無効な記録は回数0として扱う（警告ログのみ）。
+/// 失敗回数を確実に残すには`set_unlock_attempts_backend`で保護されたバックエンドに保存する
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct AttemptRecord {
+    failed_attempts: u32,
+    last_failure_at: i64,
+    #[serde(default)]
+    mac: String,
+}
+
+impl AttemptRecord {
+    /// 公開鍵のない旧フォーマットでは鍵ファイルの内容に紐付ける
+    /// （パスワード変更や保存し直しで鍵ファイルが変わっても、同じ鍵なら記録は有効なまま）
+    fn mac_key(key_file: &[u8]) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(b"meiso-unlock-attempts-v2");
+        match KeyFileSummary::parse(key_file).ok().and_then(|summary| summary.public_key) {
+            Some(public_key) => hasher.update(public_key),
+            None => hasher.update(key_file),
+        }
+        hasher.finalize().into()
+    }
+
+    fn compute_mac(&self, key_file: &[u8]) -> String {
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(&Self::mac_key(key_file))
+            .expect("HMAC accepts keys of any length");
+        mac.update(&self.failed_attempts.to_le_bytes());
+        mac.update(&self.last_failure_at.to_le_bytes());
+        ::base64::engine::general_purpose::STANDARD.encode(mac.finalize().into_bytes())
+    }
+
+    fn seal(mut self, key_file: &[u8]) -> Self {
+        self.mac = self.compute_mac(key_file);
+        self
+    }
+
+    fn verify(&self, key_file: &[u8]) -> bool {
+        let expected = self.compute_mac(key_file);
+        // 定数時間比較
+        expected.len() == self.mac.len()
+            && expected
+                .bytes()
+                .zip(self.mac.bytes())
+                .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+                == 0
+    }
+
+    /// 次の試行まで待つ必要のある秒数
+    fn retry_after_secs(&self, policy: &UnlockPolicy, now: i64) -> u64 {
+        if self.failed_attempts < policy.free_attempts.max(1) {
+            return 0;
+        }
+        let exponent = (self.failed_attempts - policy.free_attempts.max(1)).min(32);
+        let delay = policy
+            .base_delay_secs
+            .saturating_mul(1u64 << exponent)
+            .min(policy.max_delay_secs);
+        let elapsed = now.saturating_sub(self.last_failure_at).max(0) as u64;
+        delay.saturating_sub(elapsed)
+    }
+}
+
+/// アンロック状態（UI表示用）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnlockStatus {
+    /// 連続失敗回数
+    pub failed_attempts: u32,
+    /// 次の試行まで待つ必要のある秒数（0なら即試行可能）
+    pub retry_after_secs: u64,
+    /// 鍵が削除されるまでの残り試行回数（削除ポリシーなしの場合はNone）
+    pub attempts_until_wipe: Option<u32>,
+}
+
+/// 鍵ストアの診断結果（パスワード不要で取得できる情報のみ）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeystoreReport {
+    /// 暗号化された秘密鍵ファイルが存在するか
+    pub has_encrypted_key: bool,
+    /// 公開鍵ファイルが存在するか
+    pub has_public_key: bool,
+    /// フォーマットバージョン（0はヘッダーなしの旧フォーマット、解析できない場合はNone）
+    pub format_version: Option<u8>,
+    /// 鍵導出パラメータ
+    pub kdf: Option<KdfParams>,
+    /// 紐付けられた公開鍵（hex、ヘッダーまたは公開鍵ファイルから）
+    pub public_key_hex: Option<String>,
+    /// キースロット
+    pub slots: Vec<KeySlotInfo>,
+    /// 秘密鍵ファイルのサイズ（バイト）
+    pub file_size: u64,
+    /// ファイルのパーミッション（unixのみ）
+    pub file_mode: Option<u32>,
+    /// パーミッションが所有者のみに限定されているか（unix以外では常にtrue）
+    pub permissions_ok: bool,
+    /// 構造的に正しく、パスワードがあれば復号化を試みられる状態か
+    pub valid: bool,
+    /// 検出した問題（UI表示用）
+    pub problems: Vec<String>,
+}
+
+/// CPUを占有する処理（Argon2・scrypt）をブロッキング用スレッドで実行
+/// 非同期ランタイムのワーカーと呼び出し元を止めないようにする
+pub(crate) async fn run_blocking<T, F>(f: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .context("Blocking key derivation task failed")?
+}
+
+fn unix_now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
 
 /// セキュアな鍵ストレージ
 /// Argon2id + AES-256-GCMで秘密鍵を暗号化保存
 pub struct SecureKeyStore {
+    storage: Arc<dyn KeyStorage>,
+    /// バックエンド内での鍵ファイル名（ファイルバックエンドではパス）
     storage_path: String,
+    /// 失敗回数の記録の保存先
+    attempts_storage: Arc<dyn KeyStorage>,
+    unlock_policy: UnlockPolicy,
+    /// 新しく保存・追加するスロットのArgon2パラメータ（読み込み時はファイルに記録された値を使う）
+    kdf: KdfParams,
 }
 
-impl SecureKeyStore {
-    /// 新しいSecureKeyStoreインスタンスを作成
-    pub fn new(storage_path: String) -> Self {
-        println!("🔐 SecureKeyStore initialized at: {}", storage_path);
-        Self { storage_path }
+impl SecureKeyStore {
+    /// 新しいSecureKeyStoreインスタンスを作成
+    ///
+    /// `storage_path`はファイルパスの他、`memory://<name>`・`callback://<backend_id>/<name>`を指定できる
+    pub fn new(storage_path: String) -> Self {
+        log_debug!("🔐 SecureKeyStore initialized at: {}", storage_path);
+        let (storage, storage_path) = resolve_storage(&storage_path);
+        Self::with_storage(storage, storage_path)
+    }
+
+    /// バックエンドを指定して作成
+    pub fn with_storage(storage: Arc<dyn KeyStorage>, name: impl Into<String>) -> Self {
+        let attempts_storage = match UNLOCK_ATTEMPTS_BACKEND.read().unwrap_or_else(|e| e.into_inner()).as_deref() {
+            Some(location) => resolve_storage(location).0,
+            None => storage.clone(),
+        };
+        Self {
+            storage,
+            attempts_storage,
+            storage_path: name.into(),
+            unlock_policy: unlock_policy(),
+            kdf: KdfParams::DEFAULT,
+        }
+    }
+
+    /// 失敗回数の記録の保存先を指定
+    pub fn with_attempts_storage(mut self, storage: Arc<dyn KeyStorage>) -> Self {
+        self.attempts_storage = storage;
+        self
+    }
+
+    /// アンロックポリシーを指定
+    pub fn with_unlock_policy(mut self, policy: UnlockPolicy) -> Self {
+        self.unlock_policy = policy;
+        self
+    }
+
+    /// 保存時のArgon2パラメータを指定（プロファイルまたは`calibrate_kdf`の結果）
+    pub fn with_kdf_params(mut self, kdf: KdfParams) -> Result<Self> {
+        kdf.validate()?;
+        self.kdf = kdf;
+        Ok(self)
+    }
+
+    fn attempts_path(&self) -> String {
+        format!("{}.attempts", self.storage_path)
+    }
+
+    /// 失敗回数の記録を読み込む（記録がない・無効な場合は回数0）
+    /// ストレージには書き込まないので、状態の取得にも使える
+    async fn read_attempts(&self, key_file: &[u8]) -> Result<AttemptRecord> {
+        let data = self
+            .attempts_storage
+            .read(&self.attempts_path())
+            .await
+            .context("Failed to read unlock attempts file")?;
+        let Some(data) = data else {
+            return Ok(AttemptRecord::default());
+        };
+
+        match serde_json::from_slice::<AttemptRecord>(&data) {
+            Ok(record) if record.verify(key_file) => Ok(record),
+            _ => {
+                log_warn!("⚠️ Unlock attempts record is invalid or belongs to another key; ignoring it");
+                Ok(AttemptRecord::default())
+            }
+        }
+    }
+
+    async fn write_attempts(&self, record: &AttemptRecord) -> Result<()> {
+        let data = serde_json::to_vec(record)?;
+        self.attempts_storage
+            .write(&self.attempts_path(), &data)
+            .await
+            .context("Failed to write unlock attempts file")
+    }
+
+    /// 失敗回数を0に戻す（記録を削除）
+    async fn reset_attempts(&self) -> Result<()> {
+        self.attempts_storage
+            .delete(&self.attempts_path())
+            .await
+            .context("Failed to delete unlock attempts file")?;
+        Ok(())
+    }
+
+    async fn clear_attempts(&self) {
+        let _ = self.attempts_storage.delete(&self.attempts_path()).await;
+    }
+
+    /// 同じ記録に対するアンロックを直列化するロック
+    ///
+    /// 並行して呼ばれると、どの呼び出しも失敗が記録される前の回数を読んでしまい、
+    /// バックオフをすり抜けて何度でも試せるため
+    fn unlock_guard(&self) -> Arc<tokio::sync::Mutex<()>> {
+        let mut guards = UNLOCK_GUARDS.lock().unwrap_or_else(|e| e.into_inner());
+        // 使われていないロックは捨てる
+        guards.retain(|_, guard| Arc::strong_count(guard) > 1);
+        guards.entry(self.attempts_path()).or_default().clone()
+    }
+
+    /// 失敗回数を考慮して鍵ファイルを復号化
+    ///
+    /// バックオフ中はArgon2を実行せずに拒否する。パスワード間違いの場合は失敗回数を記録し、
+    /// ポリシーの上限に達したら鍵を削除する
+    async fn unlock_key_file(&self, data: &[u8], method: &UnlockMethod) -> Result<DecryptedKeyFile> {
+        // 回数の確認から失敗の記録までを他のアンロックと重ねない
+        let guard = self.unlock_guard();
+        let _guard = guard.lock().await;
+        let mut record = self.read_attempts(data).await?;
+
+        let retry_after_secs = record.retry_after_secs(&self.unlock_policy, unix_now());
+        if retry_after_secs > 0 {
+            log_warn!("⏱️ Unlock throttled: retry in {}s", retry_after_secs);
+            return Err(UnlockError::Throttled { retry_after_secs }.into());
+        }
+
+        let decrypted = {
+            let (data, method) = (data.to_vec(), method.clone());
+            run_blocking(move || Ok(Self::decrypt_key_file(&data, &method))).await?
+        };
+        match decrypted {
+            Ok(result) => {
+                if record.failed_attempts > 0 {
+                    self.reset_attempts().await?;
+                }
+                Ok(result)
+            }
+            Err(e) if e.is::<WrongPassword>() => {
+                record.failed_attempts = record.failed_attempts.saturating_add(1);
+                record.last_failure_at = unix_now();
+                log_error!("❌ Unlock failed ({} consecutive)", record.failed_attempts);
+
+                if let Some(wipe_after) = self.unlock_policy.wipe_after {
+                    if record.failed_attempts >= wipe_after {
+                        log_warn!("🗑️ Wiping secret key after {} failed attempts", record.failed_attempts);
+                        self.delete_keys().await?;
+                        return Err(UnlockError::Wiped {
+                            attempts: record.failed_attempts,
+                        }
+                        .into());
+                    }
+                }
+
+                self.write_attempts(&record.seal(data)).await?;
+                Err(e)
+            }
+            // 構造的なエラーはパスワードに依存しないので数えない
+            Err(e) => Err(e),
+        }
+    }
+
+    /// 現在のアンロック状態を取得（パスワード不要）
+    pub async fn unlock_status(&self) -> Result<UnlockStatus> {
+        let data = self.read_key_file().await?;
+        let record = self.read_attempts(&data).await?;
+
+        Ok(UnlockStatus {
+            failed_attempts: record.failed_attempts,
+            retry_after_secs: record.retry_after_secs(&self.unlock_policy, unix_now()),
+            attempts_until_wipe: self
+                .unlock_policy
+                .wipe_after
+                .map(|wipe_after| wipe_after.saturating_sub(record.failed_attempts)),
+        })
+    }
+
+    /// パスワードから暗号化鍵を導出
+    /// Argon2idを使用（メモリハード、サイドチャネル攻撃耐性）
+    /// 導出した鍵はDrop時にゼロ埋めされる
+    pub(crate) fn derive_key_from_password(password: &str, salt: &[u8], kdf: &KdfParams) -> Result<Zeroizing<[u8; 32]>> {
+        use argon2::{Algorithm, Argon2, Params, Version};
+        
+        // Argon2idの設定（鍵ファイルに記録されたパラメータを使用）
+        let params = Params::new(kdf.memory_kib, kdf.iterations, kdf.parallelism, Some(32))
+            .map_err(|e| anyhow::anyhow!("Invalid Argon2 parameters: {}", e))?;
+        let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+        
+        // 32バイトの鍵を直接導出
+        let mut key = Zeroizing::new([0u8; 32]);
+        argon2
+            .hash_password_into(password.as_bytes(), salt, key.as_mut_slice())
+            .map_err(|e| anyhow::anyhow!("Failed to derive key with Argon2: {}", e))?;
+        
+        Ok(key)
+    }
+
+    /// 秘密鍵を暗号化して鍵ファイルの内容を作成（パスワードのスロット1つ）
+    /// 呼び出しごとに新しいデータ鍵・salt・nonceを生成する
+    /// 秘密鍵から導出した公開鍵をヘッダーに記録する
+    fn encrypt_key_file(secret_key: &SecretString, password: &str, kdf: KdfParams) -> Result<Vec<u8>> {
+        let public_key = Self::secret_key_public_key(secret_key)?;
+
+        let mut data_key = Zeroizing::new([0u8; DATA_KEY_LEN]);
+        OsRng.fill_bytes(data_key.as_mut_slice());
+
+        let method = UnlockMethod::Password(password.to_string());
+        let slot = KeySlot::seal(&method, kdf, &data_key, &SlottedKeyFile::prefix(&public_key))?;
+        SlottedKeyFile::seal(secret_key, public_key, &data_key, vec![slot])
+    }
+
+    /// 秘密鍵を検証し、対応する公開鍵（32バイト）を求める
+    fn secret_key_public_key(secret_key: &SecretString) -> Result<[u8; PUBKEY_LEN]> {
+        let keys = Keys::parse(secret_key.expose_secret())
+            .map_err(MeisoError::invalid_key)?;
+        Ok(public_key_to_bytes(&keys.public_key()))
+    }
+
+    /// 復号化した秘密鍵の公開鍵がヘッダーの公開鍵と一致するか検証（公開鍵hexを返す）
+    fn verify_public_key(secret_key: &SecretString, stored: Option<&[u8; PUBKEY_LEN]>) -> Result<String> {
+        let public_key_hex = Keys::parse(secret_key.expose_secret())
+            .map_err(|e| MeisoError::corrupt_key_file(format!("decrypted secret key is invalid: {}", e)))?
+            .public_key()
+            .to_hex();
+        if let Some(stored) = stored {
+            let stored = public_key_hex_from_bytes(stored);
+            if stored != public_key_hex {
+                return Err(PublicKeyMismatch {
+                    stored,
+                    expected: public_key_hex,
+                }
+                .into());
+            }
+        }
+        Ok(public_key_hex)
+    }
+
+    /// 鍵ファイルの内容を復号化
+    /// ヘッダーに公開鍵がある場合は秘密鍵から導出した公開鍵と一致するか検証する
+    fn decrypt_key_file(data: &[u8], method: &UnlockMethod) -> Result<DecryptedKeyFile> {
+        // v3: スロットからデータ鍵を取り出して復号化
+        if SlottedKeyFile::is_slotted(data) {
+            let file = SlottedKeyFile::parse(data)?;
+            let (slot_index, data_key) = file.open(method)?;
+            let secret_key = file.decrypt(&data_key)?;
+            let public_key_hex = Self::verify_public_key(&secret_key, Some(&file.public_key))?;
+            return Ok(DecryptedKeyFile {
+                secret_key,
+                public_key_hex,
+                needs_upgrade: false,
+                data_key: Some(data_key),
+                slot_index,
+            });
+        }
+
+        // v2以前: パスワードのみ
+        let UnlockMethod::Password(password) = method else {
+            return Err(MeisoError::invalid_argument("key file uses an old format that can only be unlocked with the password").into());
+        };
+
+        // 1. ヘッダーと暗号文を分離
+        let parsed = ParsedKeyFile::parse(data)?;
+        
+        // 2. パスワードから復号化鍵を導出（保存時のパラメータを使用）
+        let key = Self::derive_key_from_password(password, &parsed.salt, &parsed.kdf)?;
+        
+        // 3. 復号化
+        let cipher = Aes256Gcm::new((&*key).into());
+        let nonce = Nonce::from(parsed.nonce);
+        
+        let plaintext = Zeroizing::new(
+            cipher
+                .decrypt(&nonce, Payload { msg: parsed.ciphertext, aad: parsed.aad })
+                .map_err(|_| WrongPassword)?,
+        );
+        
+        let secret_key = std::str::from_utf8(&plaintext)
+            .map(SecretString::from)
+            .map_err(|_| MeisoError::corrupt_key_file("decrypted data is not valid UTF-8"))?;
+        
+        // 4. 公開鍵の整合性チェック
+        let public_key_hex = Self::verify_public_key(&secret_key, parsed.public_key.as_ref())?;
+        
+        Ok(DecryptedKeyFile {
+            secret_key,
+            public_key_hex,
+            needs_upgrade: true,
+            data_key: None,
+            slot_index: 0,
+        })
+    }
+
+    /// 秘密鍵を暗号化して保存
+    /// 
+    /// フォーマット: v3（パスワードのスロット1つ）
+    /// ヘッダーにはバージョン・公開鍵・スロットが含まれ、AADとして認証される
+    pub async fn save_encrypted_key(&self, secret_key: &SecretString, password: &str) -> Result<()> {
+        log_info!("🔐 Encrypting and saving secret key...");
+        
+        let data = {
+            let (secret_key, password, kdf) = (secret_key.clone(), SecretString::from(password), self.kdf);
+            run_blocking(move || Self::encrypt_key_file(&secret_key, password.expose_secret(), kdf)).await?
+        };
+        
+        // 鍵ファイルより先に戻す（途中で失敗しても古い失敗回数が新しいパスワードに残らないように）
+        self.reset_attempts().await?;
+        self.storage
+            .write(&self.storage_path, &data)
+            .await
+            .context("Failed to write encrypted key to file")?;
+
+        // 既存の公開鍵ファイルがあれば新しい鍵に合わせる（古い公開鍵が残らないように）
+        if self.has_public_key().await {
+            let public_key_hex = Self::read_header_public_key(&data)?
+                .context("Saved key file has no public key")?;
+            self.write_public_key_file(&public_key_hex).await?;
+        }
+        
+        log_info!("✅ Secret key encrypted and saved successfully");
+        Ok(())
+    }
+
+    /// 暗号化された秘密鍵をファイルから読み込んで復号化
+    ///
+    /// ヘッダーなしの旧フォーマットも読み込み可能で、復号に成功した場合は
+    /// 現行フォーマットで保存し直す
+    pub async fn load_encrypted_key(&self, password: &str) -> Result<SecretString> {
+        self.unlock(&UnlockMethod::Password(password.to_string())).await
+    }
+
+    /// 指定の方法（パスワード・端末鍵・リカバリーコード）で秘密鍵を復号化
+    pub async fn unlock(&self, method: &UnlockMethod) -> Result<SecretString> {
+        log_info!("🔐 Loading and decrypting secret key ({:?})...", method.kind());
+        
+        let data = self.read_key_file().await?;
+        
+        let decrypted = self.unlock_key_file(&data, method).await?;
+        
+        log_info!("✅ Secret key decrypted successfully");
+
+        // 公開鍵ファイルが秘密鍵と食い違っていないか確認
+        if let Some(stored) = self.read_public_key_file().await? {
+            if stored != decrypted.public_key_hex {
+                log_error!("❌ Public key file does not match the encrypted secret key");
+                return Err(PublicKeyMismatch {
+                    stored,
+                    expected: decrypted.public_key_hex,
+                }
+                .into());
+            }
+        }
+
+        // 古いフォーマットの場合は現行フォーマットに移行（旧フォーマットはパスワードでのみ開ける）
+        if let (true, UnlockMethod::Password(password)) = (decrypted.needs_upgrade, method) {
+            log_info!("🔄 Upgrading key file to format v{}...", KEY_FILE_VERSION);
+            if let Err(e) = self.save_encrypted_key(&decrypted.secret_key, password).await {
+                // 移行に失敗しても旧ファイルはそのまま読めるので続行
+                log_warn!("⚠️ Failed to upgrade key file: {}", e);
+            }
+        }
+
+        Ok(decrypted.secret_key)
+    }
+
+    /// パスワードを変更（旧パスワードで開けたスロットを新しいパスワードのスロットに置き換える）
+    /// Argon2パラメータは置き換えるスロットのものを引き継ぐ
+    ///
+    /// 一時ファイルへの書き込み → fsync → renameで置き換えるため、
+    /// どの時点で失敗しても元の鍵ファイルはそのまま残る
+    pub async fn change_password(&self, old_password: &str, new_password: &str) -> Result<()> {
+        log_info!("🔐 Changing key store password...");
+
+        let new_method = UnlockMethod::Password(new_password.to_string());
+        self.edit_slots(&UnlockMethod::Password(old_password.to_string()), move |edit| {
+            let slot = edit.reseal(edit.unlocked_slot, &new_method)?;
+            edit.slots[edit.unlocked_slot] = slot;
+            Ok(())
+        })
+        .await?;
+
+        log_info!("✅ Key store password changed successfully");
+        Ok(())
+    }
+
+    /// キースロットの一覧（パスワード不要）
+    pub async fn list_key_slots(&self) -> Result<Vec<KeySlotInfo>> {
+        let data = self.read_key_file().await?;
+        Ok(KeyFileSummary::parse(&data)?.slots)
+    }
+
+    /// スロットを追加（既存のスロットでアンロックして、データ鍵を新しい方法でも包む）
+    /// 追加したスロットの番号を返す
+    pub async fn add_key_slot(&self, unlock: &UnlockMethod, new_slot: &UnlockMethod) -> Result<u32> {
+        log_info!("🔐 Adding {:?} key slot...", new_slot.kind());
+
+        let new_slot = new_slot.clone();
+        let index = self
+            .edit_slots(unlock, move |edit| {
+                if edit.slots.len() >= MAX_KEY_SLOTS {
+                    return Err(MeisoError::invalid_argument(format!("key file already has the maximum of {} slots", MAX_KEY_SLOTS)).into());
+                }
+                let slot = edit.seal(&new_slot)?;
+                edit.slots.push(slot);
+                Ok(edit.slots.len() as u32 - 1)
+            })
+            .await?;
+
+        log_info!("✅ Key slot {} added", index);
+        Ok(index)
+    }
+
+    /// リカバリーコードのスロットを追加し、生成したコードを返す（再表示はできない）
+    pub async fn add_recovery_code_slot(&self, unlock: &UnlockMethod) -> Result<SecretString> {
+        let code = generate_recovery_code();
+        self.add_key_slot(unlock, &UnlockMethod::RecoveryCode(code.expose_secret().to_string()))
+            .await?;
+        Ok(code)
+    }
+
+    /// スロットを削除（最後の1つは削除できない）
+    pub async fn remove_key_slot(&self, unlock: &UnlockMethod, index: u32) -> Result<()> {
+        log_info!("🔐 Removing key slot {}...", index);
+
+        self.edit_slots(unlock, move |edit| {
+            let index = index as usize;
+            if index >= edit.slots.len() {
+                return Err(MeisoError::invalid_argument(format!("key slot {} does not exist", index)).into());
+            }
+            if edit.slots.len() == 1 {
+                return Err(MeisoError::invalid_argument("cannot remove the last key slot").into());
+            }
+            edit.slots.remove(index);
+            Ok(())
+        })
+        .await?;
+
+        log_info!("✅ Key slot {} removed", index);
+        Ok(())
+    }
+
+    /// アンロックしてスロットを編集し、同じデータ鍵で鍵ファイルを書き直す
+    /// 旧フォーマットの場合はアンロックに使ったパスワードのスロットを持つv3に移行する
+    /// 編集（新しいスロットの鍵導出）はブロッキング用スレッドで実行される
+    async fn edit_slots<T, F>(&self, unlock: &UnlockMethod, edit: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut SlotEdit) -> Result<T> + Send + 'static,
+    {
+        let data = self.read_key_file().await?;
+        let decrypted = self.unlock_key_file(&data, unlock).await?;
+        let public_key = Self::secret_key_public_key(&decrypted.secret_key)?;
+
+        let (unlock, kdf) = (unlock.clone(), self.kdf);
+        let (result, new_data) = run_blocking(move || {
+            let (data_key, slots) = match decrypted.data_key {
+                Some(data_key) => (data_key, SlottedKeyFile::parse(&data)?.slots),
+                None => {
+                    let mut data_key = Zeroizing::new([0u8; DATA_KEY_LEN]);
+                    OsRng.fill_bytes(data_key.as_mut_slice());
+                    let slot = KeySlot::seal(&unlock, kdf, &data_key, &SlottedKeyFile::prefix(&public_key))?;
+                    (data_key, vec![slot])
+                }
+            };
+
+            let mut slot_edit = SlotEdit {
+                slots,
+                unlocked_slot: decrypted.slot_index,
+                data_key,
+                public_key,
+                kdf,
+            };
+            let result = edit(&mut slot_edit)?;
+
+            let new_data = SlottedKeyFile::seal(
+                &decrypted.secret_key,
+                public_key,
+                &slot_edit.data_key,
+                slot_edit.slots,
+            )?;
+            Ok((result, new_data))
+        })
+        .await?;
+        self.reset_attempts().await?;
+        self.storage
+            .write(&self.storage_path, &new_data)
+            .await
+            .context("Failed to write re-encrypted key to file")?;
+        Ok(result)
+    }
+
+    /// 秘密鍵をNIP-49形式（ncryptsec）でエクスポート
+    ///
+    /// 鍵ストアのパスワードで復号し、同じパスワードとscrypt(2^log_n)で再暗号化する。
+    /// 他のNostrクライアントで読み込める形式のため、生のnsecをコピーする必要がない
+    pub async fn export_ncryptsec(&self, password: &str, log_n: u8) -> Result<String> {
+        if !(NCRYPTSEC_MIN_LOG_N..=NCRYPTSEC_MAX_LOG_N).contains(&log_n) {
+            return Err(MeisoError::invalid_argument(format!(
+                "log_n must be between {} and {} (got {})",
+                NCRYPTSEC_MIN_LOG_N, NCRYPTSEC_MAX_LOG_N, log_n
+            ))
+            .into());
+        }
+
+        let secret_key = self.load_encrypted_key(password).await?;
+        let keys = Keys::parse(secret_key.expose_secret())
+            .map_err(|e| MeisoError::corrupt_key_file(format!("stored secret key is invalid: {}", e)))?;
+
+        log_info!("🔐 Exporting secret key as ncryptsec (log_n={})...", log_n);
+
+        // 鍵は暗号化された状態でのみ扱われてきたため KeySecurity::Medium (0x01)
+        let encrypted = {
+            let password = SecretString::from(password);
+            run_blocking(move || {
+                EncryptedSecretKey::new(keys.secret_key(), password.expose_secret(), log_n, KeySecurity::Medium)
+                    .map_err(|e| anyhow::anyhow!("Failed to encrypt secret key with NIP-49: {}", e))
+            })
+            .await?
+        };
+        let ncryptsec = encrypted
+            .to_bech32()
+            .map_err(|e| anyhow::anyhow!("Failed to encode ncryptsec: {}", e))?;
+
+        log_info!("✅ Secret key exported as ncryptsec");
+        Ok(ncryptsec)
+    }
+
+    /// NIP-49形式（ncryptsec）の秘密鍵を復号して鍵ストアに保存（公開鍵hexを返す）
+    ///
+    /// 同じパスワードで鍵ストアに保存するため、復号した秘密鍵は呼び出し側に返らない
+    pub async fn import_ncryptsec(&self, ncryptsec: &str, password: &str) -> Result<String> {
+        log_info!("🔐 Importing ncryptsec secret key...");
+
+        let encrypted = EncryptedSecretKey::from_bech32(ncryptsec.trim())
+            .map_err(|e| MeisoError::invalid_key(format!("invalid ncryptsec: {}", e)))?;
+
+        if matches!(encrypted.key_security(), KeySecurity::Weak) {
+            log_warn!("⚠️ Imported ncryptsec is marked as weak (key was handled insecurely)");
+        }
+
+        let secret_key = {
+            let password = SecretString::from(password);
+            run_blocking(move || {
+                encrypted
+                    .to_secret_key(password.expose_secret())
+                    .map_err(|_| MeisoError::WrongPassword.into())
+            })
+            .await?
+        };
+        let keys = Keys::new(secret_key);
+
+        let secret_hex = SecretString::from(keys.secret_key().to_secret_hex());
+        self.save_encrypted_key(&secret_hex, password).await?;
+
+        let public_key_hex = keys.public_key().to_hex();
+        log_info!("✅ ncryptsec imported: {}...", &public_key_hex[..16]);
+        Ok(public_key_hex)
+    }
+
+    /// 暗号化された秘密鍵ファイルを読み込む
+    async fn read_key_file(&self) -> Result<Vec<u8>> {
+        self.storage
+            .read(&self.storage_path)
+            .await
+            .context("Failed to read encrypted key file")?
+            .ok_or_else(|| MeisoError::KeyNotFound.into())
+    }
+
+    fn public_key_path(&self) -> String {
+        format!("{}.pub", self.storage_path)
     }
 
-    /// パスワードから暗号化鍵を導出
-    /// Argon2idを使用（メモリハード、サイドチャネル攻撃耐性）
-    fn derive_key_from_password(password: &str, salt: &[u8]) -> Result<[u8; 32]> {
-        use argon2::{Algorithm, Argon2, Params, Version};
-        
-        // Argon2idの設定
-        let argon2 = Argon2::new(
-            Algorithm::Argon2id,
-            Version::V0x13,
-            Params::default(),
-        );
-        
-        // 32バイトの鍵を直接導出
-        let mut key = [0u8; 32];
-        argon2
-            .hash_password_into(password.as_bytes(), salt, &mut key)
-            .context("Failed to derive key with Argon2")?;
-        
-        Ok(key)
+    /// 鍵ファイルのヘッダーから公開鍵を読み取る（v2以降、パスワード不要）
+    fn read_header_public_key(data: &[u8]) -> Result<Option<String>> {
+        Ok(KeyFileSummary::parse(data)?
+            .public_key
+            .as_ref()
+            .map(public_key_hex_from_bytes))
     }
 
-    /// 秘密鍵を暗号化して保存
-    /// 
-    /// フォーマット: [salt(16B)] + [nonce(12B)] + [ciphertext]
-    pub async fn save_encrypted_key(&self, secret_key: &str, password: &str) -> Result<()> {
-        println!("🔐 Encrypting and saving secret key...");
-        
-        // 1. ランダムなsaltを生成（16バイト）
-        let mut salt = [0u8; 16];
-        OsRng.fill_bytes(&mut salt);
-        
-        // 2. パスワードから暗号化鍵を導出
-        let key = Self::derive_key_from_password(password, &salt)?;
-        
-        // 3. ランダムなnonceを生成（12バイト）
-        let mut nonce_bytes = [0u8; 12];
-        OsRng.fill_bytes(&mut nonce_bytes);
-        let nonce = Nonce::from(nonce_bytes);
-        
-        // 4. AES-256-GCMで暗号化
-        let cipher = Aes256Gcm::new(&key.into());
-        let ciphertext = cipher
-            .encrypt(&nonce, secret_key.as_bytes())
-            .map_err(|e| anyhow::anyhow!("Failed to encrypt secret key with AES-256-GCM: {:?}", e))?;
-        
-        // 5. ファイルに保存: salt + nonce + ciphertext
-        let mut data = Vec::new();
-        data.extend_from_slice(&salt);
-        data.extend_from_slice(&nonce_bytes);
-        data.extend_from_slice(&ciphertext);
-        
-        tokio::fs::write(&self.storage_path, data)
+    /// 公開鍵ファイルを読み込んで検証（存在しない場合はNone）
+    async fn read_public_key_file(&self) -> Result<Option<String>> {
+        let Some(data) = self
+            .storage
+            .read(&self.public_key_path())
             .await
-            .context("Failed to write encrypted key to file")?;
-        
-        println!("✅ Secret key encrypted and saved successfully");
-        Ok(())
+            .context("Failed to read public key file")?
+        else {
+            return Ok(None);
+        };
+        std::str::from_utf8(&data)
+            .map_err(anyhow::Error::from)
+            .and_then(normalize_public_key)
+            .map(Some)
+            .map_err(|e| MeisoError::corrupt_key_file(format!("public key file: {}", e)).into())
     }
 
-    /// 暗号化された秘密鍵をファイルから読み込んで復号化
-    pub async fn load_encrypted_key(&self, password: &str) -> Result<String> {
-        println!("🔐 Loading and decrypting secret key...");
-        
-        // 1. ファイルから読み込み
-        let data = tokio::fs::read(&self.storage_path)
+    async fn write_public_key_file(&self, public_key_hex: &str) -> Result<()> {
+        self.storage
+            .write(&self.public_key_path(), public_key_hex.as_bytes())
             .await
-            .context("Failed to read encrypted key file")?;
-        
-        // 2. データを分離: salt(16B) + nonce(12B) + ciphertext
-        if data.len() < 28 {
-            anyhow::bail!("Encrypted key file is too short (corrupted?)");
+            .context("Failed to write public key to file")
+    }
+
+    /// 暗号化された秘密鍵ファイルのヘッダーに記録された公開鍵（hex）
+    async fn encrypted_key_public_key(&self) -> Result<Option<String>> {
+        match self
+            .storage
+            .read(&self.storage_path)
+            .await
+            .context("Failed to read encrypted key file")?
+        {
+            Some(data) => Self::read_header_public_key(&data),
+            None => Ok(None),
         }
-        
-        let salt = &data[0..16];
-        let nonce_bytes = &data[16..28];
-        let ciphertext = &data[28..];
-        
-        // 3. パスワードから復号化鍵を導出
-        let key = Self::derive_key_from_password(password, salt)?;
-        
-        // 4. 復号化
-        let cipher = Aes256Gcm::new(&key.into());
-        let nonce = Nonce::from(*<&[u8; 12]>::try_from(nonce_bytes).context("Invalid nonce length")?);
-        
-        let plaintext = cipher
-            .decrypt(&nonce, ciphertext)
-            .map_err(|_| anyhow::anyhow!("Failed to decrypt secret key (wrong password?)"))?;
-        
-        let secret_key = String::from_utf8(plaintext)
-            .context("Decrypted data is not valid UTF-8")?;
-        
-        println!("✅ Secret key decrypted successfully");
-        Ok(secret_key)
     }
 
     /// Amber使用時: 公開鍵のみ保存（平文でOK）
+    ///
+    /// hex / npubを受け付け、hex形式で保存する。
+    /// 暗号化された秘密鍵がある場合は、その公開鍵と一致しなければエラー
     pub async fn save_public_key(&self, public_key: &str) -> Result<()> {
-        let pub_path = format!("{}.pub", self.storage_path);
-        println!("🔐 Saving public key to: {}", pub_path);
-        
-        tokio::fs::write(&pub_path, public_key)
-            .await
-            .context("Failed to write public key to file")?;
-        
-        println!("✅ Public key saved successfully");
+        let public_key_hex = normalize_public_key(public_key)?;
+        log_debug!("🔐 Saving public key to: {}", self.public_key_path());
+
+        if let Some(expected) = self.encrypted_key_public_key().await? {
+            if expected != public_key_hex {
+                return Err(PublicKeyMismatch {
+                    stored: public_key_hex,
+                    expected,
+                }
+                .into());
+            }
+        }
+
+        self.write_public_key_file(&public_key_hex).await?;
+
+        log_info!("✅ Public key saved successfully");
         Ok(())
     }
 
-    /// 公開鍵を読み込み（Amber使用時）
+    /// 公開鍵を読み込み（Amber使用時、hex形式）
+    ///
+    /// 暗号化された秘密鍵のヘッダーにある公開鍵と食い違う場合はエラー
     pub async fn load_public_key(&self) -> Result<Option<String>> {
-        let pub_path = format!("{}.pub", self.storage_path);
-        
-        match tokio::fs::read_to_string(&pub_path).await {
-            Ok(key) => {
-                println!("✅ Public key loaded from: {}", pub_path);
-                Ok(Some(key))
-            }
-            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
-                println!("ℹ️ Public key file not found");
-                Ok(None)
+        let Some(public_key_hex) = self.read_public_key_file().await? else {
+            log_info!("ℹ️ Public key file not found");
+            return Ok(None);
+        };
+
+        if let Some(expected) = self.encrypted_key_public_key().await? {
+            if expected != public_key_hex {
+                log_error!("❌ Public key file does not match the encrypted secret key");
+                return Err(PublicKeyMismatch {
+                    stored: public_key_hex,
+                    expected,
+                }
+                .into());
             }
-            Err(e) => Err(e).context("Failed to read public key file"),
         }
+
+        log_debug!("✅ Public key loaded from: {}", self.public_key_path());
+        Ok(Some(public_key_hex))
+    }
+
+    /// 保存されている公開鍵を取得（パスワード不要）
+    ///
+    /// 暗号化された秘密鍵のヘッダー（v2以降）を優先し、なければ公開鍵ファイルを読む
+    pub async fn stored_public_key(&self) -> Result<Option<String>> {
+        if let Some(public_key_hex) = self.encrypted_key_public_key().await? {
+            return Ok(Some(public_key_hex));
+        }
+        self.load_public_key().await
     }
 
     /// 保存された鍵を全て削除
     pub async fn delete_keys(&self) -> Result<()> {
-        println!("🗑️ Deleting stored keys...");
+        log_info!("🗑️ Deleting stored keys...");
         
         let mut deleted_count = 0;
         
         // 暗号化された秘密鍵を削除
-        if tokio::fs::remove_file(&self.storage_path).await.is_ok() {
-            println!("✅ Deleted encrypted secret key");
+        if self.storage.delete(&self.storage_path).await? {
+            log_info!("✅ Deleted encrypted secret key");
             deleted_count += 1;
         }
         
         // 公開鍵を削除
-        let pub_path = format!("{}.pub", self.storage_path);
-        if tokio::fs::remove_file(&pub_path).await.is_ok() {
-            println!("✅ Deleted public key");
+        if self.storage.delete(&self.public_key_path()).await? {
+            log_info!("✅ Deleted public key");
             deleted_count += 1;
         }
+
+        // 失敗回数の記録を削除
+        self.clear_attempts().await;
         
         if deleted_count > 0 {
-            println!("✅ Deleted {} key file(s)", deleted_count);
+            log_info!("✅ Deleted {} key file(s)", deleted_count);
         } else {
-            println!("ℹ️ No key files found to delete");
+            log_info!("ℹ️ No key files found to delete");
         }
         
         Ok(())
@@ -173,19 +1733,139 @@ impl SecureKeyStore {
 
     /// 鍵ファイルが存在するか確認
     pub async fn has_encrypted_key(&self) -> bool {
-        tokio::fs::metadata(&self.storage_path).await.is_ok()
+        self.storage.exists(&self.storage_path).await.unwrap_or(false)
     }
 
     /// 公開鍵ファイルが存在するか確認
     pub async fn has_public_key(&self) -> bool {
-        let pub_path = format!("{}.pub", self.storage_path);
-        tokio::fs::metadata(&pub_path).await.is_ok()
+        self.storage.exists(&self.public_key_path()).await.unwrap_or(false)
+    }
+
+    /// 鍵ストアを診断（パスワード不要）
+    ///
+    /// ファイルの構造・パーミッション・公開鍵の整合性をチェックする。
+    /// 壊れたファイルでもエラーにはせず、`problems`に理由を入れて返す
+    pub async fn inspect(&self) -> Result<KeystoreReport> {
+        let mut report = KeystoreReport {
+            has_encrypted_key: false,
+            has_public_key: false,
+            format_version: None,
+            kdf: None,
+            public_key_hex: None,
+            slots: Vec::new(),
+            file_size: 0,
+            file_mode: None,
+            permissions_ok: true,
+            valid: false,
+            problems: Vec::new(),
+        };
+
+        // 公開鍵ファイル
+        let public_key_file = match self.read_public_key_file().await {
+            Ok(public_key) => {
+                report.has_public_key = public_key.is_some();
+                public_key
+            }
+            Err(e) => {
+                report.has_public_key = true;
+                report.problems.push(format!("{:#}", e));
+                None
+            }
+        };
+        if report.has_public_key {
+            self.check_permissions(&self.public_key_path(), &mut report).await;
+        }
+
+        // 暗号化された秘密鍵ファイル
+        let data = match self
+            .storage
+            .read(&self.storage_path)
+            .await
+            .context("Failed to read encrypted key file")?
+        {
+            Some(data) => data,
+            None => {
+                // Amberモード（公開鍵のみ）なら公開鍵ファイルが正しければ有効
+                report.public_key_hex = public_key_file;
+                report.valid = report.public_key_hex.is_some() && report.problems.is_empty();
+                return Ok(report);
+            }
+        };
+        report.has_encrypted_key = true;
+        report.file_size = data.len() as u64;
+        self.check_permissions(&self.storage_path, &mut report).await;
+
+        let parsed = match KeyFileSummary::parse(&data) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                report.problems.push(format!("{:#}", e));
+                return Ok(report);
+            }
+        };
+        report.format_version = Some(parsed.version);
+        report.kdf = parsed.slots.iter().find_map(|slot| slot.kdf);
+        report.slots = parsed.slots;
+        if parsed.version < KEY_FILE_VERSION {
+            report
+                .problems
+                .push(format!("Key file uses format v{} and will be upgraded on next unlock", parsed.version));
+        }
+
+        let mut structure_ok = true;
+        let plaintext_len = parsed.ciphertext_len.checked_sub(TAG_LEN);
+        if !plaintext_len.is_some_and(|len| SECRET_KEY_LENS.contains(&len)) {
+            structure_ok = false;
+            report.problems.push(format!(
+                "Unexpected ciphertext length {} (file truncated or corrupted?)",
+                parsed.ciphertext_len
+            ));
+        }
+
+        let header_public_key = parsed.public_key.as_ref().map(public_key_hex_from_bytes);
+        if let Some(public_key_hex) = &header_public_key {
+            if normalize_public_key(public_key_hex).is_err() {
+                structure_ok = false;
+                report.problems.push("Public key in key file header is not a valid point".to_string());
+            }
+        }
+        if let (Some(header), Some(file)) = (&header_public_key, &public_key_file) {
+            if header != file {
+                report.problems.push(
+                    PublicKeyMismatch {
+                        stored: file.clone(),
+                        expected: header.clone(),
+                    }
+                    .to_string(),
+                );
+            }
+        }
+
+        report.public_key_hex = header_public_key.or(public_key_file);
+        report.valid = structure_ok;
+        Ok(report)
+    }
+
+    /// 所有者以外が読み書きできるパーミッションになっていないか確認
+    /// （パーミッションを持たないバックエンドでは何もしない）
+    async fn check_permissions(&self, path: &str, report: &mut KeystoreReport) {
+        if let Some(mode) = self.storage.permissions(path).await {
+            if path == self.storage_path {
+                report.file_mode = Some(mode);
+            }
+            if mode & 0o077 != 0 {
+                report.permissions_ok = false;
+                report
+                    .problems
+                    .push(format!("{} is accessible by other users (mode {:o})", path, mode));
+            }
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::storage::MemoryStorage;
     use tempfile::TempDir;
 
     /// テスト用の一時ディレクトリとパスを作成
@@ -195,21 +1875,28 @@ mod tests {
         (temp_dir, storage_path)
     }
 
+    /// テスト用のランダムな鍵（hex秘密鍵）
+    fn test_keys() -> (Keys, SecretString) {
+        let keys = Keys::generate();
+        let secret_key = SecretString::from(keys.secret_key().to_secret_hex());
+        (keys, secret_key)
+    }
+
     #[tokio::test]
     async fn test_encrypt_and_decrypt() {
         let (_temp_dir, storage_path) = setup_test_storage();
         let store = SecureKeyStore::new(storage_path);
         
-        let secret_key = "nsec1test1234567890abcdefghijklmnopqrstuvwxyz";
+        let (_, secret_key) = test_keys();
         let password = "my_secure_password_123";
         
         // 保存
-        store.save_encrypted_key(secret_key, password).await.unwrap();
+        store.save_encrypted_key(&secret_key, password).await.unwrap();
         
         // 読み込み
         let loaded_key = store.load_encrypted_key(password).await.unwrap();
         
-        assert_eq!(secret_key, loaded_key);
+        assert_eq!(secret_key.expose_secret(), loaded_key.expose_secret());
     }
 
     #[tokio::test]
@@ -217,31 +1904,568 @@ mod tests {
         let (_temp_dir, storage_path) = setup_test_storage();
         let store = SecureKeyStore::new(storage_path);
         
-        let secret_key = "nsec1test1234567890abcdefghijklmnopqrstuvwxyz";
+        let (_, secret_key) = test_keys();
         let password = "correct_password";
         let wrong_password = "wrong_password";
         
         // 保存
-        store.save_encrypted_key(secret_key, password).await.unwrap();
+        store.save_encrypted_key(&secret_key, password).await.unwrap();
         
         // 間違ったパスワードで読み込み（失敗するはず）
         let result = store.load_encrypted_key(wrong_password).await;
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_header_records_kdf_params() {
+        let (_temp_dir, storage_path) = setup_test_storage();
+        let store = SecureKeyStore::new(storage_path.clone());
+
+        let (keys, secret_key) = test_keys();
+        store.save_encrypted_key(&secret_key, "password").await.unwrap();
+
+        // ヘッダーにマジック・バージョン・公開鍵・スロットのArgon2パラメータが記録されている
+        let data = std::fs::read(&storage_path).unwrap();
+        assert!(data.starts_with(KEY_FILE_MAGIC));
+        let file = SlottedKeyFile::parse(&data).unwrap();
+        assert_eq!(file.public_key, public_key_to_bytes(&keys.public_key()));
+        assert_eq!(file.slots.len(), 1);
+        assert_eq!(file.slots[0].kind, KeySlotKind::Password);
+        assert_eq!(file.slots[0].kdf, KdfParams::DEFAULT);
+    }
+
+    #[tokio::test]
+    async fn test_legacy_format_is_upgraded() {
+        let (_temp_dir, storage_path) = setup_test_storage();
+        let (keys, secret_key) = test_keys();
+        let secret_key = secret_key.expose_secret();
+        let password = "password";
+
+        // 旧フォーマット（salt + nonce + ciphertext、AADなし）で書き込む
+        let salt = [7u8; SALT_LEN];
+        let nonce_bytes = [9u8; NONCE_LEN];
+        let key = SecureKeyStore::derive_key_from_password(password, &salt, &KdfParams::LEGACY).unwrap();
+        let ciphertext = Aes256Gcm::new((&*key).into())
+            .encrypt(&Nonce::from(nonce_bytes), secret_key.as_bytes())
+            .unwrap();
+        let mut legacy = Vec::new();
+        legacy.extend_from_slice(&salt);
+        legacy.extend_from_slice(&nonce_bytes);
+        legacy.extend_from_slice(&ciphertext);
+        std::fs::write(&storage_path, &legacy).unwrap();
+
+        // 旧フォーマットでも読み込める
+        let store = SecureKeyStore::new(storage_path.clone());
+        assert_eq!(store.load_encrypted_key(password).await.unwrap().expose_secret(), secret_key);
+
+        // 読み込み後は現行フォーマットに移行されている
+        let upgraded = std::fs::read(&storage_path).unwrap();
+        assert!(upgraded.starts_with(KEY_FILE_MAGIC));
+        assert_eq!(store.stored_public_key().await.unwrap(), Some(keys.public_key().to_hex()));
+        assert_eq!(store.load_encrypted_key(password).await.unwrap().expose_secret(), secret_key);
+    }
+
+    #[tokio::test]
+    async fn test_v1_format_is_upgraded() {
+        let (_temp_dir, storage_path) = setup_test_storage();
+        let (keys, secret_key) = test_keys();
+
+        // 公開鍵なしのv1ヘッダーで書き込む
+        let salt = [7u8; SALT_LEN];
+        let nonce_bytes = [9u8; NONCE_LEN];
+        let header = KeyFileHeader {
+            version: 1,
+            kdf: KdfParams::DEFAULT,
+            public_key: None,
+            salt,
+            nonce: nonce_bytes,
+        }
+        .encode();
+        let key = SecureKeyStore::derive_key_from_password("password", &salt, &KdfParams::DEFAULT).unwrap();
+        let ciphertext = Aes256Gcm::new((&*key).into())
+            .encrypt(
+                &Nonce::from(nonce_bytes),
+                Payload { msg: secret_key.expose_secret().as_bytes(), aad: &header },
+            )
+            .unwrap();
+        std::fs::write(&storage_path, [header, ciphertext].concat()).unwrap();
+
+        let store = SecureKeyStore::new(storage_path.clone());
+        assert_eq!(store.stored_public_key().await.unwrap(), None);
+        store.load_encrypted_key("password").await.unwrap();
+
+        assert!(SlottedKeyFile::is_slotted(&std::fs::read(&storage_path).unwrap()));
+        assert_eq!(store.stored_public_key().await.unwrap(), Some(keys.public_key().to_hex()));
+    }
+
+    #[tokio::test]
+    async fn test_public_key_mismatch_is_rejected() {
+        let (_temp_dir, storage_path) = setup_test_storage();
+        let store = SecureKeyStore::new(storage_path.clone());
+
+        let (keys, secret_key) = test_keys();
+        let (other_keys, _) = test_keys();
+        store.save_encrypted_key(&secret_key, "password").await.unwrap();
+
+        // 別の鍵の公開鍵は保存できない
+        let err = store.save_public_key(&other_keys.public_key().to_hex()).await.unwrap_err();
+        assert!(err.is::<PublicKeyMismatch>());
+        store.save_public_key(&keys.public_key().to_bech32().unwrap()).await.unwrap();
+
+        // 公開鍵ファイルが外部から書き換えられた場合
+        std::fs::write(format!("{}.pub", storage_path), other_keys.public_key().to_hex()).unwrap();
+        assert!(store.load_public_key().await.unwrap_err().is::<PublicKeyMismatch>());
+        assert!(store.load_encrypted_key("password").await.unwrap_err().is::<PublicKeyMismatch>());
+
+        // 壊れた公開鍵ファイル
+        std::fs::write(format!("{}.pub", storage_path), "garbage").unwrap();
+        assert!(store.load_public_key().await.is_err());
+
+        // ヘッダーの公開鍵が改ざんされた場合は認証に失敗する
+        std::fs::remove_file(format!("{}.pub", storage_path)).unwrap();
+        let mut data = std::fs::read(&storage_path).unwrap();
+        data[PUBKEY_OFFSET_V3] ^= 0x01;
+        std::fs::write(&storage_path, &data).unwrap();
+        assert!(store.load_encrypted_key("password").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_stored_public_key_without_password() {
+        let (_temp_dir, storage_path) = setup_test_storage();
+        let store = SecureKeyStore::new(storage_path);
+        assert_eq!(store.stored_public_key().await.unwrap(), None);
+
+        // Amberモード: 公開鍵ファイルのみ
+        let (amber_keys, _) = test_keys();
+        store.save_public_key(&amber_keys.public_key().to_hex()).await.unwrap();
+        assert_eq!(store.stored_public_key().await.unwrap(), Some(amber_keys.public_key().to_hex()));
+        store.delete_keys().await.unwrap();
+
+        // 秘密鍵モード: ヘッダーから取得
+        let (keys, secret_key) = test_keys();
+        store.save_encrypted_key(&secret_key, "password").await.unwrap();
+        assert_eq!(store.stored_public_key().await.unwrap(), Some(keys.public_key().to_hex()));
+    }
+
+    #[tokio::test]
+    async fn test_inspect_keystore() {
+        let (_temp_dir, storage_path) = setup_test_storage();
+        let store = SecureKeyStore::new(storage_path.clone());
+
+        // 何もない状態
+        let report = store.inspect().await.unwrap();
+        assert!(!report.has_encrypted_key && !report.has_public_key && !report.valid);
+
+        // 正常な鍵ファイル
+        let (keys, secret_key) = test_keys();
+        store.save_encrypted_key(&secret_key, "password").await.unwrap();
+        let report = store.inspect().await.unwrap();
+        assert!(report.valid, "{:?}", report.problems);
+        assert!(report.problems.is_empty(), "{:?}", report.problems);
+        assert_eq!(report.format_version, Some(KEY_FILE_VERSION));
+        assert_eq!(report.kdf, Some(KdfParams::DEFAULT));
+        assert_eq!(report.public_key_hex, Some(keys.public_key().to_hex()));
+        assert_eq!(report.file_size, std::fs::metadata(&storage_path).unwrap().len());
+        #[cfg(unix)]
+        assert_eq!(report.file_mode, Some(0o600));
+
+        // 途中で切れたファイル
+        let data = std::fs::read(&storage_path).unwrap();
+        std::fs::write(&storage_path, &data[..data.len() - 10]).unwrap();
+        let report = store.inspect().await.unwrap();
+        assert!(!report.valid);
+        assert_eq!(report.public_key_hex, Some(keys.public_key().to_hex()));
+
+        std::fs::write(&storage_path, &data[..30]).unwrap();
+        let report = store.inspect().await.unwrap();
+        assert!(!report.valid && report.format_version.is_none());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_inspect_reports_loose_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (_temp_dir, storage_path) = setup_test_storage();
+        let store = SecureKeyStore::new(storage_path.clone());
+        store.save_encrypted_key(&test_keys().1, "password").await.unwrap();
+
+        std::fs::set_permissions(&storage_path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        let report = store.inspect().await.unwrap();
+        assert!(report.valid);
+        assert!(!report.permissions_ok);
+        assert_eq!(report.problems.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_memory_backend() {
+        let store = SecureKeyStore::with_storage(Arc::new(MemoryStorage::new()), "identity");
+        let (keys, secret_key) = test_keys();
+
+        store.save_encrypted_key(&secret_key, "password").await.unwrap();
+        store.save_public_key(&keys.public_key().to_hex()).await.unwrap();
+        assert!(store.has_encrypted_key().await && store.has_public_key().await);
+
+        store.change_password("password", "new_password").await.unwrap();
+        assert!(store.load_encrypted_key("password").await.unwrap_err().is::<WrongPassword>());
+        assert_eq!(store.unlock_status().await.unwrap().failed_attempts, 1);
+        assert_eq!(
+            store.load_encrypted_key("new_password").await.unwrap().expose_secret(),
+            secret_key.expose_secret()
+        );
+
+        let report = store.inspect().await.unwrap();
+        assert!(report.valid && report.permissions_ok && report.file_mode.is_none());
+
+        store.delete_keys().await.unwrap();
+        assert!(!store.has_encrypted_key().await && !store.has_public_key().await);
+    }
+
+    #[tokio::test]
+    async fn test_shared_memory_location() {
+        let (_, secret_key) = test_keys();
+        SecureKeyStore::new("memory://shared-test".to_string())
+            .save_encrypted_key(&secret_key, "password")
+            .await
+            .unwrap();
+
+        // 同じ場所文字列なら別インスタンスからも読める
+        let store = SecureKeyStore::new("memory://shared-test".to_string());
+        assert!(store.load_encrypted_key("password").await.is_ok());
+        assert!(!SecureKeyStore::new("memory://other".to_string()).has_encrypted_key().await);
+        store.delete_keys().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_key_slots() {
+        let (_temp_dir, storage_path) = setup_test_storage();
+        let store = SecureKeyStore::new(storage_path.clone());
+        let (_, secret_key) = test_keys();
+        store.save_encrypted_key(&secret_key, "password").await.unwrap();
+
+        let password = UnlockMethod::Password("password".to_string());
+        let device = UnlockMethod::DeviceKey(vec![42u8; 32]);
+
+        // 端末鍵とリカバリーコードのスロットを追加
+        assert_eq!(store.add_key_slot(&password, &device).await.unwrap(), 1);
+        let code = store.add_recovery_code_slot(&device).await.unwrap();
+        let kinds: Vec<_> = store.list_key_slots().await.unwrap().iter().map(|slot| slot.kind).collect();
+        assert_eq!(kinds, [KeySlotKind::Password, KeySlotKind::DeviceKey, KeySlotKind::RecoveryCode]);
+
+        // どのスロットでも同じ秘密鍵が取り出せる（リカバリーコードは小文字・区切りなしでも可）
+        let sloppy_code = UnlockMethod::RecoveryCode(code.expose_secret().to_lowercase().replace('-', ""));
+        for method in [&password, &device, &sloppy_code] {
+            assert_eq!(store.unlock(method).await.unwrap().expose_secret(), secret_key.expose_secret());
+        }
+        let wrong_device = UnlockMethod::DeviceKey(vec![7u8; 32]);
+        assert!(store.unlock(&wrong_device).await.unwrap_err().is::<WrongPassword>());
+
+        // パスワード変更は他のスロットに影響しない
+        store.change_password("password", "new_password").await.unwrap();
+        assert!(store.load_encrypted_key("password").await.is_err());
+        assert!(store.load_encrypted_key("new_password").await.is_ok());
+        assert!(store.unlock(&device).await.is_ok());
+
+        // パスワードのスロットを削除すると端末鍵・リカバリーコードでのみ開ける
+        store.remove_key_slot(&device, 0).await.unwrap();
+        let err = store.load_encrypted_key("new_password").await.unwrap_err();
+        assert!(!err.is::<WrongPassword>(), "{}", err);
+        store.remove_key_slot(&device, 0).await.unwrap();
+        assert!(store.remove_key_slot(&sloppy_code, 0).await.is_err());
+        assert_eq!(store.unlock(&sloppy_code).await.unwrap().expose_secret(), secret_key.expose_secret());
+    }
+
+    #[tokio::test]
+    async fn test_slot_table_is_authenticated() {
+        let (_temp_dir, storage_path) = setup_test_storage();
+        // 鍵ファイルを書き換えると失敗回数の記録も無効になるので、バックオフなしのポリシーで確認する
+        let policy = UnlockPolicy {
+            base_delay_secs: 0,
+            ..UnlockPolicy::default()
+        };
+        let store = SecureKeyStore::new(storage_path.clone()).with_unlock_policy(policy);
+        store.save_encrypted_key(&test_keys().1, "password").await.unwrap();
+        let password = UnlockMethod::Password("password".to_string());
+        store.add_key_slot(&password, &UnlockMethod::DeviceKey(vec![1u8; 16])).await.unwrap();
+
+        // 2つ目のスロットを落としたファイルはデータ鍵で認証できない
+        let mut data = std::fs::read(&storage_path).unwrap();
+        data[SLOT_COUNT_OFFSET_V3] = 1;
+        let slots_end = SLOT_COUNT_OFFSET_V3 + 1 + SLOT_LEN;
+        data.drain(slots_end..slots_end + SLOT_LEN);
+        std::fs::write(&storage_path, &data).unwrap();
+        let err = store.unlock(&password).await.unwrap_err();
+        assert!(err.to_string().contains("corrupted"), "{}", err);
+
+        // 短すぎる端末鍵は受け付けない
+        assert!(UnlockMethod::DeviceKey(vec![1u8; 8])
+            .wrapping_key(&KdfParams::DEFAULT, &[0u8; SALT_LEN])
+            .is_err());
+    }
+
+    #[test]
+    fn test_kdf_profiles_and_calibration() {
+        for profile in [KdfProfile::Interactive, KdfProfile::Moderate, KdfProfile::Paranoid] {
+            assert!(profile.params().validate().is_ok());
+        }
+        assert!(KdfProfile::Moderate.params().memory_kib > KdfProfile::Interactive.params().memory_kib);
+
+        // 目標時間が短くてもInteractiveより弱くはならない
+        let calibration = calibrate_kdf(1, None).unwrap();
+        assert_eq!(calibration.kdf, KdfParams::DEFAULT);
+
+        // メモリ上限を守る
+        let calibration = calibrate_kdf(200, Some(32 * 1024)).unwrap();
+        assert!(calibration.kdf.memory_kib <= 32 * 1024);
+        assert!(calibration.kdf.validate().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_kdf_params_are_recorded_per_slot() {
+        let (_temp_dir, storage_path) = setup_test_storage();
+        let custom = KdfParams {
+            memory_kib: 8 * 1024,
+            iterations: 3,
+            parallelism: 1,
+        };
+        let store = SecureKeyStore::new(storage_path.clone()).with_kdf_params(custom).unwrap();
+        let (_, secret_key) = test_keys();
+        store.save_encrypted_key(&secret_key, "password").await.unwrap();
+        assert_eq!(store.list_key_slots().await.unwrap()[0].kdf, Some(custom));
+
+        // 別のパラメータのストアからでも、記録された値で読み込める
+        let default_store = SecureKeyStore::new(storage_path);
+        assert!(default_store.load_encrypted_key("password").await.is_ok());
+
+        // パスワードを変更しても、置き換えたスロットのパラメータを引き継ぐ
+        default_store.change_password("password", "new password").await.unwrap();
+        assert_eq!(default_store.list_key_slots().await.unwrap()[0].kdf, Some(custom));
+
+        assert!(SecureKeyStore::new("memory://invalid-kdf".to_string())
+            .with_kdf_params(KdfParams {
+                iterations: 0,
+                ..custom
+            })
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_change_password() {
+        let (_temp_dir, storage_path) = setup_test_storage();
+        let store = SecureKeyStore::new(storage_path);
+
+        let (_, secret_key) = test_keys();
+        store.save_encrypted_key(&secret_key, "old_password").await.unwrap();
+
+        store.change_password("old_password", "new_password").await.unwrap();
+
+        // 新しいパスワードでのみ復号化できる
+        assert!(store.load_encrypted_key("old_password").await.is_err());
+        assert_eq!(
+            store.load_encrypted_key("new_password").await.unwrap().expose_secret(),
+            secret_key.expose_secret()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_change_password_failure_keeps_original() {
+        let (_temp_dir, storage_path) = setup_test_storage();
+        let store = SecureKeyStore::new(storage_path.clone());
+
+        let (_, secret_key) = test_keys();
+        store.save_encrypted_key(&secret_key, "password").await.unwrap();
+        let original = std::fs::read(&storage_path).unwrap();
+
+        // 旧パスワードが間違っている場合
+        assert!(store.change_password("wrong", "new_password").await.is_err());
+        assert_eq!(std::fs::read(&storage_path).unwrap(), original);
+
+        // 一時ファイルが作成できない場合（同名のディレクトリで塞ぐ）
+        std::fs::create_dir(format!("{}.tmp", storage_path)).unwrap();
+        assert!(store.change_password("password", "new_password").await.is_err());
+        assert_eq!(std::fs::read(&storage_path).unwrap(), original);
+        assert_eq!(
+            store.load_encrypted_key("password").await.unwrap().expose_secret(),
+            secret_key.expose_secret()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ncryptsec_export_and_import() {
+        let (_temp_dir, storage_path) = setup_test_storage();
+        let store = SecureKeyStore::new(storage_path.clone());
+
+        let keys = Keys::generate();
+        let secret_key = SecretString::from(keys.secret_key().to_secret_hex());
+        store.save_encrypted_key(&secret_key, "password").await.unwrap();
+
+        let ncryptsec = store.export_ncryptsec("password", NCRYPTSEC_MIN_LOG_N).await.unwrap();
+        assert!(ncryptsec.starts_with("ncryptsec1"));
+        assert!(store.export_ncryptsec("password", 8).await.is_err());
+
+        // 別の鍵ストアにインポート
+        let import_store = SecureKeyStore::new(format!("{}.imported", storage_path));
+        assert!(import_store.import_ncryptsec(&ncryptsec, "wrong").await.is_err());
+        let public_key_hex = import_store.import_ncryptsec(&ncryptsec, "password").await.unwrap();
+        assert_eq!(public_key_hex, keys.public_key().to_hex());
+
+        let loaded = import_store.load_encrypted_key("password").await.unwrap();
+        assert_eq!(loaded.expose_secret(), secret_key.expose_secret());
+    }
+
+    #[tokio::test]
+    async fn test_failed_unlocks_are_throttled() {
+        let (_temp_dir, storage_path) = setup_test_storage();
+        let policy = UnlockPolicy {
+            free_attempts: 2,
+            base_delay_secs: 60,
+            max_delay_secs: 600,
+            wipe_after: None,
+        };
+        let store = SecureKeyStore::new(storage_path).with_unlock_policy(policy);
+        store.save_encrypted_key(&test_keys().1, "password").await.unwrap();
+
+        // free_attemptsまでは即座に再試行できる
+        let err = store.load_encrypted_key("wrong").await.unwrap_err();
+        assert!(err.is::<WrongPassword>());
+        assert_eq!(store.unlock_status().await.unwrap().retry_after_secs, 0);
+        store.load_encrypted_key("wrong").await.unwrap_err();
+
+        // それ以降は正しいパスワードでもバックオフ中は拒否される
+        let err = store.load_encrypted_key("password").await.unwrap_err();
+        match err.downcast_ref::<UnlockError>() {
+            Some(UnlockError::Throttled { retry_after_secs }) => {
+                assert!(*retry_after_secs > 0 && *retry_after_secs <= 60)
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+        assert_eq!(store.unlock_status().await.unwrap().failed_attempts, 2);
+    }
+
+    #[tokio::test]
+    async fn test_successful_unlock_resets_attempts() {
+        let (_temp_dir, storage_path) = setup_test_storage();
+        let store = SecureKeyStore::new(storage_path).with_unlock_policy(UnlockPolicy::default());
+        store.save_encrypted_key(&test_keys().1, "password").await.unwrap();
+
+        store.load_encrypted_key("wrong").await.unwrap_err();
+        assert_eq!(store.unlock_status().await.unwrap().failed_attempts, 1);
+
+        store.load_encrypted_key("password").await.unwrap();
+        assert_eq!(store.unlock_status().await.unwrap().failed_attempts, 0);
+    }
+
+    #[tokio::test]
+    async fn test_key_is_wiped_after_policy_limit() {
+        let (_temp_dir, storage_path) = setup_test_storage();
+        let policy = UnlockPolicy {
+            free_attempts: 10,
+            wipe_after: Some(2),
+            ..UnlockPolicy::default()
+        };
+        let store = SecureKeyStore::new(storage_path).with_unlock_policy(policy);
+        store.save_encrypted_key(&test_keys().1, "password").await.unwrap();
+
+        store.load_encrypted_key("wrong").await.unwrap_err();
+        assert_eq!(store.unlock_status().await.unwrap().attempts_until_wipe, Some(1));
+
+        let err = store.load_encrypted_key("wrong").await.unwrap_err();
+        assert!(matches!(err.downcast_ref::<UnlockError>(), Some(UnlockError::Wiped { attempts: 2 })));
+        assert!(!store.has_encrypted_key().await);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_attempts_record_is_ignored() {
+        let (_temp_dir, storage_path) = setup_test_storage();
+        let attempts_storage = Arc::new(MemoryStorage::new());
+        let attempts_path = format!("{}.attempts", storage_path);
+        let store = SecureKeyStore::new(storage_path)
+            .with_attempts_storage(attempts_storage.clone())
+            .with_unlock_policy(UnlockPolicy::default());
+        store.save_encrypted_key(&test_keys().1, "password").await.unwrap();
+        store.load_encrypted_key("wrong").await.unwrap_err();
+
+        // 書き換えた記録は回数0として扱い、状態の取得では書き込まない
+        let mut record: serde_json::Value =
+            serde_json::from_slice(&attempts_storage.read(&attempts_path).await.unwrap().unwrap()).unwrap();
+        record["failed_attempts"] = serde_json::json!(5);
+        let tampered = serde_json::to_vec(&record).unwrap();
+        attempts_storage.write(&attempts_path, &tampered).await.unwrap();
+        assert_eq!(store.unlock_status().await.unwrap().failed_attempts, 0);
+        assert_eq!(attempts_storage.read(&attempts_path).await.unwrap(), Some(tampered));
+
+        assert!(attempts_storage.delete(&attempts_path).await.unwrap());
+        assert_eq!(store.unlock_status().await.unwrap().failed_attempts, 0);
+        assert_eq!(attempts_storage.read(&attempts_path).await.unwrap(), None);
+        store.load_encrypted_key("password").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_change_password_resets_attempts_without_penalty() {
+        let (_temp_dir, storage_path) = setup_test_storage();
+        let policy = UnlockPolicy {
+            free_attempts: 3,
+            wipe_after: Some(3),
+            ..UnlockPolicy::default()
+        };
+        let store = SecureKeyStore::new(storage_path).with_unlock_policy(policy);
+        store.save_encrypted_key(&test_keys().1, "password").await.unwrap();
+        store.load_encrypted_key("wrong").await.unwrap_err();
+
+        store.change_password("password", "new password").await.unwrap();
+        assert_eq!(store.unlock_status().await.unwrap().failed_attempts, 0);
+
+        let err = store.load_encrypted_key("wrong").await.unwrap_err();
+        assert!(err.is::<WrongPassword>());
+        assert_eq!(store.unlock_status().await.unwrap().attempts_until_wipe, Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_unlocks_are_serialized() {
+        let (_temp_dir, storage_path) = setup_test_storage();
+        let policy = UnlockPolicy {
+            free_attempts: 1,
+            base_delay_secs: 60,
+            max_delay_secs: 600,
+            wipe_after: None,
+        };
+        let store = Arc::new(SecureKeyStore::new(storage_path).with_unlock_policy(policy));
+        store.save_encrypted_key(&test_keys().1, "password").await.unwrap();
+
+        // 最初の失敗が記録されるまで他の試行は待たされ、バックオフで拒否される
+        let attempts = (0..4).map(|_| {
+            let store = store.clone();
+            tokio::spawn(async move { store.load_encrypted_key("wrong").await.unwrap_err() })
+        });
+        let mut wrong_passwords = 0;
+        for attempt in attempts {
+            let err = attempt.await.unwrap();
+            if err.is::<WrongPassword>() {
+                wrong_passwords += 1;
+            } else {
+                assert!(matches!(err.downcast_ref::<UnlockError>(), Some(UnlockError::Throttled { .. })));
+            }
+        }
+        assert_eq!(wrong_passwords, 1);
+        assert_eq!(store.unlock_status().await.unwrap().failed_attempts, 1);
+    }
+
     #[tokio::test]
     async fn test_public_key_storage() {
         let (_temp_dir, storage_path) = setup_test_storage();
         let store = SecureKeyStore::new(storage_path);
         
-        let public_key = "npub1test1234567890abcdefghijklmnopqrstuvwxyz";
+        let (keys, _) = test_keys();
         
-        // 保存
-        store.save_public_key(public_key).await.unwrap();
+        // 保存（npubでもhexで保存される）
+        store.save_public_key(&keys.public_key().to_bech32().unwrap()).await.unwrap();
         
         // 読み込み
         let loaded_key = store.load_public_key().await.unwrap();
-        assert_eq!(Some(public_key.to_string()), loaded_key);
+        assert_eq!(Some(keys.public_key().to_hex()), loaded_key);
+
+        // 不正な公開鍵は保存できない
+        assert!(store.save_public_key("npub1test").await.is_err());
     }
 
     #[tokio::test]
@@ -249,13 +2473,12 @@ mod tests {
         let (_temp_dir, storage_path) = setup_test_storage();
         let store = SecureKeyStore::new(storage_path.clone());
         
-        let secret_key = "nsec1test";
-        let public_key = "npub1test";
+        let (keys, secret_key) = test_keys();
         let password = "password";
         
         // 保存
-        store.save_encrypted_key(secret_key, password).await.unwrap();
-        store.save_public_key(public_key).await.unwrap();
+        store.save_encrypted_key(&secret_key, password).await.unwrap();
+        store.save_public_key(&keys.public_key().to_hex()).await.unwrap();
         
         // 存在確認
         assert!(store.has_encrypted_key().await);
@@ -279,12 +2502,13 @@ mod tests {
         assert!(!store.has_public_key().await);
         
         // 秘密鍵を保存
-        store.save_encrypted_key("nsec1test", "password").await.unwrap();
+        let (keys, secret_key) = test_keys();
+        store.save_encrypted_key(&secret_key, "password").await.unwrap();
         assert!(store.has_encrypted_key().await);
         assert!(!store.has_public_key().await);
         
         // 公開鍵を保存
-        store.save_public_key("npub1test").await.unwrap();
+        store.save_public_key(&keys.public_key().to_hex()).await.unwrap();
         assert!(store.has_encrypted_key().await);
         assert!(store.has_public_key().await);
     }
@@ -0,0 +1,437 @@
+use anyhow::{Context, Result};
+use nostr_sdk::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tokio::sync::Mutex;
+
+use crate::key_store::SecureKeyStore;
+use crate::logging::{log_debug, log_info};
+use crate::secret::SecretString;
+use crate::storage::write_atomic;
+
+/// キーリングのインデックスファイル名
+const INDEX_FILE_NAME: &str = "keyring.json";
+/// インデックスファイルのフォーマットバージョン
+const INDEX_VERSION: u32 = 1;
+/// ラベルの最大文字数
+const MAX_LABEL_CHARS: usize = 64;
+
+/// インデックスの読み込み〜書き込みを直列化するためのロック
+static KEYRING_LOCK: once_cell::sync::Lazy<Mutex<()>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(()));
+
+/// アイデンティティの鍵管理モード
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IdentityMode {
+    /// 秘密鍵をこの端末のSecureKeyStoreに暗号化保存
+    LocalKey,
+    /// 公開鍵のみ保持（署名はAmber経由）
+    Amber,
+}
+
+/// キーリングに登録されたアイデンティティ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyringIdentity {
+    /// アイデンティティID（UUID）
+    pub id: String,
+    /// 表示用ラベル（例: "Personal", "Work"）
+    pub label: String,
+    /// 鍵管理モード
+    pub mode: IdentityMode,
+    /// 公開鍵（hex形式）
+    pub public_key_hex: String,
+    /// 対応するNostrクライアントのID（NOSTR_CLIENTSのキー）
+    pub client_id: String,
+    /// 登録日時（UNIX timestamp）
+    pub created_at: i64,
+}
+
+/// keyring.json の中身
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct KeyringIndex {
+    version: u32,
+    active_id: Option<String>,
+    identities: Vec<KeyringIdentity>,
+}
+
+/// 複数アイデンティティを管理するキーリング
+///
+/// ディレクトリ構成: [dir]/keyring.json + [dir]/[id].key（LocalKeyモードのみ）
+pub struct Keyring {
+    dir: PathBuf,
+}
+
+impl Keyring {
+    /// キーリングを開く（ディレクトリがなければ作成）
+    pub async fn open(dir: String) -> Result<Self> {
+        let dir = PathBuf::from(dir);
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .context("Failed to create keyring directory")?;
+        log_debug!("🔐 Keyring opened at: {}", dir.display());
+        Ok(Self { dir })
+    }
+
+    fn index_path(&self) -> String {
+        self.dir
+            .join(INDEX_FILE_NAME)
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// アイデンティティの鍵ファイルパス
+    pub fn key_store_path(&self, identity_id: &str) -> String {
+        self.dir
+            .join(format!("{}.key", identity_id))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// 鍵ファイルのパスから、それを使っているキーリングとアイデンティティを探す
+    /// （キーリングの外にある鍵ストアの場合はNone）
+    pub async fn find_by_key_store_path(
+        key_store_path: &str,
+    ) -> Result<Option<(Self, KeyringIdentity)>> {
+        let Some(dir) = std::path::Path::new(key_store_path).parent() else {
+            return Ok(None);
+        };
+        if !tokio::fs::try_exists(dir.join(INDEX_FILE_NAME))
+            .await
+            .unwrap_or(false)
+        {
+            return Ok(None);
+        }
+
+        let keyring = Self {
+            dir: dir.to_path_buf(),
+        };
+        let identity = keyring
+            .list()
+            .await?
+            .into_iter()
+            .find(|identity| keyring.key_store_path(&identity.id) == key_store_path);
+        Ok(identity.map(|identity| (keyring, identity)))
+    }
+
+    /// アイデンティティのSecureKeyStoreを取得
+    pub fn key_store(&self, identity_id: &str) -> SecureKeyStore {
+        SecureKeyStore::new(self.key_store_path(identity_id))
+    }
+
+    async fn load_index(&self) -> Result<KeyringIndex> {
+        match tokio::fs::read(self.index_path()).await {
+            Ok(data) => {
+                let index: KeyringIndex =
+                    serde_json::from_slice(&data).context("Failed to parse keyring index")?;
+                if index.version > INDEX_VERSION {
+                    anyhow::bail!("Unsupported keyring index version: {}", index.version);
+                }
+                Ok(index)
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(KeyringIndex {
+                version: INDEX_VERSION,
+                ..Default::default()
+            }),
+            Err(e) => Err(e).context("Failed to read keyring index"),
+        }
+    }
+
+    async fn save_index(&self, index: &KeyringIndex) -> Result<()> {
+        let data = serde_json::to_vec_pretty(index)?;
+        write_atomic(&self.index_path(), &data)
+            .await
+            .context("Failed to write keyring index")
+    }
+
+    /// 登録済みアイデンティティの一覧
+    pub async fn list(&self) -> Result<Vec<KeyringIdentity>> {
+        Ok(self.load_index().await?.identities)
+    }
+
+    /// アイデンティティを取得
+    pub async fn get(&self, identity_id: &str) -> Result<KeyringIdentity> {
+        self.load_index()
+            .await?
+            .identities
+            .into_iter()
+            .find(|identity| identity.id == identity_id)
+            .with_context(|| format!("Identity not found: {}", identity_id))
+    }
+
+    /// アクティブなアイデンティティを取得
+    pub async fn active(&self) -> Result<Option<KeyringIdentity>> {
+        let index = self.load_index().await?;
+        Ok(index.active_id.as_ref().and_then(|active_id| {
+            index
+                .identities
+                .iter()
+                .find(|identity| &identity.id == active_id)
+                .cloned()
+        }))
+    }
+
+    /// 秘密鍵を持つアイデンティティを追加（鍵はパスワードで暗号化保存）
+    pub async fn add_local(
+        &self,
+        label: &str,
+        secret_key: &SecretString,
+        password: &str,
+    ) -> Result<KeyringIdentity> {
+        let keys = Keys::parse(secret_key.expose_secret())
+            .map_err(|e| anyhow::anyhow!("Invalid secret key: {}", e))?;
+        let public_key_hex = keys.public_key().to_hex();
+
+        let _guard = KEYRING_LOCK.lock().await;
+        let mut index = self.load_index().await?;
+        let identity = Self::new_identity(&index, label, IdentityMode::LocalKey, public_key_hex)?;
+
+        // 鍵ファイルを先に書き込み、成功した場合のみインデックスに登録
+        self.key_store(&identity.id)
+            .save_encrypted_key(secret_key, password)
+            .await?;
+
+        index.identities.push(identity.clone());
+        if index.active_id.is_none() {
+            index.active_id = Some(identity.id.clone());
+        }
+        self.save_index(&index).await?;
+
+        log_info!(
+            "✅ Added local identity '{}' ({})",
+            identity.label,
+            identity.id
+        );
+        Ok(identity)
+    }
+
+    /// 公開鍵のみのアイデンティティを追加（Amber使用時）
+    pub async fn add_amber(&self, label: &str, public_key: &str) -> Result<KeyringIdentity> {
+        let public_key_hex = PublicKey::parse(public_key)
+            .map_err(|e| anyhow::anyhow!("Invalid public key: {}", e))?
+            .to_hex();
+
+        let _guard = KEYRING_LOCK.lock().await;
+        let mut index = self.load_index().await?;
+        let identity = Self::new_identity(&index, label, IdentityMode::Amber, public_key_hex)?;
+
+        index.identities.push(identity.clone());
+        if index.active_id.is_none() {
+            index.active_id = Some(identity.id.clone());
+        }
+        self.save_index(&index).await?;
+
+        log_info!(
+            "✅ Added Amber identity '{}' ({})",
+            identity.label,
+            identity.id
+        );
+        Ok(identity)
+    }
+
+    /// ラベルを変更
+    pub async fn rename(&self, identity_id: &str, label: &str) -> Result<KeyringIdentity> {
+        let label = Self::validate_label(label)?;
+
+        let _guard = KEYRING_LOCK.lock().await;
+        let mut index = self.load_index().await?;
+        let identity = index
+            .identities
+            .iter_mut()
+            .find(|identity| identity.id == identity_id)
+            .with_context(|| format!("Identity not found: {}", identity_id))?;
+        identity.label = label;
+        let renamed = identity.clone();
+        self.save_index(&index).await?;
+
+        log_info!("✅ Renamed identity {} to '{}'", renamed.id, renamed.label);
+        Ok(renamed)
+    }
+
+    /// アイデンティティを削除（LocalKeyモードの場合は鍵ファイルも削除）
+    /// アクティブだった場合は残りの先頭をアクティブにする
+    pub async fn remove(&self, identity_id: &str) -> Result<()> {
+        let _guard = KEYRING_LOCK.lock().await;
+        let mut index = self.load_index().await?;
+        let position = index
+            .identities
+            .iter()
+            .position(|identity| identity.id == identity_id)
+            .with_context(|| format!("Identity not found: {}", identity_id))?;
+
+        let removed = index.identities.remove(position);
+        if index.active_id.as_deref() == Some(identity_id) {
+            index.active_id = index.identities.first().map(|identity| identity.id.clone());
+        }
+        self.save_index(&index).await?;
+
+        if removed.mode == IdentityMode::LocalKey {
+            self.key_store(&removed.id).delete_keys().await?;
+        }
+
+        log_info!("🗑️ Removed identity '{}' ({})", removed.label, removed.id);
+        Ok(())
+    }
+
+    /// アクティブなアイデンティティを切り替え
+    pub async fn set_active(&self, identity_id: &str) -> Result<KeyringIdentity> {
+        let _guard = KEYRING_LOCK.lock().await;
+        let mut index = self.load_index().await?;
+        let identity = index
+            .identities
+            .iter()
+            .find(|identity| identity.id == identity_id)
+            .cloned()
+            .with_context(|| format!("Identity not found: {}", identity_id))?;
+        index.active_id = Some(identity.id.clone());
+        self.save_index(&index).await?;
+
+        log_info!("✅ Active identity: '{}' ({})", identity.label, identity.id);
+        Ok(identity)
+    }
+
+    /// 新しいアイデンティティのエントリを作成（同じ公開鍵の重複登録は不可）
+    fn new_identity(
+        index: &KeyringIndex,
+        label: &str,
+        mode: IdentityMode,
+        public_key_hex: String,
+    ) -> Result<KeyringIdentity> {
+        let label = Self::validate_label(label)?;
+        if let Some(existing) = index
+            .identities
+            .iter()
+            .find(|identity| identity.public_key_hex == public_key_hex)
+        {
+            anyhow::bail!("Identity already exists in keyring: '{}'", existing.label);
+        }
+
+        let id = uuid::Uuid::new_v4().to_string();
+        let created_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+
+        Ok(KeyringIdentity {
+            client_id: format!("identity-{}", id),
+            id,
+            label,
+            mode,
+            public_key_hex,
+            created_at,
+        })
+    }
+
+    fn validate_label(label: &str) -> Result<String> {
+        let label = label.trim();
+        if label.is_empty() {
+            anyhow::bail!("Identity label must not be empty");
+        }
+        if label.chars().count() > MAX_LABEL_CHARS {
+            anyhow::bail!(
+                "Identity label is too long (max {} characters)",
+                MAX_LABEL_CHARS
+            );
+        }
+        Ok(label.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    async fn setup_keyring() -> (TempDir, Keyring) {
+        let temp_dir = TempDir::new().unwrap();
+        let keyring = Keyring::open(temp_dir.path().to_str().unwrap().to_string())
+            .await
+            .unwrap();
+        (temp_dir, keyring)
+    }
+
+    #[tokio::test]
+    async fn test_add_list_and_select_identities() {
+        let (_temp_dir, keyring) = setup_keyring().await;
+
+        let personal_keys = Keys::generate();
+        let personal_secret = SecretString::from(personal_keys.secret_key().to_secret_hex());
+        let personal = keyring
+            .add_local("Personal", &personal_secret, "password")
+            .await
+            .unwrap();
+
+        let work_pubkey = Keys::generate().public_key();
+        let work = keyring
+            .add_amber("Work", &work_pubkey.to_bech32().unwrap())
+            .await
+            .unwrap();
+
+        // 最初に追加したものがアクティブになる
+        assert_eq!(keyring.active().await.unwrap().unwrap().id, personal.id);
+        assert_eq!(keyring.list().await.unwrap().len(), 2);
+        assert_eq!(personal.mode, IdentityMode::LocalKey);
+        assert_eq!(work.mode, IdentityMode::Amber);
+        assert_eq!(work.public_key_hex, work_pubkey.to_hex());
+        assert_ne!(personal.client_id, work.client_id);
+
+        // 鍵はアイデンティティごとの鍵ファイルに保存されている
+        let loaded = keyring
+            .key_store(&personal.id)
+            .load_encrypted_key("password")
+            .await
+            .unwrap();
+        assert_eq!(loaded.expose_secret(), personal_secret.expose_secret());
+
+        keyring.set_active(&work.id).await.unwrap();
+        assert_eq!(keyring.active().await.unwrap().unwrap().id, work.id);
+    }
+
+    #[tokio::test]
+    async fn test_rename_and_remove_identity() {
+        let (_temp_dir, keyring) = setup_keyring().await;
+
+        let secret = SecretString::from(Keys::generate().secret_key().to_secret_hex());
+        let identity = keyring
+            .add_local("Personal", &secret, "password")
+            .await
+            .unwrap();
+
+        let renamed = keyring.rename(&identity.id, "  Home  ").await.unwrap();
+        assert_eq!(renamed.label, "Home");
+        assert!(keyring.rename(&identity.id, " ").await.is_err());
+
+        let key_store_path = keyring.key_store_path(&identity.id);
+        let (_, found) = Keyring::find_by_key_store_path(&key_store_path)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.id, identity.id);
+        assert!(Keyring::find_by_key_store_path("/nonexistent/identity.key")
+            .await
+            .unwrap()
+            .is_none());
+
+        keyring.remove(&identity.id).await.unwrap();
+        assert!(keyring.list().await.unwrap().is_empty());
+        assert!(keyring.active().await.unwrap().is_none());
+        assert!(!keyring.key_store(&identity.id).has_encrypted_key().await);
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_identity_is_rejected() {
+        let (_temp_dir, keyring) = setup_keyring().await;
+
+        let keys = Keys::generate();
+        let secret = SecretString::from(keys.secret_key().to_secret_hex());
+        keyring
+            .add_local("Personal", &secret, "password")
+            .await
+            .unwrap();
+
+        let result = keyring
+            .add_amber("Same key", &keys.public_key().to_hex())
+            .await;
+        assert!(result.is_err());
+        assert_eq!(keyring.list().await.unwrap().len(), 1);
+    }
+}
@@ -4,7 +4,20 @@ use std::sync::Arc;
 use tokio::sync::Mutex;
 
 pub mod api;
+pub mod base32;
+pub mod error;
 pub mod key_store;
+pub mod keyring;
+pub mod logging;
+pub mod mnemonic;
+pub mod proxy;
+pub mod reconnect;
+pub mod relay_status;
+pub mod secret;
+pub mod session;
+pub mod shamir;
+pub mod storage;
+pub mod transfer;
 
 /// 複数のNostrクライアントを管理（client_id -> MeisoNostrClient）
 pub static NOSTR_CLIENTS: once_cell::sync::Lazy<Arc<Mutex<HashMap<String, api::MeisoNostrClient>>>> =
@@ -0,0 +1,294 @@
+use nostr_sdk::prelude::*;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Arc, RwLock};
+
+/// ログレベル（Errorが最も重要、Traceが最も詳細）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum LogLevel {
+    Error = 1,
+    Warn = 2,
+    Info = 3,
+    Debug = 4,
+    Trace = 5,
+}
+
+impl LogLevel {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => LogLevel::Error,
+            2 => LogLevel::Warn,
+            3 => LogLevel::Info,
+            4 => LogLevel::Debug,
+            _ => LogLevel::Trace,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            LogLevel::Error => "ERROR",
+            LogLevel::Warn => "WARN",
+            LogLevel::Info => "INFO",
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Trace => "TRACE",
+        }
+    }
+}
+
+/// Flutter側（Talker）に転送するログレコード
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogRecord {
+    pub level: LogLevel,
+    /// 出力元のモジュール（例: "api", "key_store"）
+    pub target: String,
+    /// 秘密鍵・暗号文をマスク済みのメッセージ
+    pub message: String,
+    /// UNIX時刻（ミリ秒）
+    pub timestamp_ms: i64,
+}
+
+type LogSink = Arc<dyn Fn(LogRecord) + Send + Sync>;
+
+/// 出力する最大レベル（これより詳細なログは捨てる）
+static MAX_LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Info as u8);
+
+/// ログの転送先（Dart側のストリーム）
+static SINK: Lazy<RwLock<Option<LogSink>>> = Lazy::new(|| RwLock::new(None));
+
+pub fn set_max_level(level: LogLevel) {
+    MAX_LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+pub fn max_level() -> LogLevel {
+    LogLevel::from_u8(MAX_LEVEL.load(Ordering::Relaxed))
+}
+
+pub fn enabled(level: LogLevel) -> bool {
+    level <= max_level()
+}
+
+/// ログの転送先を設定（Noneで解除）。既存の転送先は置き換えられる
+pub fn set_sink(sink: Option<LogSink>) {
+    *SINK.write().unwrap() = sink;
+}
+
+/// ログを1件出力（マクロ経由で呼ばれる）
+///
+/// メッセージはマスクしてから転送先に渡す。
+/// 標準出力へはデバッグビルドのみ出力し、リリースビルドでは何も出さない
+pub fn log(level: LogLevel, module: &str, args: fmt::Arguments) {
+    if !enabled(level) {
+        return;
+    }
+    let sink = SINK.read().unwrap().clone();
+    emit(level, module, args, sink.as_deref());
+}
+
+/// レベル判定済みのログを整形して出力・転送
+fn emit(
+    level: LogLevel,
+    module: &str,
+    args: fmt::Arguments,
+    sink: Option<&(dyn Fn(LogRecord) + Send + Sync)>,
+) {
+    // クレート名を除いたモジュールパス
+    let target = module
+        .split_once("::")
+        .map_or(module, |(_, path)| path)
+        .to_string();
+    let message = redact(&args.to_string());
+
+    #[cfg(debug_assertions)]
+    {
+        if level <= LogLevel::Warn {
+            eprintln!("[{} {}] {}", level.label(), target, message);
+        } else {
+            println!("[{} {}] {}", level.label(), target, message);
+        }
+    }
+
+    if let Some(sink) = sink {
+        sink(LogRecord {
+            level,
+            target,
+            message,
+            timestamp_ms: now_millis(),
+        });
+    }
+}
+
+fn now_millis() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// これ以上の長さの英数字列は暗号文（NIP-44/NIP-04・署名など）とみなして伏せる
+const MAX_VISIBLE_TOKEN: usize = 64;
+
+/// 公開情報なのでそのまま残すbech32のプレフィックス
+const PUBLIC_BECH32_PREFIXES: &[&str] = &["npub1", "note1", "nprofile1", "nevent1", "naddr1"];
+
+/// 秘密にすべきbech32のプレフィックス
+const SECRET_BECH32_PREFIXES: &[&str] = &["nsec1", "ncryptsec1"];
+
+fn is_token_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '+' | '/' | '_' | '-')
+}
+
+/// ログメッセージ中の秘密鍵・暗号文をマスク
+///
+/// - nsec1/ncryptsec1 → プレフィックスのみ残す
+/// - 64桁のhex（秘密鍵と公開鍵・イベントIDは区別できない）→ すべて伏せる（公開鍵・イベントIDはnpub/noteで出力する）
+/// - それより長い英数字列（暗号文・署名・転送ペイロード）→ 長さのみ残す
+pub fn redact(message: &str) -> String {
+    let mut out = String::with_capacity(message.len());
+    let mut rest = message;
+
+    while let Some(start) = rest.find(is_token_char) {
+        out.push_str(&rest[..start]);
+        let token_rest = &rest[start..];
+        let mut end = token_rest
+            .find(|c: char| !is_token_char(c))
+            .unwrap_or(token_rest.len());
+        // base64のパディングも暗号文の一部として扱う
+        if end > MAX_VISIBLE_TOKEN {
+            end += token_rest[end..].len() - token_rest[end..].trim_start_matches('=').len();
+        }
+        out.push_str(&redact_token(&token_rest[..end]));
+        rest = &token_rest[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn redact_token(token: &str) -> String {
+    let lower = token.to_ascii_lowercase();
+    if let Some(prefix) = SECRET_BECH32_PREFIXES
+        .iter()
+        .find(|p| lower.starts_with(**p))
+    {
+        return format!("{}[redacted]", prefix);
+    }
+    if PUBLIC_BECH32_PREFIXES.iter().any(|p| lower.starts_with(p)) {
+        return token.to_string();
+    }
+    if token.len() == 64 && token.chars().all(|c| c.is_ascii_hexdigit()) {
+        return "[redacted hex]".to_string();
+    }
+    if token.len() > MAX_VISIBLE_TOKEN {
+        return format!("[redacted {} chars]", token.len());
+    }
+    token.to_string()
+}
+
+/// ログに出すイベントID（hexのままだとマスクされるのでnote形式にする）
+pub(crate) fn note_id(id: &EventId) -> String {
+    id.to_bech32().unwrap_or_else(|_| id.to_hex())
+}
+
+macro_rules! log_error {
+    ($($arg:tt)*) => {
+        $crate::logging::log($crate::logging::LogLevel::Error, module_path!(), format_args!($($arg)*))
+    };
+}
+
+macro_rules! log_warn {
+    ($($arg:tt)*) => {
+        $crate::logging::log($crate::logging::LogLevel::Warn, module_path!(), format_args!($($arg)*))
+    };
+}
+
+macro_rules! log_info {
+    ($($arg:tt)*) => {
+        $crate::logging::log($crate::logging::LogLevel::Info, module_path!(), format_args!($($arg)*))
+    };
+}
+
+macro_rules! log_debug {
+    ($($arg:tt)*) => {
+        $crate::logging::log($crate::logging::LogLevel::Debug, module_path!(), format_args!($($arg)*))
+    };
+}
+
+macro_rules! log_trace {
+    ($($arg:tt)*) => {
+        $crate::logging::log($crate::logging::LogLevel::Trace, module_path!(), format_args!($($arg)*))
+    };
+}
+
+pub(crate) use {log_debug, log_error, log_info, log_trace, log_warn};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_redact() {
+        let hex = "a".repeat(64);
+        assert_eq!(redact(&format!("key: {}", hex)), "key: [redacted hex]");
+        assert_eq!(
+            redact("nsec1vl029mgpspedva04g90vltkh6fvh240zqtv9k0t9af8935ke9laqsnlfe5 loaded"),
+            "nsec1[redacted] loaded"
+        );
+        assert_eq!(
+            redact("ncryptsec1qgg9947rlpvqu76pj5ecreduf9jxhselq2nae2kghhvd5g7dgjtcxfqtd67p9m0w57lspw8gsq6yphnm8623nsl8xn9j4jdzz84zm3frztj3z7s35vpzmqf6ksu8r89qk5z2zxfmu5gv8th8wclt0h4p"),
+            "ncryptsec1[redacted]"
+        );
+
+        let npub = "npub180cvv07tjdrrgpa0j7j7tmnyl2yr6yr7l8j4s3evf6u64th6gkwsyjh6w6";
+        assert_eq!(
+            redact(&format!("public key: {}", npub)),
+            format!("public key: {}", npub)
+        );
+
+        let ciphertext = format!("Ag{}==", "x".repeat(130));
+        assert_eq!(
+            redact(&format!("content={}", ciphertext)),
+            "content=[redacted 134 chars]"
+        );
+
+        assert_eq!(
+            redact("✅ Relay added: wss://relay.damus.io (3 todos)"),
+            "✅ Relay added: wss://relay.damus.io (3 todos)"
+        );
+    }
+
+    #[test]
+    fn test_level_order() {
+        assert!(LogLevel::Error < LogLevel::Warn);
+        assert!(LogLevel::Debug < LogLevel::Trace);
+        for level in [
+            LogLevel::Error,
+            LogLevel::Warn,
+            LogLevel::Info,
+            LogLevel::Debug,
+            LogLevel::Trace,
+        ] {
+            assert_eq!(LogLevel::from_u8(level as u8), level);
+        }
+    }
+
+    #[test]
+    fn test_sink_receives_redacted_record() {
+        // グローバルの転送先・レベルは他のテストのログと競合するので使わない
+        let records = Mutex::new(Vec::new());
+        let sink = |record: LogRecord| records.lock().unwrap().push(record);
+        emit(
+            LogLevel::Warn,
+            module_path!(),
+            format_args!("secret {}", "b".repeat(64)),
+            Some(&sink),
+        );
+
+        let records = records.into_inner().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].level, LogLevel::Warn);
+        assert_eq!(records[0].target, "logging::tests");
+        assert_eq!(records[0].message, "secret [redacted hex]");
+    }
+}
@@ -0,0 +1,124 @@
+use aes_gcm::aead::OsRng;
+use anyhow::Result;
+use bip39::Mnemonic;
+use nostr_sdk::prelude::*;
+use rand::RngCore;
+use zeroize::Zeroizing;
+
+use crate::secret::SecretString;
+
+/// 生成時に選択できる単語数（12語 = 128bit、24語 = 256bit）
+pub const SUPPORTED_WORD_COUNTS: [usize; 2] = [12, 24];
+
+/// ランダムなBIP-39ニーモニック（英語）を生成
+pub fn generate_mnemonic(word_count: usize) -> Result<SecretString> {
+    if !SUPPORTED_WORD_COUNTS.contains(&word_count) {
+        anyhow::bail!("Unsupported word count: {} (expected 12 or 24)", word_count);
+    }
+
+    // 3語あたり32bitのエントロピー
+    let mut entropy = Zeroizing::new(vec![0u8; word_count / 3 * 4]);
+    OsRng.fill_bytes(&mut entropy);
+
+    let mnemonic = Mnemonic::from_entropy(&entropy)
+        .map_err(|e| anyhow::anyhow!("Failed to generate mnemonic: {}", e))?;
+    Ok(SecretString::from(mnemonic.to_string()))
+}
+
+/// ニーモニックを検証（大文字・余分な空白は正規化）
+/// 不明な単語はその位置と単語をエラーに含める
+pub fn parse_mnemonic(phrase: &str) -> Result<Mnemonic> {
+    let words: Vec<String> = phrase.split_whitespace().map(str::to_lowercase).collect();
+    let normalized = Zeroizing::new(words.join(" "));
+
+    Mnemonic::parse_normalized(&normalized).map_err(|e| match e {
+        bip39::Error::UnknownWord(index) => anyhow::anyhow!(
+            "Unknown word at position {}: '{}'",
+            index + 1,
+            words.get(index).map(String::as_str).unwrap_or("")
+        ),
+        bip39::Error::BadWordCount(count) => anyhow::anyhow!(
+            "Invalid word count: {} (expected 12, 15, 18, 21 or 24)",
+            count
+        ),
+        bip39::Error::InvalidChecksum => {
+            anyhow::anyhow!("Invalid mnemonic checksum (check the spelling and order of the words)")
+        }
+        other => anyhow::anyhow!("Invalid mnemonic: {}", other),
+    })
+}
+
+/// NIP-06に従ってニーモニックから鍵を導出（m/44'/1237'/<account>'/0/0）
+pub fn derive_keys(phrase: &str, passphrase: Option<&str>, account: u32) -> Result<Keys> {
+    let mnemonic = parse_mnemonic(phrase)?;
+    let normalized = Zeroizing::new(mnemonic.to_string());
+
+    Keys::from_mnemonic_with_account(normalized.as_str(), passphrase, Some(account))
+        .map_err(|e| anyhow::anyhow!("Failed to derive keys from mnemonic: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// NIP-06のテストベクタ
+    const NIP06_MNEMONIC: &str =
+        "leader monkey parrot ring guide accident before fence cannon height naive bean";
+    const NIP06_SECRET_HEX: &str =
+        "7f7ff03d123792d6ac594bfa67bf6d0c0ab55b6b1fdb6249303fe861f1ccba9a";
+
+    #[test]
+    fn test_generate_mnemonic_word_counts() {
+        for word_count in SUPPORTED_WORD_COUNTS {
+            let phrase = generate_mnemonic(word_count).unwrap();
+            assert_eq!(phrase.expose_secret().split(' ').count(), word_count);
+            assert!(parse_mnemonic(phrase.expose_secret()).is_ok());
+        }
+        assert!(generate_mnemonic(13).is_err());
+    }
+
+    #[test]
+    fn test_derive_keys_matches_nip06_vector() {
+        let keys = derive_keys(NIP06_MNEMONIC, None, 0).unwrap();
+        assert_eq!(keys.secret_key().to_secret_hex(), NIP06_SECRET_HEX);
+
+        // 大文字・余分な空白があっても同じ鍵になる
+        let messy = format!("  {}  ", NIP06_MNEMONIC.to_uppercase().replace(' ', "   "));
+        assert_eq!(
+            derive_keys(&messy, None, 0).unwrap().public_key(),
+            keys.public_key()
+        );
+
+        // パスフレーズやアカウントが違えば別の鍵になる
+        assert_ne!(
+            derive_keys(NIP06_MNEMONIC, Some("extra"), 0)
+                .unwrap()
+                .public_key(),
+            keys.public_key()
+        );
+        assert_ne!(
+            derive_keys(NIP06_MNEMONIC, None, 1).unwrap().public_key(),
+            keys.public_key()
+        );
+    }
+
+    #[test]
+    fn test_invalid_mnemonic_errors() {
+        let unknown_word = NIP06_MNEMONIC.replace("parrot", "parrots");
+        let err = parse_mnemonic(&unknown_word).unwrap_err().to_string();
+        assert!(
+            err.contains("position 3") && err.contains("parrots"),
+            "{}",
+            err
+        );
+
+        let bad_checksum = NIP06_MNEMONIC.replace("bean", "leader");
+        let err = parse_mnemonic(&bad_checksum).unwrap_err().to_string();
+        assert!(err.contains("checksum"), "{}", err);
+
+        let err = parse_mnemonic("leader monkey parrot")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("word count"), "{}", err);
+    }
+}
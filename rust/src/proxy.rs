@@ -0,0 +1,626 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use crate::error::MeisoError;
+
+/// プロキシを経由させるリレーの範囲
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ProxyRouting {
+    /// すべてのリレーをプロキシ経由にする
+    #[default]
+    All,
+    /// `.onion`のリレーだけプロキシ経由にし、それ以外は直接接続する
+    OnionOnly,
+}
+
+/// リレーごとの接続経路
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelayRoute {
+    Direct,
+    Proxy(SocketAddr),
+    /// `.onion`なのにプロキシがない（直接接続するとDNSに漏れるので接続しない）
+    Blocked,
+}
+
+/// クライアントごとのSOCKS5プロキシ設定
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProxySettings {
+    pub addr: SocketAddr,
+    pub routing: ProxyRouting,
+    /// 厳格モード（フェイルクローズ）
+    ///
+    /// すべてのリレーをプロキシ経由にし（`routing`は無視）、
+    /// 接続の前にプロキシが応答することを確認する
+    pub strict: bool,
+}
+
+impl ProxySettings {
+    /// プロキシURLを解析して設定を作成
+    pub async fn new(proxy_url: &str, routing: ProxyRouting) -> Result<Self> {
+        Ok(Self {
+            addr: parse_proxy_url(proxy_url).await?,
+            routing,
+            strict: false,
+        })
+    }
+
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// プロキシの疎通確認（厳格モードでなければ何もしない）
+    pub async fn ensure_available(&self, timeout: Duration) -> Result<()> {
+        if self.strict {
+            check_socks5(self.addr, timeout).await?;
+        }
+        Ok(())
+    }
+}
+
+/// プロキシURLをソケットアドレスに変換
+///
+/// `socks5://127.0.0.1:9050`、`socks5h://localhost:9050`、`127.0.0.1:9050`の形式を受け付ける。
+/// SOCKS5以外（http://など）はエラー
+pub async fn parse_proxy_url(proxy_url: &str) -> Result<SocketAddr> {
+    let trimmed = proxy_url.trim();
+    let host_port = match trimmed.split_once("://") {
+        Some((scheme, rest)) => {
+            if !matches!(scheme.to_ascii_lowercase().as_str(), "socks5" | "socks5h") {
+                return Err(MeisoError::invalid_argument(format!(
+                    "unsupported proxy scheme '{}' (only socks5 is supported)",
+                    scheme
+                ))
+                .into());
+            }
+            rest
+        }
+        None => trimmed,
+    };
+    let host_port = host_port.trim_end_matches('/');
+
+    if let Ok(addr) = host_port.parse::<SocketAddr>() {
+        return Ok(addr);
+    }
+    // ホスト名の解決はランタイムのワーカーを止めないように非同期で行う
+    tokio::net::lookup_host(host_port)
+        .await
+        .with_context(|| format!("Invalid proxy address: {}", host_port))?
+        .next()
+        .ok_or_else(|| {
+            MeisoError::invalid_argument(format!("proxy address not resolved: {}", host_port))
+                .into()
+        })
+}
+
+/// リレーURLを(スキーム, ホスト, ポート)に分解
+/// ポート省略時はws=80、wss=443
+fn split_relay_url(relay_url: &str) -> (String, String, Option<u16>) {
+    let (scheme, rest) = relay_url
+        .trim()
+        .split_once("://")
+        .map_or(("", relay_url.trim()), |(scheme, rest)| (scheme, rest));
+    let scheme = scheme.to_ascii_lowercase();
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or("");
+
+    let (host, port) = if let Some(v6) = authority.strip_prefix('[') {
+        // [::1]:port
+        let (host, after) = v6.split_once(']').unwrap_or((v6, ""));
+        (host, after.strip_prefix(':'))
+    } else {
+        match authority.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        }
+    };
+    let port = match port {
+        Some(port) => port.parse().ok(),
+        None if scheme == "wss" => Some(443),
+        None if scheme == "ws" => Some(80),
+        None => None,
+    };
+    (
+        scheme,
+        host.trim_end_matches('.').to_ascii_lowercase(),
+        port,
+    )
+}
+
+/// リレーURLのホストが`.onion`か
+pub fn is_onion_relay(relay_url: &str) -> bool {
+    split_relay_url(relay_url).1.ends_with(".onion")
+}
+
+/// リレーの接続経路を決める
+///
+/// `.onion`と厳格モードはルーティング設定に関係なく常にプロキシ経由
+pub fn route_for(proxy: Option<&ProxySettings>, relay_url: &str) -> RelayRoute {
+    let onion = is_onion_relay(relay_url);
+    match proxy {
+        Some(proxy) if proxy.strict || onion || proxy.routing == ProxyRouting::All => {
+            RelayRoute::Proxy(proxy.addr)
+        }
+        Some(_) => RelayRoute::Direct,
+        None if onion => RelayRoute::Blocked,
+        None => RelayRoute::Direct,
+    }
+}
+
+/// SOCKS5のあいさつ（認証なし）を送り、プロキシが受け入れたか確認
+async fn socks5_greeting(stream: &mut TcpStream) -> std::result::Result<(), String> {
+    // VER=5, NMETHODS=1, METHOD=0（認証なし）
+    stream
+        .write_all(&[0x05, 0x01, 0x00])
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut reply = [0u8; 2];
+    stream
+        .read_exact(&mut reply)
+        .await
+        .map_err(|e| e.to_string())?;
+    match reply {
+        [0x05, 0x00] => Ok(()),
+        [0x05, 0xff] => Err("proxy requires authentication".to_string()),
+        reply => Err(format!("not a SOCKS5 proxy (reply {:02x?})", reply)),
+    }
+}
+
+/// SOCKS5のCONNECT（ホスト名のまま渡すので、名前解決はプロキシ側で行われる）
+/// IPアドレスはドメイン名として受け付けないプロキシがあるので、アドレスの種類で送る
+async fn socks5_connect(
+    stream: &mut TcpStream,
+    host: &str,
+    port: u16,
+) -> std::result::Result<(), String> {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    // VER=5, CMD=1（CONNECT）, RSV=0
+    let mut request = vec![0x05, 0x01, 0x00];
+    match host.parse::<IpAddr>() {
+        // ATYP=1（IPv4）
+        Ok(IpAddr::V4(ip)) => {
+            request.push(0x01);
+            request.extend_from_slice(&ip.octets());
+        }
+        // ATYP=4（IPv6）
+        Ok(IpAddr::V6(ip)) => {
+            request.push(0x04);
+            request.extend_from_slice(&ip.octets());
+        }
+        // ATYP=3（ドメイン名）
+        Err(_) => {
+            if host.is_empty() || host.len() > 255 {
+                return Err("invalid relay host".to_string());
+            }
+            request.extend_from_slice(&[0x03, host.len() as u8]);
+            request.extend_from_slice(host.as_bytes());
+        }
+    }
+    request.extend_from_slice(&port.to_be_bytes());
+    stream
+        .write_all(&request)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut head = [0u8; 4];
+    stream
+        .read_exact(&mut head)
+        .await
+        .map_err(|e| e.to_string())?;
+    if head[1] != 0x00 {
+        return Err(socks5_reply_message(head[1]).to_string());
+    }
+    // BND.ADDR + BND.PORTを読み捨てる
+    let addr_len = match head[3] {
+        0x01 => 4,
+        0x04 => 16,
+        0x03 => {
+            let mut len = [0u8; 1];
+            stream
+                .read_exact(&mut len)
+                .await
+                .map_err(|e| e.to_string())?;
+            len[0] as usize
+        }
+        atyp => return Err(format!("unknown address type {:#04x} in reply", atyp)),
+    };
+    let mut bound = vec![0u8; addr_len + 2];
+    stream
+        .read_exact(&mut bound)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn socks5_reply_message(code: u8) -> &'static str {
+    match code {
+        0x01 => "general SOCKS server failure",
+        0x02 => "connection not allowed by ruleset",
+        0x03 => "network unreachable",
+        0x04 => "host unreachable",
+        0x05 => "connection refused",
+        0x06 => "TTL expired",
+        0x07 => "command not supported",
+        0x08 => "address type not supported",
+        _ => "unknown SOCKS5 error",
+    }
+}
+
+/// SOCKS5のハンドシェイク（認証なし）が通るか確認
+///
+/// 接続できない・SOCKS5として応答しない場合は`ProxyUnavailable`を返す
+pub async fn check_socks5(addr: SocketAddr, timeout: Duration) -> Result<()> {
+    let handshake = async {
+        let mut stream = TcpStream::connect(addr).await.map_err(|e| e.to_string())?;
+        socks5_greeting(&mut stream).await
+    };
+
+    let reason = match tokio::time::timeout(timeout, handshake).await {
+        Ok(Ok(())) => return Ok(()),
+        Ok(Err(reason)) => reason,
+        Err(_) => format!("no response within {}s", timeout.as_secs()),
+    };
+    Err(MeisoError::ProxyUnavailable {
+        proxy: addr.to_string(),
+        reason,
+    }
+    .into())
+}
+
+/// プロキシ診断の段階
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProbeStage {
+    /// プロキシのURL解析・TCP接続
+    Connect,
+    /// SOCKS5のあいさつ
+    Handshake,
+    /// プロキシ経由でリレーへのCONNECT
+    RelayConnect,
+    /// リレーとのWebSocketアップグレード（wssはTLSを含む）
+    WebSocket,
+}
+
+/// プロキシ診断の結果（設定画面の表示用）
+///
+/// 各段階のレイテンシは診断開始からの累計（ミリ秒）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProxyProbeResult {
+    pub proxy_url: String,
+    pub test_relay: Option<String>,
+    /// SOCKS5のハンドシェイクが成功したか
+    pub handshake_ok: bool,
+    pub handshake_latency_ms: Option<u64>,
+    /// プロキシ経由でリレーのホストに接続できたか
+    pub relay_connect_ok: bool,
+    pub relay_connect_latency_ms: Option<u64>,
+    /// プロキシ経由でWebSocketアップグレードが成功したか
+    pub websocket_ok: bool,
+    pub websocket_latency_ms: Option<u64>,
+    /// 失敗した段階（すべて成功ならNone）
+    pub failed_stage: Option<ProbeStage>,
+    pub error: Option<String>,
+}
+
+impl ProxyProbeResult {
+    fn new(proxy_url: &str, test_relay: Option<&str>) -> Self {
+        Self {
+            proxy_url: proxy_url.to_string(),
+            test_relay: test_relay.map(str::to_string),
+            handshake_ok: false,
+            handshake_latency_ms: None,
+            relay_connect_ok: false,
+            relay_connect_latency_ms: None,
+            websocket_ok: false,
+            websocket_latency_ms: None,
+            failed_stage: None,
+            error: None,
+        }
+    }
+
+    fn fail(mut self, stage: ProbeStage, error: impl std::fmt::Display) -> Self {
+        self.failed_stage = Some(stage);
+        self.error = Some(error.to_string());
+        self
+    }
+}
+
+/// プロキシを診断する
+///
+/// SOCKS5のハンドシェイクを行い、test_relayが指定されていればプロキシ経由で
+/// リレーに接続してWebSocketアップグレードまで確認する。
+/// 失敗はエラーではなく結果の`failed_stage`/`error`で返す
+pub async fn probe_proxy(
+    proxy_url: &str,
+    test_relay: Option<&str>,
+    timeout: Duration,
+) -> ProxyProbeResult {
+    let result = ProxyProbeResult::new(proxy_url, test_relay);
+    let started = Instant::now();
+    let elapsed_ms = || started.elapsed().as_millis() as u64;
+
+    let addr = match parse_proxy_url(proxy_url).await {
+        Ok(addr) => addr,
+        Err(e) => return result.fail(ProbeStage::Connect, format!("{:#}", e)),
+    };
+    let mut stream = match tokio::time::timeout(timeout, TcpStream::connect(addr)).await {
+        Ok(Ok(stream)) => stream,
+        Ok(Err(e)) => return result.fail(ProbeStage::Connect, e),
+        Err(_) => return result.fail(ProbeStage::Connect, "connection timed out"),
+    };
+
+    let mut result = match tokio::time::timeout(timeout, socks5_greeting(&mut stream)).await {
+        Ok(Ok(())) => ProxyProbeResult {
+            handshake_ok: true,
+            handshake_latency_ms: Some(elapsed_ms()),
+            ..result
+        },
+        Ok(Err(e)) => return result.fail(ProbeStage::Handshake, e),
+        Err(_) => return result.fail(ProbeStage::Handshake, "handshake timed out"),
+    };
+
+    let Some(relay_url) = test_relay else {
+        return result;
+    };
+    let (scheme, host, port) = split_relay_url(relay_url);
+    let Some(port) = port.filter(|_| matches!(scheme.as_str(), "ws" | "wss")) else {
+        return result.fail(
+            ProbeStage::RelayConnect,
+            format!("invalid relay URL: {}", relay_url),
+        );
+    };
+
+    match tokio::time::timeout(timeout, socks5_connect(&mut stream, &host, port)).await {
+        Ok(Ok(())) => {
+            result.relay_connect_ok = true;
+            result.relay_connect_latency_ms = Some(elapsed_ms());
+        }
+        Ok(Err(e)) => return result.fail(ProbeStage::RelayConnect, e),
+        Err(_) => return result.fail(ProbeStage::RelayConnect, "CONNECT timed out"),
+    }
+
+    let upgrade = tokio_tungstenite::client_async_tls_with_config(relay_url, stream, None, None);
+    match tokio::time::timeout(timeout, upgrade).await {
+        Ok(Ok((mut ws, _response))) => {
+            result.websocket_ok = true;
+            result.websocket_latency_ms = Some(elapsed_ms());
+            let _ = ws.close(None).await;
+            result
+        }
+        Ok(Err(e)) => result.fail(ProbeStage::WebSocket, e),
+        Err(_) => result.fail(ProbeStage::WebSocket, "WebSocket upgrade timed out"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_parse_proxy_url() {
+        let expected: SocketAddr = "127.0.0.1:9050".parse().unwrap();
+        assert_eq!(
+            parse_proxy_url("socks5://127.0.0.1:9050").await.unwrap(),
+            expected
+        );
+        assert_eq!(
+            parse_proxy_url("socks5h://127.0.0.1:9050/").await.unwrap(),
+            expected
+        );
+        assert_eq!(parse_proxy_url("127.0.0.1:9050").await.unwrap(), expected);
+        assert_eq!(
+            parse_proxy_url("localhost:9050").await.unwrap().port(),
+            9050
+        );
+
+        let error = MeisoError::from(parse_proxy_url("http://127.0.0.1:8080").await.unwrap_err());
+        assert_eq!(error.code(), "invalid_argument");
+        assert!(parse_proxy_url("socks5://127.0.0.1").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_route_for() {
+        let onion = "ws://oxtrdevav64z64yb7x6rjg4ntzqjhedm5b5zjqulugknhzr46ny2qbad.onion";
+        let clearnet = "wss://relay.damus.io";
+        assert!(is_onion_relay(onion));
+        assert!(is_onion_relay("wss://abc.onion:443/path"));
+        assert!(!is_onion_relay(clearnet));
+        assert!(!is_onion_relay("wss://onion.example.com"));
+
+        let all = ProxySettings::new("127.0.0.1:9050", ProxyRouting::All)
+            .await
+            .unwrap();
+        let onion_only = ProxySettings {
+            routing: ProxyRouting::OnionOnly,
+            ..all
+        };
+
+        assert_eq!(route_for(Some(&all), clearnet), RelayRoute::Proxy(all.addr));
+        assert_eq!(route_for(Some(&all), onion), RelayRoute::Proxy(all.addr));
+        assert_eq!(route_for(Some(&onion_only), clearnet), RelayRoute::Direct);
+        assert_eq!(
+            route_for(Some(&onion_only), onion),
+            RelayRoute::Proxy(all.addr)
+        );
+        assert_eq!(route_for(None, clearnet), RelayRoute::Direct);
+        assert_eq!(route_for(None, onion), RelayRoute::Blocked);
+
+        // 厳格モードではOnionOnlyでも平文のリレーに直接つながない
+        let strict = onion_only.strict(true);
+        assert_eq!(
+            route_for(Some(&strict), clearnet),
+            RelayRoute::Proxy(all.addr)
+        );
+    }
+
+    #[test]
+    fn test_split_relay_url() {
+        assert_eq!(
+            split_relay_url("wss://relay.damus.io"),
+            ("wss".to_string(), "relay.damus.io".to_string(), Some(443))
+        );
+        assert_eq!(
+            split_relay_url("ws://Relay.Example.com:7777/path"),
+            (
+                "ws".to_string(),
+                "relay.example.com".to_string(),
+                Some(7777)
+            )
+        );
+        assert_eq!(split_relay_url("ws://[::1]:8080").1, "::1");
+        assert_eq!(split_relay_url("relay.example.com").2, None);
+    }
+
+    /// ハンドシェイクだけに応答するSOCKS5の代役
+    async fn spawn_fake_proxy(reply: &'static [u8]) -> SocketAddr {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut greeting = [0u8; 3];
+                if stream.read_exact(&mut greeting).await.is_ok() {
+                    let _ = stream.write_all(reply).await;
+                }
+            }
+        });
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_check_socks5() {
+        let timeout = Duration::from_secs(2);
+
+        let socks = spawn_fake_proxy(&[0x05, 0x00]).await;
+        check_socks5(socks, timeout).await.unwrap();
+        let strict = ProxySettings::new(&socks.to_string(), ProxyRouting::All)
+            .await
+            .unwrap()
+            .strict(true);
+        strict.ensure_available(timeout).await.unwrap();
+
+        let http = spawn_fake_proxy(b"HTTP/1.1 400 Bad Request\r\n\r\n").await;
+        let error = MeisoError::from(check_socks5(http, timeout).await.unwrap_err());
+        assert_eq!(error.code(), "proxy_unavailable");
+
+        // 閉じたポート（Orbotが止まっている状態）
+        let closed = {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            listener.local_addr().unwrap()
+        };
+        let error = MeisoError::from(check_socks5(closed, timeout).await.unwrap_err());
+        assert!(matches!(error, MeisoError::ProxyUnavailable { .. }));
+
+        // 厳格モードでなければ確認しない
+        let lenient = ProxySettings::new(&closed.to_string(), ProxyRouting::All)
+            .await
+            .unwrap();
+        lenient.ensure_available(timeout).await.unwrap();
+    }
+
+    /// CONNECTにも応答し、接続先のアドレスの種類・ホスト・ポートを記録するSOCKS5の代役
+    /// （トンネルの先は何も返さずに閉じる）
+    async fn spawn_connecting_proxy(
+        rep: u8,
+    ) -> (
+        SocketAddr,
+        tokio::sync::oneshot::Receiver<(u8, String, u16)>,
+    ) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut greeting = [0u8; 3];
+            stream.read_exact(&mut greeting).await.unwrap();
+            stream.write_all(&[0x05, 0x00]).await.unwrap();
+
+            let mut head = [0u8; 4];
+            stream.read_exact(&mut head).await.unwrap();
+            let host = match head[3] {
+                0x01 => {
+                    let mut ip = [0u8; 4];
+                    stream.read_exact(&mut ip).await.unwrap();
+                    std::net::Ipv4Addr::from(ip).to_string()
+                }
+                0x04 => {
+                    let mut ip = [0u8; 16];
+                    stream.read_exact(&mut ip).await.unwrap();
+                    std::net::Ipv6Addr::from(ip).to_string()
+                }
+                _ => {
+                    let mut len = [0u8; 1];
+                    stream.read_exact(&mut len).await.unwrap();
+                    let mut host = vec![0u8; len[0] as usize];
+                    stream.read_exact(&mut host).await.unwrap();
+                    String::from_utf8(host).unwrap()
+                }
+            };
+            let mut port = [0u8; 2];
+            stream.read_exact(&mut port).await.unwrap();
+            let _ = tx.send((head[3], host, u16::from_be_bytes(port)));
+
+            stream
+                .write_all(&[0x05, rep, 0x00, 0x01, 0, 0, 0, 0, 0, 0])
+                .await
+                .unwrap();
+        });
+        (addr, rx)
+    }
+
+    #[tokio::test]
+    async fn test_probe_proxy() {
+        let timeout = Duration::from_secs(2);
+
+        // ハンドシェイクのみ
+        let socks = spawn_fake_proxy(&[0x05, 0x00]).await;
+        let result = probe_proxy(&format!("socks5://{}", socks), None, timeout).await;
+        assert!(result.handshake_ok);
+        assert!(result.handshake_latency_ms.is_some());
+        assert_eq!(result.failed_stage, None);
+
+        // SOCKS5ではないサーバー
+        let http = spawn_fake_proxy(b"HTTP/1.1 400 Bad Request\r\n\r\n").await;
+        let result = probe_proxy(&http.to_string(), Some("wss://relay.damus.io"), timeout).await;
+        assert!(!result.handshake_ok);
+        assert_eq!(result.failed_stage, Some(ProbeStage::Handshake));
+
+        // CONNECTは通るがWebSocketの応答がない
+        let (socks, target) = spawn_connecting_proxy(0x00).await;
+        let onion = "ws://oxtrdevav64z64yb7x6rjg4ntzqjhedm5b5zjqulugknhzr46ny2qbad.onion";
+        let result = probe_proxy(&socks.to_string(), Some(onion), timeout).await;
+        assert!(result.handshake_ok && result.relay_connect_ok);
+        assert!(!result.websocket_ok);
+        assert_eq!(result.failed_stage, Some(ProbeStage::WebSocket));
+        // .onionのホスト名はローカルで名前解決せずプロキシに渡す
+        assert_eq!(
+            target.await.unwrap(),
+            (
+                0x03,
+                "oxtrdevav64z64yb7x6rjg4ntzqjhedm5b5zjqulugknhzr46ny2qbad.onion".to_string(),
+                80
+            )
+        );
+
+        // IPアドレスはアドレスの種類を付けて送る（IPv6は角括弧なし）
+        for (relay_url, expected) in [
+            (
+                "ws://192.168.1.10:7777",
+                (0x01, "192.168.1.10".to_string(), 7777),
+            ),
+            ("ws://[fd00::1]:7777", (0x04, "fd00::1".to_string(), 7777)),
+        ] {
+            let (socks, target) = spawn_connecting_proxy(0x00).await;
+            let result = probe_proxy(&socks.to_string(), Some(relay_url), timeout).await;
+            assert!(result.relay_connect_ok);
+            assert_eq!(target.await.unwrap(), expected);
+        }
+
+        // プロキシがCONNECTを拒否
+        let (socks, _target) = spawn_connecting_proxy(0x04).await;
+        let result = probe_proxy(&socks.to_string(), Some("wss://relay.damus.io"), timeout).await;
+        assert_eq!(result.failed_stage, Some(ProbeStage::RelayConnect));
+        assert_eq!(result.error.as_deref(), Some("host unreachable"));
+    }
+}
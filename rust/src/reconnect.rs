@@ -0,0 +1,536 @@
+use nostr_sdk::prelude::*;
+use once_cell::sync::Lazy;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock, Weak};
+use std::time::Duration;
+use tokio::sync::{watch, Mutex};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+use crate::logging::{log_debug, log_info, log_warn};
+use crate::relay_status::{RelayConnectionState, RelayErrorLog};
+
+/// アプリの表示状態（Flutter側のAppLifecycleStateから渡されるヒント）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AppLifecycle {
+    Foreground,
+    /// バックグラウンド中はOSに接続を切られるので再接続しない
+    Background,
+}
+
+/// プロセス全体の表示状態（すべての監視タスクが参照する）
+static APP_LIFECYCLE: Lazy<watch::Sender<AppLifecycle>> =
+    Lazy::new(|| watch::channel(AppLifecycle::Foreground).0);
+
+/// 表示状態を設定。フォアグラウンドに戻るとバックオフを待たずに再接続する
+pub fn set_app_lifecycle(state: AppLifecycle) {
+    APP_LIFECYCLE.send_replace(state);
+}
+
+pub fn app_lifecycle() -> AppLifecycle {
+    *APP_LIFECYCLE.borrow()
+}
+
+/// リレーの状態変化の通知
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RelayStateChange {
+    pub relay_url: String,
+    pub state: RelayConnectionState,
+    /// 連続した再接続の試行回数（接続できたら0に戻る）
+    pub reconnect_attempts: u32,
+    /// 次の再接続までの待ち時間（ミリ秒、予定がなければNone）
+    pub next_retry_ms: Option<u64>,
+    /// UNIX秒
+    pub timestamp: i64,
+}
+
+/// 再接続の間隔
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    /// 1回目の再接続までの待ち時間
+    pub base_delay: Duration,
+    /// 待ち時間の上限
+    pub max_delay: Duration,
+}
+
+/// ジッター付きの指数バックオフ
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    policy: ReconnectPolicy,
+    attempts: u32,
+}
+
+impl Backoff {
+    pub fn new(policy: ReconnectPolicy) -> Self {
+        Self {
+            policy,
+            attempts: 0,
+        }
+    }
+
+    /// 前回リセットしてからの試行回数
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    pub fn reset(&mut self) {
+        self.attempts = 0;
+    }
+
+    /// 試行を1回数えて、次の試行までの待ち時間を返す
+    ///
+    /// base·2^(試行回数-1)を上限で頭打ちにし、その半分〜全体の範囲でランダムにずらす
+    /// （同時に切れたリレーが一斉に再接続しないように）
+    pub fn next_delay<R: Rng + ?Sized>(&mut self, rng: &mut R) -> Duration {
+        let exponent = self.attempts.min(20);
+        self.attempts = self.attempts.saturating_add(1);
+
+        let cap = self
+            .policy
+            .base_delay
+            .saturating_mul(1 << exponent)
+            .min(self.policy.max_delay);
+        let cap_ms = cap.as_millis() as u64;
+        let half_ms = cap_ms / 2;
+        Duration::from_millis(half_ms + rng.gen_range(0..=cap_ms - half_ms))
+    }
+}
+
+/// リレーの状態を確認する間隔
+const CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+type StateListener = Arc<dyn Fn(RelayStateChange) + Send + Sync>;
+
+/// リレーごとの監視状態
+struct RelayTracker {
+    backoff: Backoff,
+    /// 最後に確認した状態（未確認ならNone）
+    state: Option<RelayConnectionState>,
+    /// この時刻までは再接続しない
+    retry_at: Option<Instant>,
+}
+
+struct Inner {
+    client: Client,
+    errors: RelayErrorLog,
+    policy: ReconnectPolicy,
+    trackers: Mutex<HashMap<String, RelayTracker>>,
+    listener: RwLock<Option<StateListener>>,
+    task: std::sync::Mutex<Option<JoinHandle<()>>>,
+}
+
+/// 切断されたリレーだけを再接続する監視タスク（クライアントごとに1つ）
+///
+/// 接続中のリレーには触れず、切断・停止したリレーをバックオフしながら個別に再接続する。
+/// Subscriptionはnostr-sdkがリレーごとに保持していて再接続時に張り直すので、ここでは送らない。
+/// クローンは同じタスクを共有する
+#[derive(Clone)]
+pub struct ReconnectSupervisor {
+    inner: Arc<Inner>,
+}
+
+impl std::fmt::Debug for ReconnectSupervisor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReconnectSupervisor")
+            .field("policy", &self.inner.policy)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ReconnectSupervisor {
+    /// 監視タスクを起動
+    pub fn start(client: Client, errors: RelayErrorLog, policy: ReconnectPolicy) -> Self {
+        let inner = Arc::new(Inner {
+            client,
+            errors,
+            policy,
+            trackers: Mutex::new(HashMap::new()),
+            listener: RwLock::new(None),
+            task: std::sync::Mutex::new(None),
+        });
+        let task = tokio::spawn(supervise(Arc::downgrade(&inner)));
+        *inner.task.lock().unwrap() = Some(task);
+        Self { inner }
+    }
+
+    /// 監視タスクを停止
+    pub fn stop(&self) {
+        if let Some(task) = self.inner.task.lock().unwrap().take() {
+            task.abort();
+        }
+    }
+
+    /// 状態変化の通知先を設定（Noneで解除）。既存の通知先は置き換えられる
+    pub fn set_listener(&self, listener: Option<StateListener>) {
+        *self.inner.listener.write().unwrap() = listener;
+    }
+
+    /// バックオフを待たずに、切断中のリレーの再接続を今すぐ始める（接続中のリレーはそのまま）
+    pub async fn reconnect_now(&self) {
+        self.inner.reset_backoff().await;
+        self.inner.check_relays().await;
+    }
+}
+
+async fn supervise(inner: Weak<Inner>) {
+    let mut lifecycle = APP_LIFECYCLE.subscribe();
+    let mut was_background = false;
+
+    loop {
+        if *lifecycle.borrow_and_update() == AppLifecycle::Background {
+            if !was_background {
+                log_debug!("⏸️ App in background - pausing relay reconnects");
+                was_background = true;
+            }
+            if lifecycle.changed().await.is_err() {
+                return;
+            }
+            continue;
+        }
+
+        // クライアントが破棄されていたら終了
+        let Some(supervisor) = inner.upgrade() else {
+            return;
+        };
+        if was_background {
+            log_debug!("▶️ App in foreground - reconnecting relays");
+            supervisor.reset_backoff().await;
+            was_background = false;
+        }
+        supervisor.check_relays().await;
+        drop(supervisor);
+
+        tokio::select! {
+            _ = tokio::time::sleep(CHECK_INTERVAL) => {}
+            changed = lifecycle.changed() => {
+                if changed.is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+impl Inner {
+    async fn reset_backoff(&self) {
+        for tracker in self.trackers.lock().await.values_mut() {
+            tracker.backoff.reset();
+            tracker.retry_at = None;
+        }
+    }
+
+    /// 全リレーの状態を確認し、切断中で待ち時間を過ぎたリレーの再接続を始める
+    ///
+    /// 接続はバックグラウンドで進むので、結果は次回の確認で反映される
+    async fn check_relays(&self) {
+        let relays = self.client.relays().await;
+        let due = {
+            let mut trackers = self.trackers.lock().await;
+            trackers.retain(|url, _| relays.keys().any(|relay_url| relay_url.as_str() == url));
+
+            let mut due = Vec::new();
+            for (relay_url, relay) in relays {
+                let url = relay_url.to_string();
+                let tracker = trackers.entry(url.clone()).or_insert_with(|| RelayTracker {
+                    backoff: Backoff::new(self.policy),
+                    state: None,
+                    retry_at: None,
+                });
+
+                let state = RelayConnectionState::from(relay.status());
+                self.observe(&url, tracker, state);
+
+                let is_down = matches!(
+                    state,
+                    RelayConnectionState::Initialized
+                        | RelayConnectionState::Disconnected
+                        | RelayConnectionState::Terminated
+                );
+                let now = Instant::now();
+                if !is_down || tracker.retry_at.is_some_and(|at| now < at) {
+                    continue;
+                }
+
+                let delay = tracker.backoff.next_delay(&mut rand::thread_rng());
+                tracker.retry_at = Some(now + delay);
+                log_info!(
+                    "🔄 Reconnecting to {} (attempt {}, next retry in {}ms)",
+                    url,
+                    tracker.backoff.attempts(),
+                    delay.as_millis()
+                );
+                self.emit(&url, state, tracker);
+                due.push(relay);
+            }
+            due
+        };
+
+        // 接続の開始を待つ間も他の呼び出しが監視状態を読めるように、ロックを離してから接続する
+        for relay in due {
+            relay.connect(None).await;
+        }
+    }
+
+    /// 状態の変化を記録して通知
+    fn observe(&self, url: &str, tracker: &mut RelayTracker, state: RelayConnectionState) {
+        let previous = tracker.state.replace(state);
+        if previous == Some(state) {
+            return;
+        }
+
+        match state {
+            RelayConnectionState::Connected => {
+                if tracker.backoff.attempts() > 0 {
+                    log_info!(
+                        "✅ Reconnected to {} after {} attempts",
+                        url,
+                        tracker.backoff.attempts()
+                    );
+                }
+                tracker.backoff.reset();
+                tracker.retry_at = None;
+            }
+            RelayConnectionState::Disconnected | RelayConnectionState::Terminated => {
+                if previous == Some(RelayConnectionState::Connected) {
+                    log_warn!("⚠️ Lost connection to {}", url);
+                    self.errors.record(url, "connection lost");
+                } else if tracker.backoff.attempts() > 0 {
+                    self.errors.record(url, "reconnect failed");
+                }
+            }
+            _ => {}
+        }
+        self.emit(url, state, tracker);
+    }
+
+    fn emit(&self, url: &str, state: RelayConnectionState, tracker: &RelayTracker) {
+        let listener = self.listener.read().unwrap().clone();
+        let Some(listener) = listener else {
+            return;
+        };
+        let now = Instant::now();
+        listener(RelayStateChange {
+            relay_url: url.to_string(),
+            state,
+            reconnect_attempts: tracker.backoff.attempts(),
+            next_retry_ms: tracker
+                .retry_at
+                .map(|at| at.saturating_duration_since(now).as_millis() as u64),
+            timestamp: Timestamp::now().as_u64() as i64,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio_tungstenite::tungstenite;
+
+    const TEST_POLICY: ReconnectPolicy = ReconnectPolicy {
+        base_delay: Duration::from_millis(10),
+        max_delay: Duration::from_millis(100),
+    };
+
+    /// テスト用のリレー。接続数と受け取ったREQの数を数える
+    struct MockRelay {
+        url: String,
+        connections: Arc<AtomicUsize>,
+        reqs: Arc<AtomicUsize>,
+        streams: Arc<std::sync::Mutex<Vec<std::net::TcpStream>>>,
+    }
+
+    impl MockRelay {
+        fn start() -> Self {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            let relay = Self {
+                url: format!("ws://{}", listener.local_addr().unwrap()),
+                connections: Arc::default(),
+                reqs: Arc::default(),
+                streams: Arc::default(),
+            };
+            let (connections, reqs, streams) = (
+                relay.connections.clone(),
+                relay.reqs.clone(),
+                relay.streams.clone(),
+            );
+            std::thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    streams.lock().unwrap().push(stream.try_clone().unwrap());
+                    let (connections, reqs) = (connections.clone(), reqs.clone());
+                    std::thread::spawn(move || {
+                        let Ok(mut ws) = tungstenite::accept(stream) else {
+                            return;
+                        };
+                        connections.fetch_add(1, Ordering::SeqCst);
+                        while let Ok(message) = ws.read() {
+                            if matches!(&message, tungstenite::Message::Text(text) if text.starts_with("[\"REQ\""))
+                            {
+                                reqs.fetch_add(1, Ordering::SeqCst);
+                            }
+                        }
+                    });
+                }
+            });
+            relay
+        }
+
+        fn connections(&self) -> usize {
+            self.connections.load(Ordering::SeqCst)
+        }
+
+        fn reqs(&self) -> usize {
+            self.reqs.load(Ordering::SeqCst)
+        }
+
+        /// 開いている接続をすべて切る（リレー側の障害）
+        fn drop_connections(&self) {
+            for stream in self.streams.lock().unwrap().drain(..) {
+                let _ = stream.shutdown(std::net::Shutdown::Both);
+            }
+        }
+    }
+
+    async fn wait_until(mut condition: impl FnMut() -> bool) {
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while !condition() {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .expect("condition not met in time");
+    }
+
+    async fn connected_client(relays: &[&MockRelay]) -> Client {
+        let client = Client::default();
+        for relay in relays {
+            client
+                .pool()
+                .add_relay(relay.url.as_str(), RelayOptions::new().reconnect(false))
+                .await
+                .unwrap();
+        }
+        client.connect_with_timeout(Duration::from_secs(5)).await;
+        client
+            .subscribe(vec![Filter::new().kind(Kind::TextNote)], None)
+            .await
+            .unwrap();
+        for relay in relays {
+            wait_until(|| relay.reqs() == 1).await;
+        }
+        client
+    }
+
+    async fn relay_state(client: &Client, url: &str) -> RelayConnectionState {
+        RelayConnectionState::from(client.relay(url).await.unwrap().status())
+    }
+
+    #[tokio::test]
+    async fn test_healthy_relay_is_left_alone() {
+        let relay = MockRelay::start();
+        let client = connected_client(&[&relay]).await;
+        let supervisor =
+            ReconnectSupervisor::start(client.clone(), RelayErrorLog::default(), TEST_POLICY);
+
+        for _ in 0..3 {
+            supervisor.reconnect_now().await;
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert_eq!(relay.connections(), 1);
+        assert_eq!(relay.reqs(), 1);
+        assert_eq!(
+            relay_state(&client, &relay.url).await,
+            RelayConnectionState::Connected
+        );
+        supervisor.stop();
+    }
+
+    #[tokio::test]
+    async fn test_only_down_relay_is_reconnected_and_subscribed_once() {
+        let (healthy, failing) = (MockRelay::start(), MockRelay::start());
+        let client = connected_client(&[&healthy, &failing]).await;
+        let supervisor =
+            ReconnectSupervisor::start(client.clone(), RelayErrorLog::default(), TEST_POLICY);
+        let changes = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = changes.clone();
+        supervisor.set_listener(Some(Arc::new(move |change: RelayStateChange| {
+            sink.lock().unwrap().push(change);
+        })));
+
+        // nostr-sdkは購読時刻と接続時刻を秒単位で比べて張り直すので、秒をまたいでから切る
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        failing.drop_connections();
+        let failing_url = RelayUrl::parse(&failing.url).unwrap();
+        let relay = client.relay(&failing_url).await.unwrap();
+        wait_until(|| relay.status() == RelayStatus::Terminated).await;
+
+        supervisor.reconnect_now().await;
+        wait_until(|| failing.connections() == 2 && failing.reqs() == 2).await;
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        // 再接続時のREQはnostr-sdkが送る1回だけで、接続中のリレーには何もしない
+        assert_eq!(failing.reqs(), 2);
+        assert_eq!(healthy.connections(), 1);
+        assert_eq!(healthy.reqs(), 1);
+        let healthy_url = RelayUrl::parse(&healthy.url).unwrap().to_string();
+        assert!(changes
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|change| change.relay_url == healthy_url)
+            .all(|change| change.state == RelayConnectionState::Connected));
+        supervisor.stop();
+    }
+
+    #[test]
+    fn test_backoff_grows_with_jitter_and_resets() {
+        let policy = ReconnectPolicy {
+            base_delay: Duration::from_millis(1000),
+            max_delay: Duration::from_millis(30_000),
+        };
+        let mut backoff = Backoff::new(policy);
+        let mut rng = StdRng::seed_from_u64(7);
+
+        // 上限: 1s, 2s, 4s, 8s, 16s, 30s, 30s（それぞれ半分〜全体）
+        for cap_ms in [1000, 2000, 4000, 8000, 16_000, 30_000, 30_000] {
+            let delay = backoff.next_delay(&mut rng).as_millis() as u64;
+            assert!(
+                (cap_ms / 2..=cap_ms).contains(&delay),
+                "{} not in {}/2..={}",
+                delay,
+                cap_ms,
+                cap_ms
+            );
+        }
+        assert_eq!(backoff.attempts(), 7);
+
+        backoff.reset();
+        assert_eq!(backoff.attempts(), 0);
+        assert!(backoff.next_delay(&mut rng) <= Duration::from_millis(1000));
+
+        // 試行回数が多くてもオーバーフローしない
+        for _ in 0..100 {
+            assert!(backoff.next_delay(&mut rng) <= policy.max_delay);
+        }
+    }
+
+    #[test]
+    fn test_jitter_spreads_delays() {
+        let policy = ReconnectPolicy {
+            base_delay: Duration::from_millis(1000),
+            max_delay: Duration::from_millis(60_000),
+        };
+        let mut rng = StdRng::seed_from_u64(42);
+        let delays: std::collections::HashSet<_> = (0..10)
+            .map(|_| {
+                let mut backoff = Backoff::new(policy);
+                backoff.next_delay(&mut rng)
+            })
+            .collect();
+        assert!(delays.len() > 1);
+    }
+}
@@ -0,0 +1,210 @@
+use nostr_sdk::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// リレーの接続状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RelayConnectionState {
+    /// 追加されたが接続していない
+    Initialized,
+    /// 接続待ち
+    Pending,
+    Connecting,
+    Connected,
+    /// 切断中（自動再接続の対象）
+    Disconnected,
+    /// 停止済み（再接続しない）
+    Terminated,
+}
+
+impl From<RelayStatus> for RelayConnectionState {
+    fn from(status: RelayStatus) -> Self {
+        match status {
+            RelayStatus::Initialized => RelayConnectionState::Initialized,
+            RelayStatus::Pending => RelayConnectionState::Pending,
+            RelayStatus::Connecting => RelayConnectionState::Connecting,
+            RelayStatus::Connected => RelayConnectionState::Connected,
+            RelayStatus::Disconnected => RelayConnectionState::Disconnected,
+            RelayStatus::Terminated => RelayConnectionState::Terminated,
+        }
+    }
+}
+
+/// リレーごとの状態
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RelayStatusEntry {
+    pub url: String,
+    pub state: RelayConnectionState,
+    /// 現在の接続が確立した時刻（UNIX秒、未接続ならNone）
+    pub connected_since: Option<i64>,
+    /// 現在の接続の継続時間（秒、未接続ならNone）
+    pub connected_secs: Option<u64>,
+    /// 最後に記録されたエラー（接続・送信の失敗など）
+    pub last_error: Option<String>,
+    /// last_errorの時刻（UNIX秒）
+    pub last_error_at: Option<i64>,
+    /// 接続を試みた回数（初回を含む）
+    pub connection_attempts: u64,
+    /// 接続に成功した回数
+    pub successful_connections: u64,
+    /// 再接続を試みた回数（初回を除く）
+    pub reconnect_attempts: u64,
+    /// 計測されたラウンドトリップ時間（ミリ秒、未計測ならNone）
+    pub latency_ms: Option<u64>,
+}
+
+/// 全リレーの状態レポート
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RelayStatusReport {
+    pub relays: Vec<RelayStatusEntry>,
+    pub total_count: u32,
+    /// 実際に接続しているリレー数
+    pub connected_count: u32,
+    /// 1つ以上のリレーに接続しているか
+    pub online: bool,
+}
+
+impl RelayStatusReport {
+    /// リレーごとの状態からレポートを作成（URL順）
+    pub fn new(mut relays: Vec<RelayStatusEntry>) -> Self {
+        relays.sort_by(|a, b| a.url.cmp(&b.url));
+        let connected_count = relays
+            .iter()
+            .filter(|relay| relay.state == RelayConnectionState::Connected)
+            .count() as u32;
+        Self {
+            total_count: relays.len() as u32,
+            connected_count,
+            online: connected_count > 0,
+            relays,
+        }
+    }
+}
+
+/// 記録されたエラー
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelayError {
+    pub message: String,
+    pub at: i64,
+}
+
+/// リレーごとの最後のエラーを記録する（nostr-sdkのプールは保持しないため）
+///
+/// クローンは同じ記録を共有する
+#[derive(Debug, Clone, Default)]
+pub struct RelayErrorLog {
+    errors: Arc<Mutex<HashMap<String, RelayError>>>,
+}
+
+impl RelayErrorLog {
+    pub fn record(&self, relay_url: &str, message: impl std::fmt::Display) {
+        self.errors.lock().unwrap().insert(
+            relay_url.to_string(),
+            RelayError {
+                message: message.to_string(),
+                at: Timestamp::now().as_u64() as i64,
+            },
+        );
+    }
+
+    pub fn get(&self, relay_url: &str) -> Option<RelayError> {
+        self.errors.lock().unwrap().get(relay_url).cloned()
+    }
+
+    /// リレーを外したときに記録も消す
+    pub fn remove(&self, relay_url: &str) {
+        self.errors.lock().unwrap().remove(relay_url);
+    }
+}
+
+/// プール内のリレーの状態を集める
+pub async fn collect_relay_status(client: &Client, errors: &RelayErrorLog) -> RelayStatusReport {
+    let now = Timestamp::now().as_u64() as i64;
+    let mut entries = Vec::new();
+
+    for (url, relay) in client.relays().await {
+        let url = url.to_string();
+        let state = RelayConnectionState::from(relay.status());
+        let stats = relay.stats();
+
+        let connected_since = match stats.connected_at().as_u64() as i64 {
+            at if state == RelayConnectionState::Connected && at > 0 => Some(at),
+            _ => None,
+        };
+        let attempts = stats.attempts() as u64;
+        let last_error = errors.get(&url);
+
+        entries.push(RelayStatusEntry {
+            connected_since,
+            connected_secs: connected_since.map(|at| now.saturating_sub(at).max(0) as u64),
+            last_error: last_error.as_ref().map(|e| e.message.clone()),
+            last_error_at: last_error.map(|e| e.at),
+            connection_attempts: attempts,
+            successful_connections: stats.success() as u64,
+            reconnect_attempts: attempts.saturating_sub(1),
+            latency_ms: stats.latency().map(|latency| latency.as_millis() as u64),
+            state,
+            url,
+        });
+    }
+
+    RelayStatusReport::new(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(url: &str, state: RelayConnectionState) -> RelayStatusEntry {
+        RelayStatusEntry {
+            url: url.to_string(),
+            state,
+            connected_since: None,
+            connected_secs: None,
+            last_error: None,
+            last_error_at: None,
+            connection_attempts: 1,
+            successful_connections: 0,
+            reconnect_attempts: 0,
+            latency_ms: None,
+        }
+    }
+
+    #[test]
+    fn test_report_counts_only_connected_relays() {
+        let offline = RelayStatusReport::new(vec![
+            entry("wss://b.example", RelayConnectionState::Disconnected),
+            entry("wss://a.example", RelayConnectionState::Connecting),
+        ]);
+        assert_eq!(offline.total_count, 2);
+        assert_eq!(offline.connected_count, 0);
+        assert!(!offline.online);
+        assert_eq!(offline.relays[0].url, "wss://a.example");
+
+        let online = RelayStatusReport::new(vec![
+            entry("wss://a.example", RelayConnectionState::Connected),
+            entry("wss://b.example", RelayConnectionState::Terminated),
+        ]);
+        assert_eq!(online.connected_count, 1);
+        assert!(online.online);
+
+        assert!(!RelayStatusReport::new(Vec::new()).online);
+    }
+
+    #[test]
+    fn test_error_log_is_shared_between_clones() {
+        let log = RelayErrorLog::default();
+        let clone = log.clone();
+        clone.record("wss://a.example", "connection refused");
+
+        assert_eq!(
+            log.get("wss://a.example").unwrap().message,
+            "connection refused"
+        );
+        assert!(log.get("wss://b.example").is_none());
+
+        log.remove("wss://a.example");
+        assert!(clone.get("wss://a.example").is_none());
+    }
+}
@@ -0,0 +1,67 @@
+use std::fmt;
+use zeroize::Zeroizing;
+
+/// 秘密情報（nsec / hex秘密鍵など）を保持する文字列
+///
+/// Drop時にメモリをゼロ埋めする。Debug/Display出力は常に伏せ字になるため、
+/// ログやエラーメッセージに秘密鍵が混入しない
+#[derive(Clone, Default)]
+pub struct SecretString(Zeroizing<String>);
+
+impl SecretString {
+    /// 文字列を所有権ごと受け取ってラップ
+    pub fn new(value: String) -> Self {
+        Self(Zeroizing::new(value))
+    }
+
+    /// 中身を参照する（呼び出し側で複製しないこと）
+    pub fn expose_secret(&self) -> &str {
+        self.0.as_str()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(value: String) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<&str> for SecretString {
+    fn from(value: &str) -> Self {
+        Self::new(value.to_string())
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretString([REDACTED])")
+    }
+}
+
+impl fmt::Display for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[REDACTED]")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debug_and_display_are_redacted() {
+        let secret = SecretString::from("nsec1secretvalue");
+
+        assert_eq!(format!("{:?}", secret), "SecretString([REDACTED])");
+        assert_eq!(format!("{}", secret), "[REDACTED]");
+        assert_eq!(
+            format!("{:?}", Some(secret.clone())),
+            "Some(SecretString([REDACTED]))"
+        );
+        assert_eq!(secret.expose_secret(), "nsec1secretvalue");
+    }
+}
@@ -0,0 +1,187 @@
+use once_cell::sync::Lazy;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+use crate::logging::log_info;
+
+/// セッションがロックされている（署名・復号の前に`unlock_session`が必要）
+#[derive(Debug, thiserror::Error)]
+#[error("Session is locked. Unlock with your password to sign or decrypt")]
+pub struct SessionLocked;
+
+/// 無操作時間を計測するタイマー
+#[derive(Debug)]
+pub struct IdleTimer {
+    timeout: Option<Duration>,
+    last_activity: Instant,
+}
+
+impl Default for IdleTimer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IdleTimer {
+    pub fn new() -> Self {
+        Self {
+            timeout: None,
+            last_activity: Instant::now(),
+        }
+    }
+
+    /// タイムアウトを設定（None = 自動ロック無効）。計測はリセットされる
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+        self.touch();
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// 操作があったことを記録
+    pub fn touch(&mut self) {
+        self.last_activity = Instant::now();
+    }
+
+    /// ロックまでの残り時間（自動ロック無効ならNone、期限切れならゼロ）
+    pub fn remaining(&self, now: Instant) -> Option<Duration> {
+        let timeout = self.timeout?;
+        let idle = now.saturating_duration_since(self.last_activity);
+        Some(timeout.saturating_sub(idle))
+    }
+}
+
+/// プロセス全体のアイドルタイマー
+static IDLE_TIMER: Lazy<Arc<Mutex<IdleTimer>>> =
+    Lazy::new(|| Arc::new(Mutex::new(IdleTimer::new())));
+
+/// 実行中の監視タスク
+static WATCHER: Lazy<Mutex<Option<tokio::task::JoinHandle<()>>>> = Lazy::new(|| Mutex::new(None));
+
+/// ユーザー操作を記録（自動ロックまでの時間が延長される）
+///
+/// リレーからの定期同期などバックグラウンド処理では呼ばないこと
+pub fn record_activity() {
+    IDLE_TIMER.lock().unwrap().touch();
+}
+
+/// 現在の自動ロックのタイムアウト
+pub fn idle_timeout() -> Option<Duration> {
+    IDLE_TIMER.lock().unwrap().timeout()
+}
+
+/// 自動ロックのタイムアウトを設定し、監視タスクを起動し直す
+///
+/// 無操作のままタイムアウトを過ぎると`on_expire`が呼ばれる。Noneで無効化
+pub fn set_idle_timeout<F, Fut>(
+    runtime: &tokio::runtime::Handle,
+    timeout: Option<Duration>,
+    on_expire: F,
+) where
+    F: Fn() -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    IDLE_TIMER.lock().unwrap().set_timeout(timeout);
+
+    let mut watcher = WATCHER.lock().unwrap();
+    if let Some(handle) = watcher.take() {
+        handle.abort();
+    }
+    if timeout.is_none() {
+        log_info!("🔓 Auto-lock disabled");
+        return;
+    }
+
+    log_info!("⏲️ Auto-lock after {:?} of inactivity", timeout.unwrap());
+    *watcher = Some(runtime.spawn(watch_idle(IDLE_TIMER.clone(), on_expire)));
+}
+
+/// タイマーを監視し、無操作のままタイムアウトを過ぎたら`on_expire`を呼ぶ
+/// タイムアウトが無効になったら終了する
+async fn watch_idle<F, Fut>(timer: Arc<Mutex<IdleTimer>>, on_expire: F)
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = ()>,
+{
+    loop {
+        let remaining = timer.lock().unwrap().remaining(Instant::now());
+        match remaining {
+            None => return,
+            Some(remaining) if remaining.is_zero() => {
+                log_info!("🔒 Idle timeout reached - locking session");
+                on_expire().await;
+                // ロック後は次の操作まで再ロックしない
+                timer.lock().unwrap().touch();
+            }
+            Some(remaining) => tokio::time::sleep(remaining).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_idle_timer_remaining() {
+        let mut timer = IdleTimer::new();
+        assert_eq!(timer.remaining(Instant::now()), None);
+
+        timer.set_timeout(Some(Duration::from_secs(60)));
+        let start = timer.last_activity;
+        assert_eq!(timer.remaining(start), Some(Duration::from_secs(60)));
+        assert_eq!(
+            timer.remaining(start + Duration::from_secs(45)),
+            Some(Duration::from_secs(15))
+        );
+        assert_eq!(
+            timer.remaining(start + Duration::from_secs(90)),
+            Some(Duration::ZERO)
+        );
+
+        timer.touch();
+        assert!(timer.remaining(Instant::now()).unwrap() > Duration::from_secs(59));
+
+        timer.set_timeout(None);
+        assert_eq!(timer.remaining(Instant::now()), None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_idle_watcher_fires() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let timer = Arc::new(Mutex::new(IdleTimer::new()));
+        timer
+            .lock()
+            .unwrap()
+            .set_timeout(Some(Duration::from_secs(60)));
+        let fired = Arc::new(AtomicUsize::new(0));
+        let counter = fired.clone();
+        let watcher = tokio::spawn(watch_idle(timer.clone(), move || {
+            let counter = counter.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+        }));
+
+        // 操作があれば計測し直す
+        tokio::time::sleep(Duration::from_secs(59)).await;
+        assert_eq!(fired.load(Ordering::SeqCst), 0);
+        timer.lock().unwrap().touch();
+        tokio::time::sleep(Duration::from_secs(59)).await;
+        assert_eq!(fired.load(Ordering::SeqCst), 0);
+
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        assert_eq!(fired.load(Ordering::SeqCst), 1);
+
+        // 無効にすると監視を終える
+        timer.lock().unwrap().set_timeout(None);
+        tokio::time::sleep(Duration::from_secs(120)).await;
+        assert!(watcher.is_finished());
+        assert_eq!(fired.load(Ordering::SeqCst), 1);
+    }
+}
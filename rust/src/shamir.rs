@@ -0,0 +1,394 @@
+use aes_gcm::aead::OsRng;
+use anyhow::Result;
+use nostr_sdk::prelude::*;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+use crate::logging::log_info;
+use crate::secret::SecretString;
+
+/// シェア文字列のプレフィックス
+const SHARE_PREFIX: &str = "meiso-share-";
+/// シェアのフォーマットバージョン
+const SHARE_VERSION: u8 = 1;
+/// 秘密鍵の長さ
+const SECRET_LEN: usize = 32;
+/// 秘密鍵のフィンガープリント・チェックサムの長さ
+const FINGERPRINT_LEN: usize = 4;
+const CHECKSUM_LEN: usize = 4;
+/// version(1) + set_id(4) + threshold(1) + index(1) + fingerprint(4) + data(32)
+const SHARE_BODY_LEN: usize = 1 + 4 + 1 + 1 + FINGERPRINT_LEN + SECRET_LEN;
+/// 分割できるシェア数の上限
+pub const MAX_SHARES: u8 = 16;
+
+/// シェアの情報（秘密を含まない、復元画面の進捗表示用）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShareInfo {
+    /// 同じ分割で作られたシェアに共通のID（hex）
+    pub set_id: String,
+    /// シェア番号（1始まり）
+    pub index: u8,
+    /// 復元に必要なシェア数
+    pub threshold: u8,
+}
+
+/// デコードしたシェア
+struct Share {
+    set_id: [u8; 4],
+    threshold: u8,
+    index: u8,
+    fingerprint: [u8; FINGERPRINT_LEN],
+    data: Zeroizing<[u8; SECRET_LEN]>,
+}
+
+impl Share {
+    fn encode(&self) -> SecretString {
+        let mut body = Zeroizing::new(Vec::with_capacity(SHARE_BODY_LEN + CHECKSUM_LEN));
+        body.push(SHARE_VERSION);
+        body.extend_from_slice(&self.set_id);
+        body.push(self.threshold);
+        body.push(self.index);
+        body.extend_from_slice(&self.fingerprint);
+        body.extend_from_slice(self.data.as_slice());
+        let checksum = checksum(&body);
+        body.extend_from_slice(&checksum);
+
+        let encoded = Zeroizing::new(crate::base32::encode(&body));
+        SecretString::from(format!(
+            "{}{}",
+            SHARE_PREFIX,
+            crate::base32::group(&encoded, 4)
+        ))
+    }
+
+    fn decode(text: &str) -> Result<Self> {
+        let text = text.trim();
+        let encoded = text
+            .get(..SHARE_PREFIX.len())
+            .filter(|prefix| prefix.eq_ignore_ascii_case(SHARE_PREFIX))
+            .map(|_| &text[SHARE_PREFIX.len()..])
+            .ok_or_else(|| {
+                anyhow::anyhow!("Not a Meiso backup share (expected '{}...')", SHARE_PREFIX)
+            })?;
+        let body = Zeroizing::new(
+            crate::base32::decode(encoded).map_err(|e| anyhow::anyhow!("Invalid share: {}", e))?,
+        );
+        if body.len() != SHARE_BODY_LEN + CHECKSUM_LEN {
+            anyhow::bail!("Invalid share length (missing or extra characters?)");
+        }
+        let (body, stored_checksum) = body.split_at(SHARE_BODY_LEN);
+        if checksum(body) != stored_checksum {
+            anyhow::bail!("Share checksum mismatch (check for typos)");
+        }
+        if body[0] != SHARE_VERSION {
+            anyhow::bail!("Unsupported share version: {}", body[0]);
+        }
+
+        let mut set_id = [0u8; 4];
+        set_id.copy_from_slice(&body[1..5]);
+        let threshold = body[5];
+        let index = body[6];
+        if threshold < 2 || index == 0 {
+            anyhow::bail!("Invalid share parameters");
+        }
+        let mut fingerprint = [0u8; FINGERPRINT_LEN];
+        fingerprint.copy_from_slice(&body[7..7 + FINGERPRINT_LEN]);
+        let mut data = Zeroizing::new([0u8; SECRET_LEN]);
+        data.copy_from_slice(&body[7 + FINGERPRINT_LEN..]);
+
+        Ok(Self {
+            set_id,
+            threshold,
+            index,
+            fingerprint,
+            data,
+        })
+    }
+
+    fn info(&self) -> ShareInfo {
+        ShareInfo {
+            set_id: self.set_id.iter().map(|b| format!("{:02x}", b)).collect(),
+            index: self.index,
+            threshold: self.threshold,
+        }
+    }
+}
+
+fn checksum(data: &[u8]) -> [u8; CHECKSUM_LEN] {
+    let digest = Sha256::digest(data);
+    let mut checksum = [0u8; CHECKSUM_LEN];
+    checksum.copy_from_slice(&digest[..CHECKSUM_LEN]);
+    checksum
+}
+
+/// 復元した秘密鍵が元の鍵と一致するか確認するためのフィンガープリント
+fn fingerprint(secret: &[u8; SECRET_LEN]) -> [u8; FINGERPRINT_LEN] {
+    let mut hasher = Sha256::new();
+    hasher.update(b"meiso-shamir-fingerprint");
+    hasher.update(secret);
+    let mut fingerprint = [0u8; FINGERPRINT_LEN];
+    fingerprint.copy_from_slice(&hasher.finalize()[..FINGERPRINT_LEN]);
+    fingerprint
+}
+
+/// GF(256)の乗算（AESと同じ既約多項式 x^8 + x^4 + x^3 + x + 1）
+/// 秘密に依存する分岐・テーブル参照を避ける
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0u8;
+    for _ in 0..8 {
+        product ^= a & 0u8.wrapping_sub(b & 1);
+        let carry = 0u8.wrapping_sub(a >> 7);
+        a = (a << 1) ^ (0x1b & carry);
+        b >>= 1;
+    }
+    product
+}
+
+/// GF(256)の逆元（a^254）
+fn gf_inv(a: u8) -> u8 {
+    let mut result = 1u8;
+    let mut base = a;
+    let mut exponent = 254u8;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = gf_mul(result, base);
+        }
+        base = gf_mul(base, base);
+        exponent >>= 1;
+    }
+    result
+}
+
+/// 秘密鍵（hex / nsec）を32バイトに変換
+pub(crate) fn secret_key_bytes(secret_key: &SecretString) -> Result<Zeroizing<[u8; SECRET_LEN]>> {
+    let keys = Keys::parse(secret_key.expose_secret())
+        .map_err(|e| anyhow::anyhow!("Invalid secret key: {}", e))?;
+    let hex = Zeroizing::new(keys.secret_key().to_secret_hex());
+    let mut bytes = Zeroizing::new([0u8; SECRET_LEN]);
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)?;
+    }
+    Ok(bytes)
+}
+
+/// 秘密鍵をシェアに分割（threshold個のシェアで復元可能）
+///
+/// 各シェアは`meiso-share-XXXX-XXXX-…`形式の書き写せる文字列で、
+/// チェックサムにより書き間違いを検出できる
+pub fn split_secret_key(
+    secret_key: &SecretString,
+    threshold: u8,
+    share_count: u8,
+) -> Result<Vec<SecretString>> {
+    if threshold < 2 || threshold > share_count || share_count > MAX_SHARES {
+        anyhow::bail!(
+            "Invalid share parameters: need 2 <= threshold ({}) <= shares ({}) <= {}",
+            threshold,
+            share_count,
+            MAX_SHARES
+        );
+    }
+
+    let secret = secret_key_bytes(secret_key)?;
+    let mut set_id = [0u8; 4];
+    OsRng.fill_bytes(&mut set_id);
+    let fingerprint = fingerprint(&secret);
+
+    // バイトごとに f(0) = secret となる (threshold - 1) 次の多項式を作る
+    let mut coefficients = Zeroizing::new(vec![[0u8; SECRET_LEN]; threshold as usize - 1]);
+    for coefficient in coefficients.iter_mut() {
+        OsRng.fill_bytes(coefficient);
+    }
+
+    let shares = (1..=share_count)
+        .map(|x| {
+            let mut data = Zeroizing::new([0u8; SECRET_LEN]);
+            for (i, byte) in data.iter_mut().enumerate() {
+                // ホーナー法: ((c_{k-1} x + c_{k-2}) x + ... ) x + secret
+                let mut y = 0u8;
+                for coefficient in coefficients.iter().rev() {
+                    y = gf_mul(y, x) ^ coefficient[i];
+                }
+                *byte = gf_mul(y, x) ^ secret[i];
+            }
+            Share {
+                set_id,
+                threshold,
+                index: x,
+                fingerprint,
+                data,
+            }
+            .encode()
+        })
+        .collect();
+
+    log_info!(
+        "✅ Secret key split into {} shares (threshold {})",
+        share_count,
+        threshold
+    );
+    Ok(shares)
+}
+
+/// シェアの情報を取得（チェックサムも検証する）
+pub fn inspect_share(share: &str) -> Result<ShareInfo> {
+    Ok(Share::decode(share)?.info())
+}
+
+/// シェアから秘密鍵（hex）を復元
+pub fn combine_shares(shares: &[String]) -> Result<SecretString> {
+    let mut decoded: Vec<Share> = Vec::new();
+    for (position, text) in shares.iter().enumerate() {
+        let share =
+            Share::decode(text).map_err(|e| anyhow::anyhow!("Share {}: {}", position + 1, e))?;
+        if let Some(first) = decoded.first() {
+            if share.set_id != first.set_id || share.threshold != first.threshold {
+                anyhow::bail!("Share {} belongs to a different backup set", position + 1);
+            }
+        }
+        match decoded
+            .iter()
+            .find(|existing| existing.index == share.index)
+        {
+            Some(existing) if existing.data.as_slice() != share.data.as_slice() => {
+                anyhow::bail!(
+                    "Share #{} was entered twice with different contents",
+                    share.index
+                )
+            }
+            Some(_) => continue,
+            None => decoded.push(share),
+        }
+    }
+
+    let Some(first) = decoded.first() else {
+        anyhow::bail!("No shares provided");
+    };
+    let threshold = first.threshold as usize;
+    if decoded.len() < threshold {
+        anyhow::bail!(
+            "{} of {} required shares provided",
+            decoded.len(),
+            threshold
+        );
+    }
+    let points = &decoded[..threshold];
+
+    // ラグランジュ補間で f(0) を求める
+    let mut secret = Zeroizing::new([0u8; SECRET_LEN]);
+    for (j, share_j) in points.iter().enumerate() {
+        let mut basis = 1u8;
+        for (m, share_m) in points.iter().enumerate() {
+            if m != j {
+                // l_j(0) = Π x_m / (x_m - x_j)  （GF(2^8)では減算はXOR）
+                basis = gf_mul(
+                    basis,
+                    gf_mul(share_m.index, gf_inv(share_m.index ^ share_j.index)),
+                );
+            }
+        }
+        for (byte, share_byte) in secret.iter_mut().zip(share_j.data.iter()) {
+            *byte ^= gf_mul(basis, *share_byte);
+        }
+    }
+
+    if fingerprint(&secret) != first.fingerprint {
+        anyhow::bail!("Recovered key does not match the backup fingerprint (corrupted share?)");
+    }
+
+    let secret_hex: String = secret.iter().map(|b| format!("{:02x}", b)).collect();
+    let secret_key = SecretString::from(secret_hex);
+    Keys::parse(secret_key.expose_secret())
+        .map_err(|e| anyhow::anyhow!("Recovered key is invalid: {}", e))?;
+
+    log_info!("✅ Secret key recovered from {} shares", threshold);
+    Ok(secret_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_secret() -> (Keys, SecretString) {
+        let keys = Keys::generate();
+        let secret_key = SecretString::from(keys.secret_key().to_secret_hex());
+        (keys, secret_key)
+    }
+
+    #[test]
+    fn test_gf256_inverse() {
+        for a in 1..=255u8 {
+            assert_eq!(gf_mul(a, gf_inv(a)), 1, "a = {}", a);
+        }
+    }
+
+    #[test]
+    fn test_split_and_combine_any_subset() {
+        let (keys, secret_key) = test_secret();
+        let shares: Vec<String> = split_secret_key(&secret_key, 3, 5)
+            .unwrap()
+            .iter()
+            .map(|share| share.expose_secret().to_string())
+            .collect();
+        assert!(shares.iter().all(|share| share.starts_with(SHARE_PREFIX)));
+
+        for subset in [[0, 1, 2], [4, 2, 0], [1, 3, 4]] {
+            let selected: Vec<String> = subset.iter().map(|&i| shares[i].clone()).collect();
+            let recovered = combine_shares(&selected).unwrap();
+            assert_eq!(recovered.expose_secret(), keys.secret_key().to_secret_hex());
+        }
+
+        // 閾値未満
+        let err = combine_shares(&shares[..2]).unwrap_err().to_string();
+        assert!(err.contains("2 of 3"), "{}", err);
+        // 同じシェアの重複は1つとして数える
+        assert!(
+            combine_shares(&[shares[0].clone(), shares[0].clone(), shares[1].clone()]).is_err()
+        );
+
+        let info = inspect_share(&shares[3]).unwrap();
+        assert_eq!((info.index, info.threshold), (4, 3));
+    }
+
+    #[test]
+    fn test_share_typos_and_mixed_sets_are_detected() {
+        let (_, secret_key) = test_secret();
+        let shares = split_secret_key(&secret_key, 2, 3).unwrap();
+        let other = split_secret_key(&secret_key, 2, 3).unwrap();
+
+        // 1文字の書き間違い
+        let share = shares[0].expose_secret();
+        let position = share.len() - 6;
+        let replacement = if &share[position..position + 1] == "A" {
+            "B"
+        } else {
+            "A"
+        };
+        let typo = format!(
+            "{}{}{}",
+            &share[..position],
+            replacement,
+            &share[position + 1..]
+        );
+        assert!(inspect_share(&typo).is_err());
+
+        // 小文字・区切りの違いは許容
+        let relaxed = share.to_lowercase().replace('-', " ").replacen(' ', "-", 2);
+        assert!(inspect_share(&relaxed).is_ok(), "{}", relaxed);
+
+        // 別の分割のシェアは混ぜられない
+        let mixed = [
+            shares[0].expose_secret().to_string(),
+            other[1].expose_secret().to_string(),
+        ];
+        assert!(combine_shares(&mixed)
+            .unwrap_err()
+            .to_string()
+            .contains("different backup set"));
+
+        assert!(split_secret_key(&secret_key, 1, 3).is_err());
+        assert!(split_secret_key(&secret_key, 4, 3).is_err());
+    }
+}
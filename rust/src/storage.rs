@@ -0,0 +1,367 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, RwLock};
+use zeroize::Zeroizing;
+
+use crate::logging::log_debug;
+
+/// インメモリストレージを指定する場所文字列のプレフィックス（`memory://<name>`）
+pub const MEMORY_SCHEME: &str = "memory://";
+/// Flutter側のコールバックストレージを指定する場所文字列のプレフィックス（`callback://<backend_id>/<name>`）
+pub const CALLBACK_SCHEME: &str = "callback://";
+
+/// 鍵データ（暗号化済みblob）の保存先
+///
+/// `name`はバックエンド内でのキー。ファイルバックエンドではファイルパスになる
+#[async_trait]
+pub trait KeyStorage: Send + Sync {
+    /// 読み込み（存在しない場合はNone）
+    async fn read(&self, name: &str) -> Result<Option<Vec<u8>>>;
+
+    /// 書き込み（失敗した場合は既存のデータを変更しないこと）
+    async fn write(&self, name: &str, data: &[u8]) -> Result<()>;
+
+    /// 削除（存在した場合はtrue）
+    async fn delete(&self, name: &str) -> Result<bool>;
+
+    /// 存在確認
+    async fn exists(&self, name: &str) -> Result<bool> {
+        Ok(self.read(name).await?.is_some())
+    }
+
+    /// パーミッション（unixのmode）。ファイル以外のバックエンドではNone
+    async fn permissions(&self, _name: &str) -> Option<u32> {
+        None
+    }
+}
+
+/// ファイルシステムに保存するバックエンド（従来の動作）
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FileStorage;
+
+#[async_trait]
+impl KeyStorage for FileStorage {
+    async fn read(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        match tokio::fs::read(name).await {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", name)),
+        }
+    }
+
+    async fn write(&self, name: &str, data: &[u8]) -> Result<()> {
+        write_atomic(name, data).await
+    }
+
+    async fn delete(&self, name: &str) -> Result<bool> {
+        match tokio::fs::remove_file(name).await {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e).with_context(|| format!("Failed to delete {}", name)),
+        }
+    }
+
+    async fn exists(&self, name: &str) -> Result<bool> {
+        Ok(tokio::fs::metadata(name).await.is_ok())
+    }
+
+    async fn permissions(&self, name: &str) -> Option<u32> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            tokio::fs::metadata(name)
+                .await
+                .ok()
+                .map(|metadata| metadata.permissions().mode() & 0o777)
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = name;
+            None
+        }
+    }
+}
+
+/// メモリ上に保持するバックエンド（テスト・一時的な鍵用）
+/// 上書き・削除時に古いデータはゼロ埋めされる
+#[derive(Default)]
+pub struct MemoryStorage {
+    blobs: Mutex<HashMap<String, Zeroizing<Vec<u8>>>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl KeyStorage for MemoryStorage {
+    async fn read(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        let blobs = self.blobs.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(blobs.get(name).map(|data| data.to_vec()))
+    }
+
+    async fn write(&self, name: &str, data: &[u8]) -> Result<()> {
+        let mut blobs = self.blobs.lock().unwrap_or_else(|e| e.into_inner());
+        blobs.insert(name.to_string(), Zeroizing::new(data.to_vec()));
+        Ok(())
+    }
+
+    async fn delete(&self, name: &str) -> Result<bool> {
+        let mut blobs = self.blobs.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(blobs.remove(name).is_some())
+    }
+}
+
+/// コールバックが返すFuture（flutter_rust_bridgeの`DartFnFuture`と同じ形）
+pub type StorageFuture<T> = Pin<Box<dyn Future<Output = T> + Send + 'static>>;
+
+/// Flutter側が実装するバックエンド（Android Keystoreでラップしたストレージなど）
+///
+/// get: blobを返す（存在しない場合はnull）
+/// put: 保存に成功したらtrue
+/// delete: 存在して削除したらtrue
+pub struct CallbackStorage {
+    get: Box<dyn Fn(String) -> StorageFuture<Option<Vec<u8>>> + Send + Sync>,
+    put: Box<dyn Fn(String, Vec<u8>) -> StorageFuture<bool> + Send + Sync>,
+    delete: Box<dyn Fn(String) -> StorageFuture<bool> + Send + Sync>,
+}
+
+impl CallbackStorage {
+    pub fn new(
+        get: impl Fn(String) -> StorageFuture<Option<Vec<u8>>> + Send + Sync + 'static,
+        put: impl Fn(String, Vec<u8>) -> StorageFuture<bool> + Send + Sync + 'static,
+        delete: impl Fn(String) -> StorageFuture<bool> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            get: Box::new(get),
+            put: Box::new(put),
+            delete: Box::new(delete),
+        }
+    }
+}
+
+#[async_trait]
+impl KeyStorage for CallbackStorage {
+    async fn read(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        Ok((self.get)(name.to_string()).await)
+    }
+
+    async fn write(&self, name: &str, data: &[u8]) -> Result<()> {
+        if !(self.put)(name.to_string(), data.to_vec()).await {
+            anyhow::bail!("Storage callback failed to write {}", name);
+        }
+        Ok(())
+    }
+
+    async fn delete(&self, name: &str) -> Result<bool> {
+        Ok((self.delete)(name.to_string()).await)
+    }
+}
+
+/// `memory://`で共有されるインメモリストレージ
+static SHARED_MEMORY_STORAGE: Lazy<Arc<MemoryStorage>> =
+    Lazy::new(|| Arc::new(MemoryStorage::new()));
+
+/// 登録済みのコールバックバックエンド（backend_id → ストレージ）
+static CALLBACK_STORAGES: Lazy<RwLock<HashMap<String, Arc<CallbackStorage>>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// コールバックバックエンドを登録（同じIDは置き換え）
+pub fn register_callback_storage(backend_id: &str, storage: CallbackStorage) {
+    log_debug!("🔌 Registering callback storage backend: {}", backend_id);
+    CALLBACK_STORAGES
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(backend_id.to_string(), Arc::new(storage));
+}
+
+/// コールバックバックエンドの登録を解除（登録されていた場合はtrue）
+pub fn unregister_callback_storage(backend_id: &str) -> bool {
+    CALLBACK_STORAGES
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(backend_id)
+        .is_some()
+}
+
+/// 登録IDで参照するコールバックバックエンド
+/// 操作ごとに登録を引き直すので、Flutter側のホットリスタートで再登録されても追従する
+struct RegisteredCallbackStorage {
+    backend_id: String,
+}
+
+impl RegisteredCallbackStorage {
+    fn storage(&self) -> Result<Arc<CallbackStorage>> {
+        CALLBACK_STORAGES
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&self.backend_id)
+            .cloned()
+            .with_context(|| format!("Storage backend not registered: {}", self.backend_id))
+    }
+}
+
+#[async_trait]
+impl KeyStorage for RegisteredCallbackStorage {
+    async fn read(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        self.storage()?.read(name).await
+    }
+
+    async fn write(&self, name: &str, data: &[u8]) -> Result<()> {
+        self.storage()?.write(name, data).await
+    }
+
+    async fn delete(&self, name: &str) -> Result<bool> {
+        self.storage()?.delete(name).await
+    }
+}
+
+/// 場所文字列からバックエンドとバックエンド内の名前を解決
+///
+/// - `memory://<name>`: プロセス内で共有されるインメモリストレージ
+/// - `callback://<backend_id>/<name>`: `register_callback_storage`で登録したバックエンド
+/// - それ以外: ファイルパス
+pub fn resolve_storage(location: &str) -> (Arc<dyn KeyStorage>, String) {
+    if let Some(name) = location.strip_prefix(MEMORY_SCHEME) {
+        return (SHARED_MEMORY_STORAGE.clone(), name.to_string());
+    }
+    if let Some(rest) = location.strip_prefix(CALLBACK_SCHEME) {
+        let (backend_id, name) = rest.split_once('/').unwrap_or((rest, "key"));
+        let storage = RegisteredCallbackStorage {
+            backend_id: backend_id.to_string(),
+        };
+        return (Arc::new(storage), name.to_string());
+    }
+    (Arc::new(FileStorage), location.to_string())
+}
+
+/// 一時ファイル経由でアトミックに書き込む（write → fsync → rename）
+/// 失敗した場合は一時ファイルを削除し、書き込み先は変更されない
+/// unixでは所有者のみ読み書きできるパーミッション（0600）で作成する（残っていた一時ファイルは削除してから作成）
+pub(crate) async fn write_atomic(path: &str, data: &[u8]) -> Result<()> {
+    use tokio::io::AsyncWriteExt;
+
+    let tmp_path = format!("{}.tmp", path);
+
+    let result: Result<()> = async {
+        // 前回のクラッシュで残った一時ファイルはパーミッションが0600とは限らないので作り直す
+        match tokio::fs::remove_file(&tmp_path).await {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e).context("Failed to remove stale temporary file"),
+        }
+
+        let mut options = tokio::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+        let mut file = options
+            .open(&tmp_path)
+            .await
+            .context("Failed to create temporary file")?;
+        file.write_all(data)
+            .await
+            .context("Failed to write temporary file")?;
+        file.sync_all()
+            .await
+            .context("Failed to fsync temporary file")?;
+        drop(file);
+
+        tokio::fs::rename(&tmp_path, path)
+            .await
+            .context("Failed to replace file")?;
+
+        // renameをディレクトリに永続化（失敗してもデータ自体は置き換え済み）
+        #[cfg(unix)]
+        if let Some(parent) = std::path::Path::new(path).parent() {
+            if let Ok(dir) = tokio::fs::File::open(parent).await {
+                let _ = dir.sync_all().await;
+            }
+        }
+
+        Ok(())
+    }
+    .await;
+
+    if result.is_err() {
+        let _ = tokio::fs::remove_file(&tmp_path).await;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_memory_storage_roundtrip() {
+        let storage = MemoryStorage::new();
+        assert_eq!(storage.read("key").await.unwrap(), None);
+
+        storage.write("key", b"data").await.unwrap();
+        assert_eq!(storage.read("key").await.unwrap(), Some(b"data".to_vec()));
+        assert!(storage.exists("key").await.unwrap());
+
+        assert!(storage.delete("key").await.unwrap());
+        assert!(!storage.delete("key").await.unwrap());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_write_atomic_replaces_stale_tmp_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("key").to_string_lossy().to_string();
+        let tmp_path = format!("{}.tmp", path);
+        std::fs::write(&tmp_path, b"stale").unwrap();
+        std::fs::set_permissions(&tmp_path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        write_atomic(&path, b"data").await.unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"data");
+        assert_eq!(
+            std::fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_callback_storage() {
+        let blobs = Arc::new(Mutex::new(HashMap::<String, Vec<u8>>::new()));
+        let (get_blobs, put_blobs, delete_blobs) = (blobs.clone(), blobs.clone(), blobs.clone());
+        let storage = CallbackStorage::new(
+            move |name| {
+                let data = get_blobs.lock().unwrap().get(&name).cloned();
+                Box::pin(async move { data })
+            },
+            move |name, data| {
+                put_blobs.lock().unwrap().insert(name, data);
+                Box::pin(async { true })
+            },
+            move |name| {
+                let existed = delete_blobs.lock().unwrap().remove(&name).is_some();
+                Box::pin(async move { existed })
+            },
+        );
+
+        let (resolved, name) = resolve_storage("callback://test-backend/identity.key");
+        assert_eq!(name, "identity.key");
+        // 登録前はエラー
+        assert!(resolved.read(&name).await.is_err());
+
+        register_callback_storage("test-backend", storage);
+        resolved.write(&name, b"blob").await.unwrap();
+        assert_eq!(blobs.lock().unwrap().get("identity.key").unwrap(), b"blob");
+        assert_eq!(resolved.read(&name).await.unwrap(), Some(b"blob".to_vec()));
+        assert!(resolved.delete(&name).await.unwrap());
+
+        assert!(unregister_callback_storage("test-backend"));
+        assert!(resolved.read(&name).await.is_err());
+    }
+}
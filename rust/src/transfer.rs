@@ -0,0 +1,379 @@
+use ::base64::Engine;
+use aes_gcm::{
+    aead::{Aead, KeyInit, OsRng, Payload},
+    Aes256Gcm, Nonce,
+};
+use anyhow::{Context, Result};
+use nostr_sdk::prelude::*;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use zeroize::Zeroizing;
+
+use crate::key_store::{KdfParams, SecureKeyStore};
+use crate::logging::log_info;
+use crate::secret::SecretString;
+
+/// 転送ペイロードのプレフィックス（QRコードの読み取り時に判別するため）
+pub const TRANSFER_PREFIX: &str = "meiso-transfer:";
+
+/// ペイロードのフォーマットバージョン
+const TRANSFER_VERSION: u8 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const SECRET_LEN: usize = 32;
+/// version + expires_at + Argon2パラメータ（m, t, p）+ salt + nonce
+const HEADER_LEN: usize = 1 + 8 + 4 + 4 + 4 + SALT_LEN + NONCE_LEN;
+const TAG_LEN: usize = 16;
+
+/// ワンタイムコードのエントロピー（80bit、XXXX-XXXX-XXXX-XXXX）
+const TRANSFER_CODE_BYTES: usize = 10;
+
+/// 有効期限の既定値
+pub const DEFAULT_TRANSFER_TTL: Duration = Duration::from_secs(5 * 60);
+/// 有効期限の上限（これより長い期限は指定できない）
+pub const MAX_TRANSFER_TTL: Duration = Duration::from_secs(15 * 60);
+/// 端末間の時計のずれとして許容する秒数
+const CLOCK_SKEW_SECS: i64 = 5 * 60;
+
+/// QRコード（バイナリモード・誤り訂正L）に収まる長さの上限
+const MAX_PAYLOAD_CHARS: usize = 2048;
+
+/// 転送ペイロードの有効期限切れ
+#[derive(Debug, thiserror::Error)]
+#[error("Transfer payload expired at {expires_at}. Create a new one on the source device")]
+pub struct TransferExpired {
+    /// 有効期限（UNIX秒）
+    pub expires_at: i64,
+}
+
+/// ワンタイムコードが違う（またはペイロードが改ざんされている）
+#[derive(Debug, thiserror::Error)]
+#[error("Failed to decrypt transfer payload (wrong code?)")]
+pub struct WrongTransferCode;
+
+/// 作成した転送ペイロード
+#[derive(Debug)]
+pub struct TransferPayload {
+    /// QRコードに表示する文字列（`meiso-transfer:` + base64url）
+    pub payload: String,
+    /// 移行先で入力するワンタイムコード（QRコードとは別に表示する）
+    pub code: SecretString,
+    /// 有効期限（UNIX秒）
+    pub expires_at: i64,
+}
+
+/// 転送ペイロードの中身
+#[derive(Debug)]
+pub struct TransferContents {
+    pub secret_key: SecretString,
+    pub public_key_hex: String,
+    pub relays: Vec<String>,
+    /// アプリ設定（JSON）
+    pub settings_json: Option<String>,
+}
+
+/// 秘密鍵以外の中身（QRコードに収まるようキー名を短くする）
+#[derive(Serialize, Deserialize)]
+struct TransferBody {
+    #[serde(rename = "r")]
+    relays: Vec<String>,
+    #[serde(rename = "s", default, skip_serializing_if = "Option::is_none")]
+    settings: Option<serde_json::Value>,
+}
+
+/// ペイロードのヘッダー（AAD）
+struct TransferHeader {
+    expires_at: i64,
+    kdf: KdfParams,
+    salt: [u8; SALT_LEN],
+    nonce: [u8; NONCE_LEN],
+}
+
+impl TransferHeader {
+    fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(HEADER_LEN);
+        out.push(TRANSFER_VERSION);
+        out.extend_from_slice(&self.expires_at.to_le_bytes());
+        out.extend_from_slice(&self.kdf.memory_kib.to_le_bytes());
+        out.extend_from_slice(&self.kdf.iterations.to_le_bytes());
+        out.extend_from_slice(&self.kdf.parallelism.to_le_bytes());
+        out.extend_from_slice(&self.salt);
+        out.extend_from_slice(&self.nonce);
+        out
+    }
+
+    fn decode(data: &[u8]) -> Result<Self> {
+        if data.len() < HEADER_LEN + TAG_LEN + SECRET_LEN {
+            anyhow::bail!("Transfer payload is too short");
+        }
+        if data[0] != TRANSFER_VERSION {
+            anyhow::bail!("Unsupported transfer payload version: {}", data[0]);
+        }
+        let u32_at =
+            |offset: usize| u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
+        let kdf = KdfParams {
+            memory_kib: u32_at(9),
+            iterations: u32_at(13),
+            parallelism: u32_at(17),
+        };
+        kdf.validate()?;
+        Ok(Self {
+            expires_at: i64::from_le_bytes(data[1..9].try_into().unwrap()),
+            kdf,
+            salt: data[21..21 + SALT_LEN].try_into().unwrap(),
+            nonce: data[21 + SALT_LEN..HEADER_LEN].try_into().unwrap(),
+        })
+    }
+}
+
+fn unix_now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// ワンタイムコードを生成（XXXX-XXXX-XXXX-XXXX）
+fn generate_transfer_code() -> SecretString {
+    let mut entropy = Zeroizing::new([0u8; TRANSFER_CODE_BYTES]);
+    OsRng.fill_bytes(entropy.as_mut_slice());
+    let encoded = Zeroizing::new(crate::base32::encode(entropy.as_slice()));
+    SecretString::from(crate::base32::group(&encoded, 4))
+}
+
+/// 入力されたコードを正規化（小文字・区切り・書き間違えやすい文字を吸収）
+fn normalize_transfer_code(code: &str) -> Result<Zeroizing<String>> {
+    let entropy = Zeroizing::new(
+        crate::base32::decode(code).map_err(|e| anyhow::anyhow!("Invalid transfer code: {}", e))?,
+    );
+    if entropy.len() != TRANSFER_CODE_BYTES {
+        anyhow::bail!("Invalid transfer code length");
+    }
+    Ok(Zeroizing::new(crate::base32::encode(&entropy)))
+}
+
+/// 転送ペイロードを作成
+///
+/// 秘密鍵・リレーリスト・設定をワンタイムコードから導出した鍵（Argon2id）で
+/// AES-256-GCM暗号化する。有効期限はAADに含まれるため改ざんできない
+pub fn create_transfer_payload(
+    secret_key: &SecretString,
+    relays: Vec<String>,
+    settings_json: Option<String>,
+    ttl: Duration,
+) -> Result<TransferPayload> {
+    create_transfer_payload_at(secret_key, relays, settings_json, ttl, unix_now())
+}
+
+fn create_transfer_payload_at(
+    secret_key: &SecretString,
+    relays: Vec<String>,
+    settings_json: Option<String>,
+    ttl: Duration,
+    now: i64,
+) -> Result<TransferPayload> {
+    if ttl.is_zero() || ttl > MAX_TRANSFER_TTL {
+        anyhow::bail!(
+            "Transfer payload lifetime must be between 1 and {} seconds",
+            MAX_TRANSFER_TTL.as_secs()
+        );
+    }
+    let settings = settings_json
+        .map(|json| serde_json::from_str::<serde_json::Value>(&json))
+        .transpose()
+        .context("Invalid settings JSON")?;
+    let body = serde_json::to_vec(&TransferBody { relays, settings })?;
+
+    let mut plaintext = Zeroizing::new(Vec::with_capacity(SECRET_LEN + body.len()));
+    plaintext.extend_from_slice(crate::shamir::secret_key_bytes(secret_key)?.as_slice());
+    plaintext.extend_from_slice(&body);
+
+    let mut header = TransferHeader {
+        expires_at: now + ttl.as_secs() as i64,
+        kdf: KdfParams::DEFAULT,
+        salt: [0u8; SALT_LEN],
+        nonce: [0u8; NONCE_LEN],
+    };
+    OsRng.fill_bytes(&mut header.salt);
+    OsRng.fill_bytes(&mut header.nonce);
+
+    let code = generate_transfer_code();
+    let canonical = normalize_transfer_code(code.expose_secret())?;
+    let key = SecureKeyStore::derive_key_from_password(&canonical, &header.salt, &header.kdf)?;
+
+    let mut data = header.encode();
+    let ciphertext = Aes256Gcm::new((&*key).into())
+        .encrypt(
+            &Nonce::from(header.nonce),
+            Payload {
+                msg: &plaintext,
+                aad: &data,
+            },
+        )
+        .map_err(|e| anyhow::anyhow!("Failed to encrypt transfer payload: {:?}", e))?;
+    data.extend_from_slice(&ciphertext);
+
+    let payload = format!(
+        "{}{}",
+        TRANSFER_PREFIX,
+        ::base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(&data)
+    );
+    if payload.len() > MAX_PAYLOAD_CHARS {
+        anyhow::bail!(
+            "Transfer payload is too large for a QR code ({} > {} characters). Reduce the relay list or settings",
+            payload.len(),
+            MAX_PAYLOAD_CHARS
+        );
+    }
+
+    log_info!(
+        "📦 Transfer payload created ({} characters, expires at {})",
+        payload.len(),
+        header.expires_at
+    );
+    Ok(TransferPayload {
+        payload,
+        code,
+        expires_at: header.expires_at,
+    })
+}
+
+/// 転送ペイロードを開く
+///
+/// 有効期限切れの場合は鍵導出の前に`TransferExpired`を返す
+pub fn open_transfer_payload(payload: &str, code: &str) -> Result<TransferContents> {
+    open_transfer_payload_at(payload, code, unix_now())
+}
+
+fn open_transfer_payload_at(payload: &str, code: &str, now: i64) -> Result<TransferContents> {
+    let encoded = payload
+        .trim()
+        .strip_prefix(TRANSFER_PREFIX)
+        .context("Not a Meiso transfer payload")?;
+    if encoded.len() > MAX_PAYLOAD_CHARS {
+        anyhow::bail!("Transfer payload is too large");
+    }
+    let data = ::base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(encoded)
+        .context("Invalid transfer payload encoding")?;
+    let header = TransferHeader::decode(&data)?;
+
+    if now > header.expires_at {
+        return Err(TransferExpired {
+            expires_at: header.expires_at,
+        }
+        .into());
+    }
+    if header.expires_at > now + MAX_TRANSFER_TTL.as_secs() as i64 + CLOCK_SKEW_SECS {
+        anyhow::bail!("Transfer payload expiry is too far in the future (check the device clocks)");
+    }
+
+    let canonical = normalize_transfer_code(code)?;
+    let key = SecureKeyStore::derive_key_from_password(&canonical, &header.salt, &header.kdf)?;
+    let plaintext = Zeroizing::new(
+        Aes256Gcm::new((&*key).into())
+            .decrypt(
+                &Nonce::from(header.nonce),
+                Payload {
+                    msg: &data[HEADER_LEN..],
+                    aad: &data[..HEADER_LEN],
+                },
+            )
+            .map_err(|_| WrongTransferCode)?,
+    );
+    if plaintext.len() < SECRET_LEN {
+        anyhow::bail!("Transfer payload is corrupted");
+    }
+
+    let secret = SecretKey::from_slice(&plaintext[..SECRET_LEN])
+        .map_err(|e| anyhow::anyhow!("Transfer payload contains an invalid key: {}", e))?;
+    let keys = Keys::new(secret);
+    let body: TransferBody = serde_json::from_slice(&plaintext[SECRET_LEN..])
+        .context("Transfer payload is corrupted")?;
+
+    log_info!("📦 Transfer payload opened ({} relays)", body.relays.len());
+    Ok(TransferContents {
+        secret_key: SecretString::from(keys.secret_key().to_secret_hex()),
+        public_key_hex: keys.public_key().to_hex(),
+        relays: body.relays,
+        settings_json: body.settings.map(|settings| settings.to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transfer_roundtrip() {
+        let keys = Keys::generate();
+        let secret_key = SecretString::from(keys.secret_key().to_secret_hex());
+        let relays = vec![
+            "wss://relay.damus.io".to_string(),
+            "wss://nos.lol".to_string(),
+        ];
+        let settings = r#"{"dark_mode":true,"week_start_day":1}"#.to_string();
+
+        let created = create_transfer_payload(
+            &secret_key,
+            relays.clone(),
+            Some(settings),
+            DEFAULT_TRANSFER_TTL,
+        )
+        .unwrap();
+        assert!(created.payload.starts_with(TRANSFER_PREFIX));
+        assert!(created.payload.len() < 400);
+
+        // 入力の揺れ（小文字・区切りなし）を許容
+        let typed = created.code.expose_secret().to_lowercase().replace('-', "");
+        let contents = open_transfer_payload(&created.payload, &typed).unwrap();
+        assert_eq!(
+            contents.secret_key.expose_secret(),
+            secret_key.expose_secret()
+        );
+        assert_eq!(contents.public_key_hex, keys.public_key().to_hex());
+        assert_eq!(contents.relays, relays);
+        let settings: serde_json::Value =
+            serde_json::from_str(&contents.settings_json.unwrap()).unwrap();
+        assert_eq!(settings["week_start_day"], 1);
+
+        let wrong_code = generate_transfer_code();
+        let err = open_transfer_payload(&created.payload, wrong_code.expose_secret()).unwrap_err();
+        assert!(err.downcast_ref::<WrongTransferCode>().is_some());
+    }
+
+    #[test]
+    fn test_transfer_expiry_and_tampering() {
+        let secret_key = SecretString::from(Keys::generate().secret_key().to_secret_hex());
+        let now = 1_700_000_000;
+        let created =
+            create_transfer_payload_at(&secret_key, vec![], None, Duration::from_secs(60), now)
+                .unwrap();
+        let code = created.code.expose_secret();
+
+        assert!(open_transfer_payload_at(&created.payload, code, now + 60).is_ok());
+        let err = open_transfer_payload_at(&created.payload, code, now + 61).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<TransferExpired>().unwrap().expires_at,
+            now + 60
+        );
+
+        // 有効期限を書き換えると認証に失敗する
+        let mut data = ::base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(created.payload.strip_prefix(TRANSFER_PREFIX).unwrap())
+            .unwrap();
+        data[1..9].copy_from_slice(&(now + 600).to_le_bytes());
+        let tampered = format!(
+            "{}{}",
+            TRANSFER_PREFIX,
+            ::base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(&data)
+        );
+        let err = open_transfer_payload_at(&tampered, code, now + 120).unwrap_err();
+        assert!(err.downcast_ref::<WrongTransferCode>().is_some());
+
+        assert!(
+            create_transfer_payload(&secret_key, vec![], None, Duration::from_secs(3600)).is_err()
+        );
+    }
+}